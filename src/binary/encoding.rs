@@ -0,0 +1,126 @@
+use crate::binary::BinaryImportError;
+
+pub const MAGIC: &[u8; 4] = b"HIT\0";
+pub const VERSION: u8 = 1;
+
+pub const TAG_NULL: u8 = 0;
+pub const TAG_BOOL: u8 = 1;
+pub const TAG_STRING: u8 = 2;
+pub const TAG_VEC_STRING: u8 = 3;
+pub const TAG_F32: u8 = 4;
+pub const TAG_I32: u8 = 5;
+pub const TAG_DATE: u8 = 6;
+pub const TAG_REFERENCE: u8 = 7;
+pub const TAG_VEC_REFERENCE: u8 = 8;
+pub const TAG_SUBOBJECT: u8 = 9;
+pub const TAG_VEC_SUBOBJECTS: u8 = 10;
+
+pub struct BinaryWriter {
+    bytes: Vec<u8>,
+}
+
+impl BinaryWriter {
+    pub fn new() -> Self {
+        BinaryWriter { bytes: vec![] }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn write_bytes(&mut self, value: &[u8]) {
+        self.bytes.extend_from_slice(value);
+    }
+
+    // LEB128 : 7 bits per byte, the high bit is set when more bytes follow
+    pub fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    // zigzag encoding, so that small negative numbers stay small
+    pub fn write_signed(&mut self, value: i64) {
+        self.write_varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_string(&mut self, value: &str) {
+        self.write_varint(value.len() as u64);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+}
+
+pub struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        BinaryReader { bytes, position: 0 }
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    pub fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], BinaryImportError> {
+        if self.bytes.len() - self.position < length {
+            return Err(BinaryImportError::UnexpectedEnd());
+        }
+        let bytes = &self.bytes[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, BinaryImportError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_varint(&mut self) -> Result<u64, BinaryImportError> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(BinaryImportError::InvalidNumber())
+    }
+
+    pub fn read_length(&mut self) -> Result<usize, BinaryImportError> {
+        let length = self.read_varint()?;
+        // a length cannot be greater than the remaining data
+        if length > (self.bytes.len() - self.position) as u64 {
+            return Err(BinaryImportError::UnexpectedEnd());
+        }
+        Ok(length as usize)
+    }
+
+    pub fn read_signed(&mut self) -> Result<i64, BinaryImportError> {
+        let value = self.read_varint()?;
+        Ok(((value >> 1) as i64) ^ -((value & 1) as i64))
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, BinaryImportError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn read_string(&mut self) -> Result<String, BinaryImportError> {
+        let length = self.read_length()?;
+        let bytes = self.read_bytes(length)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| BinaryImportError::InvalidString())
+    }
+}
@@ -0,0 +1,99 @@
+use crate::binary::encoding::*;
+use crate::hit_mod::Hit;
+use crate::index::IndexEntryProperty;
+use crate::object_data::{ObjectValue, Reference};
+
+fn write_references(writer: &mut BinaryWriter, references: &[Reference]) {
+    writer.write_varint(references.len() as u64);
+    for reference in references.iter() {
+        writer.write_string(&reference.id);
+    }
+}
+
+fn write_value(writer: &mut BinaryWriter, value: &ObjectValue) {
+    match value {
+        ObjectValue::Null => writer.write_u8(TAG_NULL),
+        ObjectValue::Bool(value) => {
+            writer.write_u8(TAG_BOOL);
+            writer.write_u8(*value as u8);
+        }
+        ObjectValue::String(value) => {
+            writer.write_u8(TAG_STRING);
+            writer.write_string(value);
+        }
+        ObjectValue::VecString(values) => {
+            writer.write_u8(TAG_VEC_STRING);
+            writer.write_varint(values.len() as u64);
+            for value in values.iter() {
+                writer.write_string(value);
+            }
+        }
+        ObjectValue::F32(value) => {
+            writer.write_u8(TAG_F32);
+            writer.write_f32(*value);
+        }
+        ObjectValue::I32(value) => {
+            writer.write_u8(TAG_I32);
+            writer.write_signed(*value as i64);
+        }
+        ObjectValue::Date(value) => {
+            writer.write_u8(TAG_DATE);
+            writer.write_signed(value.get_timestamp());
+            writer.write_varint(value.get_nanoseconds() as u64);
+        }
+        ObjectValue::Reference(reference) => {
+            writer.write_u8(TAG_REFERENCE);
+            writer.write_string(&reference.id);
+        }
+        ObjectValue::VecReference(references) => {
+            writer.write_u8(TAG_VEC_REFERENCE);
+            write_references(writer, references);
+        }
+        ObjectValue::SubObject(reference) => {
+            writer.write_u8(TAG_SUBOBJECT);
+            writer.write_string(&reference.id);
+        }
+        ObjectValue::VecSubObjects(references) => {
+            writer.write_u8(TAG_VEC_SUBOBJECTS);
+            write_references(writer, references);
+        }
+    }
+}
+
+fn write_parent(writer: &mut BinaryWriter, parent: &Option<IndexEntryProperty>) {
+    match parent {
+        None => writer.write_u8(0),
+        Some(parent) => {
+            writer.write_u8(1);
+            writer.write_string(&parent.id);
+            writer.write_string(&parent.property);
+        }
+    }
+}
+
+// The objects are written in the order of the index, with all their values in their order.
+// Layout : magic, format version, schema version of the kernel, main id, number of objects,
+// then for each object : id, model, parent, number of values, then (property, type tag, value)
+// for each value.
+// Numbers are varints, strings are prefixed by their length.
+pub fn export_binary(hit: &Hit) -> Result<Vec<u8>, String> {
+    let mut writer = BinaryWriter::new();
+    writer.write_bytes(MAGIC);
+    writer.write_u8(VERSION);
+    writer.write_varint(hit.kernel.get_schema_version() as u64);
+    writer.write_string(hit.get_main_object_id());
+    writer.write_varint(hit.index.iter().count() as u64);
+    for (id, entry) in hit.index.iter() {
+        let model = hit.get_model(id).ok_or("Model not found")?;
+        let entry = entry.borrow();
+        writer.write_string(id);
+        writer.write_string(model.get_name());
+        write_parent(&mut writer, &entry.get_parent());
+        writer.write_varint(entry.data.len() as u64);
+        for (property, value) in entry.data.iter() {
+            writer.write_string(property);
+            write_value(&mut writer, value);
+        }
+    }
+    Ok(writer.into_bytes())
+}
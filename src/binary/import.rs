@@ -0,0 +1,186 @@
+use std::rc::Rc;
+
+use linked_hash_map::LinkedHashMap;
+use serde_json::{json, Map, Value};
+
+use crate::binary::encoding::*;
+use crate::binary::BinaryImportError;
+use crate::hit_mod::{Hit, HitKernel, IndexModelImporter};
+use crate::import::generic_import::{finish_import, import_object_values};
+use crate::index::IndexEntryProperty;
+use crate::json::export::{export_parent, object_value_to_json};
+use crate::json::import::import;
+use crate::json::{check_document_version, JSONImportError};
+use crate::object_data::{DateTimeUtc, ObjectValue, ObjectValues, Reference};
+
+fn read_references(reader: &mut BinaryReader) -> Result<Vec<Reference>, BinaryImportError> {
+    let count = reader.read_length()?;
+    let mut references = vec![];
+    for _ in 0..count {
+        references.push(Reference {
+            id: reader.read_string()?,
+        });
+    }
+    Ok(references)
+}
+
+fn read_date(reader: &mut BinaryReader) -> Result<DateTimeUtc, BinaryImportError> {
+    let timestamp = reader.read_signed()?;
+    let nanoseconds = reader.read_varint()?;
+    if nanoseconds >= 1_000_000_000 {
+        return Err(BinaryImportError::InvalidNumber());
+    }
+    Ok(DateTimeUtc::from_timestamp_nanos(
+        timestamp,
+        nanoseconds as u32,
+    ))
+}
+
+fn read_value(reader: &mut BinaryReader) -> Result<ObjectValue, BinaryImportError> {
+    let tag = reader.read_u8()?;
+    match tag {
+        TAG_NULL => Ok(ObjectValue::Null),
+        TAG_BOOL => Ok(ObjectValue::Bool(reader.read_u8()? != 0)),
+        TAG_STRING => Ok(ObjectValue::String(reader.read_string()?)),
+        TAG_VEC_STRING => {
+            let count = reader.read_length()?;
+            let mut values = vec![];
+            for _ in 0..count {
+                values.push(reader.read_string()?);
+            }
+            Ok(ObjectValue::VecString(values))
+        }
+        TAG_F32 => Ok(ObjectValue::F32(reader.read_f32()?)),
+        TAG_I32 => {
+            let value = reader.read_signed()?;
+            if value < i32::MIN as i64 || value > i32::MAX as i64 {
+                return Err(BinaryImportError::InvalidNumber());
+            }
+            Ok(ObjectValue::I32(value as i32))
+        }
+        TAG_DATE => Ok(ObjectValue::Date(read_date(reader)?)),
+        TAG_REFERENCE => Ok(ObjectValue::Reference(Reference {
+            id: reader.read_string()?,
+        })),
+        TAG_VEC_REFERENCE => Ok(ObjectValue::VecReference(read_references(reader)?)),
+        TAG_SUBOBJECT => Ok(ObjectValue::SubObject(Reference {
+            id: reader.read_string()?,
+        })),
+        TAG_VEC_SUBOBJECTS => Ok(ObjectValue::VecSubObjects(read_references(reader)?)),
+        _ => Err(BinaryImportError::InvalidValueType(tag)),
+    }
+}
+
+fn read_parent(
+    reader: &mut BinaryReader,
+) -> Result<Option<IndexEntryProperty>, BinaryImportError> {
+    match reader.read_u8()? {
+        0 => Ok(None),
+        _ => Ok(Some(IndexEntryProperty {
+            id: reader.read_string()?,
+            property: reader.read_string()?,
+        })),
+    }
+}
+
+fn import_entry(
+    reader: &mut BinaryReader,
+    kernel: Rc<HitKernel>,
+    new_index: &mut IndexModelImporter,
+) -> Result<(), BinaryImportError> {
+    let id = reader.read_string()?;
+    let model = kernel
+        .get_model(&reader.read_string()?)
+        .map_err(BinaryImportError::HitError)?;
+    let parent = read_parent(reader)?;
+    let count = reader.read_length()?;
+    let mut values: ObjectValues = LinkedHashMap::new();
+    for _ in 0..count {
+        let property = reader.read_string()?;
+        values.insert(property, read_value(reader)?);
+    }
+    import_object_values(model, id, parent, new_index, values)
+        .map_err(BinaryImportError::HitError)
+}
+
+// the object as it is written by the JSON export, without reading its model
+fn read_json_entry(reader: &mut BinaryReader) -> Result<Value, BinaryImportError> {
+    let id = reader.read_string()?;
+    let model = reader.read_string()?;
+    let parent = read_parent(reader)?;
+    let count = reader.read_length()?;
+    let mut data = Map::new();
+    for _ in 0..count {
+        let property = reader.read_string()?;
+        let value = read_value(reader)?;
+        if value != ObjectValue::Null {
+            let value = object_value_to_json(&value)
+                .map_err(|_error| BinaryImportError::InvalidNumber())?;
+            data.insert(property, value);
+        }
+    }
+    Ok(json!({
+        "model": model,
+        "id": id,
+        "data": data,
+        "parent": export_parent(parent),
+    }))
+}
+
+// The models of an older document may no longer be in the kernel : the objects are
+// converted to a JSON document, which the JSON import migrates.
+fn import_older_document(
+    reader: &mut BinaryReader,
+    kernel: Rc<HitKernel>,
+    version: u32,
+) -> Result<Hit, BinaryImportError> {
+    let id = reader.read_string()?;
+    let count = reader.read_length()?;
+    let mut data = vec![];
+    for _ in 0..count {
+        data.push(read_json_entry(reader)?);
+    }
+    if !reader.is_at_end() {
+        return Err(BinaryImportError::TrailingData());
+    }
+    let document = json!({ "id": id, "version": version, "data": data });
+    import(&document, kernel).map_err(|error| match error {
+        JSONImportError::HitError(error) => BinaryImportError::HitError(error),
+        error => BinaryImportError::MigrationError(format!("{:?}", error)),
+    })
+}
+
+// Imports data exported by export_binary. The objects go through the importer,
+// so that the init and after import plugins are called as for the JSON import.
+pub fn import_binary(bytes: &[u8], kernel: Rc<HitKernel>) -> Result<Hit, BinaryImportError> {
+    let mut reader = BinaryReader::new(bytes);
+    if reader.read_bytes(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+        return Err(BinaryImportError::InvalidHeader());
+    }
+    let version = reader.read_u8()?;
+    if version != VERSION {
+        return Err(BinaryImportError::UnsupportedVersion(version));
+    }
+    let schema_version = reader.read_varint()?;
+    if schema_version < kernel.get_schema_version() as u64
+        && check_document_version(schema_version as u32, kernel.as_ref()).is_ok()
+    {
+        return import_older_document(&mut reader, kernel, schema_version as u32);
+    }
+    if schema_version != kernel.get_schema_version() as u64 {
+        return Err(BinaryImportError::InvalidSchemaVersion(
+            schema_version,
+            kernel.get_schema_version(),
+        ));
+    }
+    let id = reader.read_string()?;
+    let mut new_index = IndexModelImporter::new(&id, kernel.clone());
+    let count = reader.read_length()?;
+    for _ in 0..count {
+        import_entry(&mut reader, kernel.clone(), &mut new_index)?;
+    }
+    if !reader.is_at_end() {
+        return Err(BinaryImportError::TrailingData());
+    }
+    finish_import(new_index, kernel).map_err(BinaryImportError::HitError)
+}
@@ -0,0 +1,189 @@
+mod encoding;
+pub mod export;
+pub mod import;
+
+use crate::HitError;
+
+#[derive(Debug, PartialEq)]
+pub enum BinaryImportError {
+    HitError(HitError),
+    InvalidHeader(),
+    UnsupportedVersion(u8),
+    // the schema version of the document, and the version of the kernel
+    InvalidSchemaVersion(u64, u32),
+    UnexpectedEnd(),
+    TrailingData(),
+    InvalidString(),
+    InvalidNumber(),
+    InvalidValueType(u8),
+    // an older document could not be migrated to the schema version of the kernel
+    MigrationError(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary::export::export_binary;
+    use crate::binary::import::import_binary;
+    use crate::binary::BinaryImportError;
+    use crate::test_kernel::{
+        create_hit_with_all_types as create_hit, create_test_kernel,
+        create_test_kernel_with_all_types,
+    };
+    use crate::{
+        AfterImportPlugin, Hit, HitError, IndexEntryProperty, InitEntryPlugin, Kernel,
+        KernelBuilder, Model, ObjectValue, ObjectValues, Plugins,
+    };
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Dump = Vec<(String, Vec<(String, ObjectValue)>, Option<IndexEntryProperty>)>;
+
+    // the values in their order
+    fn dump(hit: &Hit) -> Dump {
+        hit.index
+            .iter()
+            .map(|(id, entry)| {
+                let entry = entry.borrow();
+                (
+                    id.clone(),
+                    entry
+                        .data
+                        .iter()
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect(),
+                    entry.get_parent(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_should_export_and_import_every_value_exactly() {
+        let hit = create_hit();
+        let bytes = export_binary(&hit).expect("Error");
+
+        let imported =
+            import_binary(&bytes, Rc::new(create_test_kernel_with_all_types())).expect("Error");
+
+        assert_eq!(imported.get_main_object_id(), "id");
+        assert_eq!(dump(&imported), dump(&hit));
+        assert_eq!(
+            imported.get_references("id3").expect("Error").len(),
+            hit.get_references("id3").expect("Error").len()
+        );
+        assert_eq!(export_binary(&imported).expect("Error"), bytes);
+    }
+
+    #[test]
+    fn it_should_be_smaller_than_json() {
+        let hit = create_hit();
+        let bytes = export_binary(&hit).expect("Error");
+        let json = crate::export(&hit).expect("Error").to_string();
+        assert!(bytes.len() < json.len());
+    }
+
+    #[test]
+    fn it_should_refuse_invalid_data() {
+        let kernel = Rc::new(create_test_kernel_with_all_types());
+        let bytes = export_binary(&create_hit()).expect("Error");
+
+        let result = import_binary(b"{\"id\": \"id\"}", kernel.clone());
+        assert_eq!(result.err(), Some(BinaryImportError::InvalidHeader()));
+
+        let mut future_version = bytes.clone();
+        future_version[4] = 99;
+        let result = import_binary(&future_version, kernel.clone());
+        assert_eq!(result.err(), Some(BinaryImportError::UnsupportedVersion(99)));
+
+        let result = import_binary(&bytes[..bytes.len() - 3], kernel.clone());
+        assert_eq!(result.err(), Some(BinaryImportError::UnexpectedEnd()));
+
+        let mut trailing_data = bytes.clone();
+        trailing_data.push(0);
+        let result = import_binary(&trailing_data, kernel.clone());
+        assert_eq!(result.err(), Some(BinaryImportError::TrailingData()));
+
+        // the document must have the version of the models of the kernel
+        let newer_kernel = KernelBuilder::new()
+            .set_schema_version(1)
+            .build()
+            .expect("Error");
+        let result = import_binary(&bytes, Rc::new(newer_kernel));
+        assert_eq!(
+            result.err(),
+            Some(BinaryImportError::InvalidSchemaVersion(0, 1))
+        );
+
+        // the models must exist in the kernel
+        let result = import_binary(&bytes, Rc::new(create_test_kernel()));
+        assert_eq!(
+            result.err(),
+            Some(BinaryImportError::HitError(HitError::ModelDoesNotExist(
+                "test/values".into()
+            )))
+        );
+    }
+
+    #[derive(Default)]
+    struct CountPlugin {
+        entries: Vec<String>,
+        imports: usize,
+    }
+
+    impl InitEntryPlugin for CountPlugin {
+        fn on_init_add_entry(
+            &mut self,
+            _model: Rc<Model>,
+            id: &str,
+            _data: ObjectValues,
+            _parent: Option<IndexEntryProperty>,
+        ) {
+            self.entries.push(id.into());
+        }
+    }
+
+    impl AfterImportPlugin for CountPlugin {
+        fn after_import(&mut self, _hit: &Hit) -> Result<(), HitError> {
+            self.imports += 1;
+            Ok(())
+        }
+    }
+
+    struct PluginKernel {
+        kernel: crate::test_kernel::TestKernel,
+        plugin: Rc<RefCell<CountPlugin>>,
+    }
+
+    impl Kernel for PluginKernel {
+        fn get_model(&self, name: &str) -> Result<Rc<Model>, HitError> {
+            self.kernel.get_model(name)
+        }
+        fn get_instantiable_models(&self) -> Vec<&Model> {
+            self.kernel.get_instantiable_models()
+        }
+        fn get_plugins(&self) -> Plugins {
+            let mut plugins = Plugins::new();
+            plugins.init_plugins.push(self.plugin.clone());
+            plugins.after_import_plugins.push(self.plugin.clone());
+            plugins
+        }
+        fn get_models(&self) -> Vec<String> {
+            self.kernel.get_models()
+        }
+    }
+
+    #[test]
+    fn it_should_call_the_import_plugins() {
+        let bytes = export_binary(&create_hit()).expect("Error");
+        let plugin = Rc::new(RefCell::new(CountPlugin::default()));
+        let kernel = PluginKernel {
+            kernel: create_test_kernel_with_all_types(),
+            plugin: plugin.clone(),
+        };
+
+        import_binary(&bytes, Rc::new(kernel)).expect("Error");
+
+        assert_eq!(plugin.borrow().entries, vec!["id", "id2", "id3", "id4"]);
+        assert_eq!(plugin.borrow().imports, 1);
+    }
+}
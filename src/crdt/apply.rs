@@ -0,0 +1,330 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use super::state::{to_property, Changes, CrdtState, Property};
+use crate::field_types::get_linked_ids;
+use crate::index::IndexEntryProperty;
+use crate::model::FieldKind;
+use crate::object_data::{Id, ObjectValue, ObjectValues};
+use crate::{Hit, HitError, HitKernel};
+
+// Updates the document with the changes of the state. The objects and the fields that
+// were not touched by the changes are left as they are, and the updates go through the
+// Hit API, so that the plugins and the listeners see them as any other edit.
+pub(crate) fn apply_changes(
+    hit: &mut Hit,
+    state: &CrdtState,
+    changes: &Changes,
+) -> Result<(), HitError> {
+    let kernel = hit.kernel.clone();
+    hit.transaction(|hit| {
+        let mut update = DocumentUpdate {
+            hit,
+            state,
+            kernel,
+            lists: HashSet::new(),
+            properties: changes.properties.clone(),
+        };
+        update.apply(changes.objects.clone())
+    })
+}
+
+struct DocumentUpdate<'a> {
+    hit: &'a mut Hit,
+    state: &'a CrdtState,
+    kernel: Rc<HitKernel>,
+    // the subobject lists to put in order
+    lists: HashSet<Property>,
+    // the fields to compare with the state
+    properties: HashSet<Property>,
+}
+
+// the object that follows `id` in a list
+fn get_next<'a>(list: &'a [Id], id: &str) -> Option<&'a Id> {
+    list.iter().skip_while(|other| *other != id).nth(1)
+}
+
+fn move_id(list: &mut Vec<Id>, id: &str, before_id: Option<&Id>) {
+    list.retain(|other| other != id);
+    let index = before_id
+        .and_then(|before_id| list.iter().position(|other| other == before_id))
+        .unwrap_or(list.len());
+    list.insert(index, id.to_string());
+}
+
+fn get_ids(value: Option<ObjectValue>) -> Vec<Id> {
+    get_linked_ids(value)
+        .into_iter()
+        .map(|reference| reference.id)
+        .collect()
+}
+
+impl<'a> DocumentUpdate<'a> {
+    fn apply(&mut self, objects: HashSet<Id>) -> Result<(), HitError> {
+        let objects = self.add_slot_objects(objects);
+        let (mut placed, removed): (Vec<Id>, Vec<Id>) =
+            objects.into_iter().partition(|id| self.is_visible(id));
+        // parents first, so that an object is never moved inside its own subtree
+        placed.sort_by_key(|id| (self.state.get_depth(id), id.clone()));
+        for id in placed.iter() {
+            self.place(id)?;
+        }
+        let mut lists: Vec<Property> = self.lists.drain().collect();
+        lists.sort();
+        for (id, property) in lists.iter() {
+            self.sort_list(id, property)?;
+        }
+        // the references can be set once every object is in the document
+        let mut properties: Vec<Property> = self.properties.drain().collect();
+        properties.sort();
+        for (id, property) in properties.iter() {
+            self.update_value(id, property)?;
+        }
+        for id in removed.iter() {
+            self.remove(id)?;
+        }
+        Ok(())
+    }
+
+    fn is_visible(&self, id: &str) -> bool {
+        self.state.is_visible(id, &*self.kernel)
+    }
+
+    fn get_field_kind(&self, id: &str, property: &str) -> Option<FieldKind> {
+        let model = self.kernel.get_model(self.state.get_model(id)?).ok()?;
+        let kind = model.get_field(property)?.borrow().get_kind();
+        Some(kind)
+    }
+
+    fn is_slot(&self, parent: &IndexEntryProperty) -> bool {
+        self.get_field_kind(&parent.id, &parent.property) == Some(FieldKind::Subobject)
+    }
+
+    // the first object of a single subobject field hides the others :
+    // when one of them changes, the others may have to be added or removed too
+    fn add_slot_objects(&self, objects: HashSet<Id>) -> HashSet<Id> {
+        let mut result = objects.clone();
+        for id in objects.iter() {
+            let parents = self
+                .state
+                .get_parent(id)
+                .cloned()
+                .into_iter()
+                .chain(self.hit.get_parent(id));
+            for parent in parents {
+                if self.is_slot(&parent) {
+                    result.extend(
+                        self.state
+                            .get_children(&parent)
+                            .into_iter()
+                            .map(|(id, _)| id),
+                    );
+                }
+            }
+        }
+        result
+    }
+
+    fn place(&mut self, id: &str) -> Result<(), HitError> {
+        // the main object has no parent
+        let parent = match self.state.get_parent(id) {
+            Some(parent) => parent.clone(),
+            None => return Ok(()),
+        };
+        if self.hit.get(id).is_none() {
+            return self.insert_subtree(id, parent);
+        }
+        self.lists.insert(to_property(&parent));
+        let current = self.hit.get_parent(id);
+        if current.as_ref() == Some(&parent) {
+            return Ok(());
+        }
+        if let Some(current) = current {
+            self.lists.insert(to_property(&current));
+        }
+        self.free_slot(&parent, id)?;
+        self.hit.move_object(id, parent, None)
+    }
+
+    // adds an object that was not part of the document, with its subobjects
+    fn insert_subtree(&mut self, id: &str, parent: IndexEntryProperty) -> Result<(), HitError> {
+        let model = match self.state.get_model(id) {
+            Some(model) => self.kernel.get_model(model)?,
+            None => return Ok(()),
+        };
+        let mut values = ObjectValues::new();
+        let mut lists = vec![];
+        for (property, field) in model.fields.iter() {
+            match field.borrow().get_kind() {
+                FieldKind::Subobject | FieldKind::SubobjectArray => {
+                    lists.push(IndexEntryProperty {
+                        id: id.to_string(),
+                        property: property.clone(),
+                    })
+                }
+                FieldKind::Computed | FieldKind::Backlinks => {}
+                FieldKind::Reference | FieldKind::ReferenceArray => {
+                    self.properties.insert((id.to_string(), property.clone()));
+                }
+                _ => {
+                    if let Some(value) = self.state.get_value(id, property, false, |_| true) {
+                        values.insert(property.clone(), value);
+                    }
+                }
+            }
+        }
+        self.free_slot(&parent, id)?;
+        self.hit
+            .insert(model.get_name(), id, values, parent.clone(), None)?;
+        self.lists.insert(to_property(&parent));
+        // the references to the object were ignored while it was not part of the document
+        self.properties.extend(self.state.get_referrers(id));
+        for list in lists.iter() {
+            self.lists.insert(to_property(list));
+            for child in self.state.get_visible_children(list, &*self.kernel) {
+                if self.hit.get(&child).is_none() {
+                    self.insert_subtree(&child, list.clone())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The object of a single subobject field is removed before another one takes its place.
+    // If it was moved to another field, it is added there again.
+    fn free_slot(&mut self, parent: &IndexEntryProperty, id: &str) -> Result<(), HitError> {
+        if !self.is_slot(parent) {
+            return Ok(());
+        }
+        match self.hit.get_value(&parent.id, &parent.property) {
+            Some(ObjectValue::SubObject(occupant)) if occupant.id != id => {
+                self.remove(&occupant.id)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // moves the objects of a subobject list that are not followed by the expected object
+    fn sort_list(&mut self, id: &str, property: &str) -> Result<(), HitError> {
+        if self.hit.get(id).is_none()
+            || self.get_field_kind(id, property) != Some(FieldKind::SubobjectArray)
+        {
+            return Ok(());
+        }
+        let parent = IndexEntryProperty {
+            id: id.to_string(),
+            property: property.to_string(),
+        };
+        let expected = self.state.get_visible_children(&parent, &*self.kernel);
+        let expected_ids: HashSet<&Id> = expected.iter().collect();
+        // the objects that are not expected are removed afterwards
+        let mut current = get_ids(self.hit.get_value(id, property));
+        current.retain(|child| expected_ids.contains(child));
+        for (index, child) in expected.iter().enumerate().rev() {
+            let next = expected.get(index + 1);
+            if get_next(&current, child) == next {
+                continue;
+            }
+            self.hit.move_object(child, parent.clone(), next.cloned())?;
+            move_id(&mut current, child, next);
+        }
+        Ok(())
+    }
+
+    fn update_value(&mut self, id: &str, property: &str) -> Result<(), HitError> {
+        if self.hit.get(id).is_none() {
+            return Ok(());
+        }
+        let kind = match self.get_field_kind(id, property) {
+            Some(kind) => kind,
+            None => return Ok(()),
+        };
+        let state = self.state;
+        let kernel = self.kernel.clone();
+        let is_visible = |reference: &str| state.is_visible(reference, &*kernel);
+        match kind {
+            FieldKind::Subobject
+            | FieldKind::SubobjectArray
+            | FieldKind::Computed
+            | FieldKind::Backlinks => Ok(()),
+            FieldKind::ReferenceArray => {
+                let expected = get_ids(state.get_value(id, property, true, is_visible));
+                self.update_references(id, property, expected)
+            }
+            _ => {
+                let value = state
+                    .get_value(id, property, false, is_visible)
+                    .unwrap_or(ObjectValue::Null);
+                if self
+                    .hit
+                    .get_value(id, property)
+                    .unwrap_or(ObjectValue::Null)
+                    == value
+                {
+                    return Ok(());
+                }
+                self.hit._set(id, property, value, true)
+            }
+        }
+    }
+
+    fn update_references(
+        &mut self,
+        id: &str,
+        property: &str,
+        expected: Vec<Id>,
+    ) -> Result<(), HitError> {
+        let mut current = get_ids(self.hit.get_value(id, property));
+        if current == expected {
+            return Ok(());
+        }
+        let target = IndexEntryProperty {
+            id: id.to_string(),
+            property: property.to_string(),
+        };
+        for reference in current.iter() {
+            if !expected.contains(reference) {
+                self.hit.remove_reference(reference, target.clone())?;
+            }
+        }
+        current.retain(|reference| expected.contains(reference));
+        for (index, reference) in expected.iter().enumerate().rev() {
+            let next = expected.get(index + 1);
+            if !current.contains(reference) {
+                self.hit
+                    .insert_reference(reference, target.clone(), next.cloned())?;
+            } else if get_next(&current, reference) != next {
+                self.hit
+                    .move_reference(reference, target.clone(), next.cloned())?;
+            } else {
+                continue;
+            }
+            move_id(&mut current, reference, next);
+        }
+        Ok(())
+    }
+
+    // The references to the removed objects are removed first. The state keeps them,
+    // but ignores them while the objects are not part of the document.
+    fn remove(&mut self, id: &str) -> Result<(), HitError> {
+        if self.hit.get(id).is_none() {
+            return Ok(());
+        }
+        let (references, _) = self.hit.find_references_recursive(id)?;
+        for (reference, referrers) in references.iter() {
+            for referrer in referrers.iter() {
+                match self.hit.get_value(&referrer.id, &referrer.property) {
+                    Some(ObjectValue::VecReference(_)) => {
+                        self.hit.remove_reference(reference, referrer.clone())?
+                    }
+                    _ => {
+                        self.hit
+                            ._set(&referrer.id, &referrer.property, ObjectValue::Null, true)?
+                    }
+                }
+            }
+        }
+        self.hit.remove_object(id)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use crate::index::IndexEntryProperty;
+use crate::object_data::{Id, ObjectValue};
+
+// Lamport timestamp : the replica id makes it unique and totally ordered
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Timestamp {
+    pub counter: u64,
+    pub replica: String,
+}
+
+// A change exchanged between replicas. The changes commute, so that the messages can be
+// received in any order and more than once.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CrdtChange {
+    Create {
+        id: Id,
+        model: String,
+    },
+    // the object and its descendants are removed, even if it is moved concurrently
+    Delete {
+        id: Id,
+    },
+    // moves the object in the tree, the latest move that does not create a cycle wins
+    Move {
+        id: Id,
+        parent: IndexEntryProperty,
+        position: Vec<u32>,
+    },
+    // last writer wins register, used for the scalar and reference fields
+    Set {
+        id: Id,
+        property: String,
+        value: ObjectValue,
+    },
+    // the element of a reference list is identified by the timestamp of its insertion
+    InsertElement {
+        id: Id,
+        property: String,
+        reference: Id,
+        position: Vec<u32>,
+    },
+    RemoveElement {
+        id: Id,
+        property: String,
+        element: Timestamp,
+    },
+    MoveElement {
+        id: Id,
+        property: String,
+        element: Timestamp,
+        position: Vec<u32>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CrdtMessage {
+    pub timestamp: Timestamp,
+    pub change: CrdtChange,
+}
@@ -0,0 +1,8 @@
+mod apply;
+mod message;
+mod position;
+mod replica;
+mod state;
+
+pub use message::{CrdtChange, CrdtMessage, Timestamp};
+pub use replica::Replica;
@@ -0,0 +1,61 @@
+// Dense position keys : a key can always be generated between two other keys, so that the
+// elements of a list are ordered by their key without renumbering the others.
+// Equal keys generated concurrently are ordered by the timestamp of the change.
+
+const BASE: u32 = 1 << 16;
+const STEP: u32 = 1 << 8;
+
+// generates a key after `before` and before `after` (None for the end of the list)
+pub(crate) fn key_between(before: Option<&[u32]>, after: Option<&[u32]>) -> Vec<u32> {
+    let before = before.unwrap_or(&[]);
+    let mut bounded = after.is_some();
+    let mut key = vec![];
+    loop {
+        let depth = key.len();
+        let low = before.get(depth).copied().unwrap_or(0);
+        let high = match after {
+            Some(after) if bounded => after.get(depth).copied().unwrap_or(0),
+            _ => BASE,
+        };
+        if high > low + 1 {
+            // leave some room after the key when appending
+            let digit = if !bounded && low + STEP < high {
+                low + STEP
+            } else {
+                low + (high - low) / 2
+            };
+            key.push(digit);
+            return key;
+        }
+        if high == low + 1 {
+            bounded = false;
+        }
+        key.push(low);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_generate_keys_between_two_keys() {
+        let first = key_between(None, None);
+        let last = key_between(Some(&first), None);
+        assert!(first < last);
+
+        let mut keys = vec![first, last];
+        // always inserting at the same place makes the keys longer
+        for _ in 0..40 {
+            let key = key_between(Some(&keys[0]), Some(&keys[1]));
+            assert!(keys[0] < key && key < keys[1]);
+            keys.insert(1, key);
+        }
+        let key = key_between(None, Some(&keys[0]));
+        assert!(key < keys[0]);
+        let key = key_between(Some(&[5]), Some(&[5, 3]));
+        assert!(vec![5] < key && key < vec![5, 3]);
+        let key = key_between(Some(&[5, 7]), Some(&[6]));
+        assert!(vec![5, 7] < key && key < vec![6]);
+    }
+}
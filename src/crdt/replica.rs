@@ -0,0 +1,316 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::apply::apply_changes;
+use super::message::{CrdtChange, CrdtMessage, Timestamp};
+use super::position::key_between;
+use super::state::{Changes, CrdtState};
+use crate::events::FieldListener;
+use crate::index::IndexEntryProperty;
+use crate::object_data::{Id, ObjectValue, ObjectValues};
+use crate::operations::{Operation, OperationRecord};
+use crate::{Hit, HitError};
+
+struct OperationCollector {
+    operations: Vec<Operation>,
+}
+
+impl FieldListener<OperationRecord> for OperationCollector {
+    fn on_update(&mut self, record: &OperationRecord) {
+        self.operations.push(record.operation.clone());
+    }
+    fn get_unique_id(&self) -> &str {
+        "crdt_replica"
+    }
+}
+
+// position of a new element inserted before `before_id`, or at the end of the list
+fn get_position(list: &[(Id, Vec<u32>)], before_id: &Option<Id>) -> Vec<u32> {
+    let index = before_id
+        .as_ref()
+        .and_then(|before_id| list.iter().position(|(id, _)| id == before_id))
+        .unwrap_or(list.len());
+    let before = match index {
+        0 => None,
+        _ => Some(&list[index - 1].1[..]),
+    };
+    let after = list.get(index).map(|(_, position)| &position[..]);
+    // two keys can be equal if they were generated concurrently :
+    // the new element is then placed after both of them
+    match (before, after) {
+        (Some(before), Some(after)) if before >= after => key_between(Some(before), None),
+        _ => key_between(before, after),
+    }
+}
+
+// A copy of the document that can be edited concurrently with the other replicas.
+// The local edits are made through the Hit API and sent as messages, the messages of
+// the other replicas can be received in any order : every replica that received the
+// same messages has the same document.
+pub struct Replica {
+    replica_id: String,
+    clock: u64,
+    state: CrdtState,
+    // the changes of the state that are not applied to the document yet, they are kept
+    // when the document rejects them and applied again by the next `receive` or `edit`
+    changes: Changes,
+    hit: Hit,
+    outbox: Vec<CrdtMessage>,
+}
+
+impl Replica {
+    // every replica must be created from the same document
+    pub fn new(replica_id: &str, hit: &Hit) -> Result<Replica, HitError> {
+        let main_id = hit.get_main_object_id().clone();
+        let entries = hit.get_subtree_entries(&main_id)?;
+        // the initial state is created by an anonymous replica,
+        // so that it is the same on every replica
+        let mut replica = Replica {
+            replica_id: String::new(),
+            clock: 0,
+            state: CrdtState::new(&main_id),
+            changes: Changes::default(),
+            hit: hit.clone(),
+            outbox: vec![],
+        };
+        for entry in entries.iter() {
+            replica.add_object(&entry.model, &entry.id, &entry.data, &entry.parent, &None);
+        }
+        replica.outbox.clear();
+        replica.changes = Changes::default();
+        replica.replica_id = replica_id.to_string();
+        replica.hit = replica.state.materialize(hit.kernel.clone())?;
+        Ok(replica)
+    }
+
+    pub fn get_replica_id(&self) -> &str {
+        &self.replica_id
+    }
+
+    pub fn get_hit(&self) -> &Hit {
+        &self.hit
+    }
+
+    // Applies local edits. They are validated as usual and are not applied if one
+    // of them fails.
+    pub fn edit<F>(&mut self, edits: F) -> Result<(), HitError>
+    where
+        F: FnOnce(&mut Hit) -> Result<(), HitError>,
+    {
+        let collector = Rc::new(RefCell::new(OperationCollector { operations: vec![] }));
+        let listener_id = self.hit.subscribe_operations(collector.clone());
+        let result = self.hit.transaction(edits);
+        self.hit.unsubscribe_operations(&listener_id)?;
+        result?;
+
+        let operations = collector.borrow().operations.clone();
+        for operation in operations.iter() {
+            self.add_operation(operation);
+        }
+        // the document already contains the edits, unless the state resolved them differently
+        self.apply_changes()
+    }
+
+    // the messages of the local edits that were not sent yet
+    pub fn take_messages(&mut self) -> Vec<CrdtMessage> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    // the messages stay in the state even if the document rejects their changes,
+    // see `apply_changes`
+    pub fn receive(&mut self, messages: &[CrdtMessage]) -> Result<(), HitError> {
+        for message in messages.iter() {
+            self.clock = self.clock.max(message.timestamp.counter);
+            self.state.integrate(message, &mut self.changes);
+        }
+        self.apply_changes()
+    }
+
+    // The document is rolled back if a change is rejected (validation, plugin, ...) :
+    // the changes stay pending, so that the document still converges with the state
+    // once the change is accepted.
+    fn apply_changes(&mut self) -> Result<(), HitError> {
+        // the local undo does not cancel the changes of the other replicas
+        self.hit.set_history_paused(true);
+        let result = apply_changes(&mut self.hit, &self.state, &self.changes);
+        self.hit.set_history_paused(false);
+        if result.is_ok() {
+            self.changes = Changes::default();
+        }
+        result
+    }
+
+    fn add_change(&mut self, change: CrdtChange) {
+        self.clock += 1;
+        let message = CrdtMessage {
+            timestamp: Timestamp {
+                counter: self.clock,
+                replica: self.replica_id.clone(),
+            },
+            change,
+        };
+        self.state.integrate(&message, &mut self.changes);
+        self.outbox.push(message);
+    }
+
+    fn add_operation(&mut self, operation: &Operation) {
+        match operation {
+            Operation::Set {
+                id,
+                property,
+                value,
+                ..
+            } => self.set_value(id, property, value),
+            Operation::Insert {
+                model,
+                id,
+                values,
+                parent,
+                before_id,
+            } => self.add_object(model, id, values, &Some(parent.clone()), before_id),
+            Operation::Remove { id, .. } => self.add_change(CrdtChange::Delete { id: id.clone() }),
+            Operation::Move {
+                id,
+                target,
+                before_id,
+                ..
+            } => {
+                let siblings: Vec<(Id, Vec<u32>)> = self
+                    .state
+                    .get_children(target)
+                    .into_iter()
+                    .filter(|(sibling, _)| sibling != id)
+                    .collect();
+                self.add_change(CrdtChange::Move {
+                    id: id.clone(),
+                    parent: target.clone(),
+                    position: get_position(&siblings, before_id),
+                });
+            }
+            Operation::Copy {
+                before_id, entries, ..
+            } => {
+                // the copied objects are listed parents first
+                for (index, entry) in entries.iter().enumerate() {
+                    let before_id = if index == 0 { before_id.clone() } else { None };
+                    self.add_object(&entry.model, &entry.id, &entry.data, &entry.parent, &before_id);
+                }
+            }
+            Operation::InsertReference {
+                id,
+                target,
+                before_id,
+            } => self.insert_element(&target.id, &target.property, id, before_id),
+            Operation::RemoveReference { id, target, .. } => {
+                let element = self
+                    .state
+                    .get_elements(&target.id, &target.property)
+                    .into_iter()
+                    .find(|element| element.reference == *id);
+                if let Some(element) = element {
+                    self.add_change(CrdtChange::RemoveElement {
+                        id: target.id.clone(),
+                        property: target.property.clone(),
+                        element: element.id,
+                    });
+                }
+            }
+            Operation::MoveReference {
+                id,
+                target,
+                before_id,
+                ..
+            } => {
+                let elements = self.state.get_elements(&target.id, &target.property);
+                let element = match elements.iter().find(|element| element.reference == *id) {
+                    Some(element) => element.id.clone(),
+                    None => return,
+                };
+                let list: Vec<(Id, Vec<u32>)> = elements
+                    .into_iter()
+                    .filter(|other| other.id != element)
+                    .map(|other| (other.reference, other.position))
+                    .collect();
+                self.add_change(CrdtChange::MoveElement {
+                    id: target.id.clone(),
+                    property: target.property.clone(),
+                    element,
+                    position: get_position(&list, before_id),
+                });
+            }
+        }
+    }
+
+    fn add_object(
+        &mut self,
+        model: &str,
+        id: &str,
+        values: &ObjectValues,
+        parent: &Option<IndexEntryProperty>,
+        before_id: &Option<Id>,
+    ) {
+        self.add_change(CrdtChange::Create {
+            id: id.to_string(),
+            model: model.to_string(),
+        });
+        if let Some(parent) = parent {
+            let siblings = self.state.get_children(parent);
+            self.add_change(CrdtChange::Move {
+                id: id.to_string(),
+                parent: parent.clone(),
+                position: get_position(&siblings, before_id),
+            });
+        }
+        for (property, value) in values.iter() {
+            self.set_value(id, property, value);
+        }
+    }
+
+    fn insert_element(&mut self, id: &str, property: &str, reference: &str, before_id: &Option<Id>) {
+        let list: Vec<(Id, Vec<u32>)> = self
+            .state
+            .get_elements(id, property)
+            .into_iter()
+            .map(|element| (element.reference, element.position))
+            .collect();
+        self.add_change(CrdtChange::InsertElement {
+            id: id.to_string(),
+            property: property.to_string(),
+            reference: reference.to_string(),
+            position: get_position(&list, before_id),
+        });
+    }
+
+    fn set_value(&mut self, id: &str, property: &str, value: &ObjectValue) {
+        match value {
+            // the subobjects are placed by their own moves
+            ObjectValue::SubObject(_) | ObjectValue::VecSubObjects(_) => {}
+            ObjectValue::VecReference(references) => {
+                self.clear_elements(id, property);
+                for reference in references.iter() {
+                    self.insert_element(id, property, &reference.id, &None);
+                }
+            }
+            _ => {
+                if *value == ObjectValue::Null {
+                    self.clear_elements(id, property);
+                }
+                self.add_change(CrdtChange::Set {
+                    id: id.to_string(),
+                    property: property.to_string(),
+                    value: value.clone(),
+                });
+            }
+        }
+    }
+
+    fn clear_elements(&mut self, id: &str, property: &str) {
+        for element in self.state.get_elements(id, property) {
+            self.add_change(CrdtChange::RemoveElement {
+                id: id.to_string(),
+                property: property.to_string(),
+                element: element.id,
+            });
+        }
+    }
+}
@@ -0,0 +1,496 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Bound::{Excluded, Unbounded};
+use std::rc::Rc;
+
+use linked_hash_map::LinkedHashMap;
+
+use super::message::{CrdtChange, CrdtMessage, Timestamp};
+use crate::import::{import_from_patches_and_entries, AddedEntry};
+use crate::index::IndexEntryProperty;
+use crate::object_data::{Id, ObjectValue, ObjectValues, Reference};
+use crate::{Hit, HitError, HitKernel};
+
+pub(crate) type Property = (Id, String);
+type Place = (IndexEntryProperty, Vec<u32>, Timestamp);
+
+#[derive(Default)]
+struct Sequence {
+    references: HashMap<Timestamp, Id>,
+    // the position of an element is a last writer wins register
+    positions: HashMap<Timestamp, (Timestamp, Vec<u32>)>,
+    removed: HashSet<Timestamp>,
+}
+
+pub(crate) struct Element {
+    pub id: Timestamp,
+    pub reference: Id,
+    pub position: Vec<u32>,
+}
+
+struct MoveRecord {
+    id: Id,
+    parent: IndexEntryProperty,
+    position: Vec<u32>,
+    // the place of the object before the move, restored when the move is undone
+    previous: Option<Place>,
+}
+
+// What a change touched : the objects that may have been added, moved or removed,
+// and the fields whose value may have changed
+#[derive(Default)]
+pub(crate) struct Changes {
+    pub objects: HashSet<Id>,
+    pub properties: HashSet<Property>,
+}
+
+// The state shared by the replicas : every change is kept. The document is loaded
+// from it by `materialize`, then updated with the changes returned by `integrate`.
+pub(crate) struct CrdtState {
+    main_id: Id,
+    models: HashMap<Id, String>,
+    deleted: HashSet<Id>,
+    moves: BTreeMap<Timestamp, MoveRecord>,
+    // the place of each object after the moves, kept up to date as the moves are applied
+    tree: HashMap<Id, Place>,
+    children: HashMap<Property, BTreeSet<(Vec<u32>, Timestamp, Id)>>,
+    registers: HashMap<Property, (Timestamp, ObjectValue)>,
+    sequences: HashMap<Property, Sequence>,
+    // the fields that reference an object, some of them may not anymore
+    referrers: HashMap<Id, HashSet<Property>>,
+}
+
+fn set_register<T>(register: &mut Option<(Timestamp, T)>, timestamp: &Timestamp, value: T) {
+    match register {
+        Some((current, _)) if *current >= *timestamp => {}
+        _ => *register = Some((timestamp.clone(), value)),
+    }
+}
+
+pub(crate) fn to_property(parent: &IndexEntryProperty) -> Property {
+    (parent.id.clone(), parent.property.clone())
+}
+
+impl CrdtState {
+    pub fn new(main_id: &str) -> Self {
+        CrdtState {
+            main_id: main_id.to_string(),
+            models: HashMap::new(),
+            deleted: HashSet::new(),
+            moves: BTreeMap::new(),
+            tree: HashMap::new(),
+            children: HashMap::new(),
+            registers: HashMap::new(),
+            sequences: HashMap::new(),
+            referrers: HashMap::new(),
+        }
+    }
+
+    pub fn integrate(&mut self, message: &CrdtMessage, changes: &mut Changes) {
+        let timestamp = &message.timestamp;
+        match &message.change {
+            CrdtChange::Create { id, model } => {
+                self.models.insert(id.clone(), model.clone());
+                changes.objects.insert(id.clone());
+            }
+            CrdtChange::Delete { id } => {
+                self.deleted.insert(id.clone());
+                changes.objects.insert(id.clone());
+            }
+            CrdtChange::Move {
+                id,
+                parent,
+                position,
+            } => {
+                let record = MoveRecord {
+                    id: id.clone(),
+                    parent: parent.clone(),
+                    position: position.clone(),
+                    previous: None,
+                };
+                self.add_move(timestamp, record, changes);
+            }
+            CrdtChange::Set {
+                id,
+                property,
+                value,
+            } => {
+                let key = (id.clone(), property.clone());
+                if let ObjectValue::Reference(reference) = value {
+                    self.add_referrer(&reference.id, &key);
+                }
+                let mut register = self.registers.remove(&key);
+                set_register(&mut register, timestamp, value.clone());
+                if let Some(register) = register {
+                    self.registers.insert(key.clone(), register);
+                }
+                changes.properties.insert(key);
+            }
+            CrdtChange::InsertElement {
+                id,
+                property,
+                reference,
+                position,
+            } => {
+                let key = (id.clone(), property.clone());
+                self.add_referrer(reference, &key);
+                let sequence = self.get_sequence_mut(id, property);
+                sequence
+                    .references
+                    .insert(timestamp.clone(), reference.clone());
+                Self::set_element_position(sequence, timestamp, timestamp, position);
+                changes.properties.insert(key);
+            }
+            CrdtChange::RemoveElement {
+                id,
+                property,
+                element,
+            } => {
+                self.get_sequence_mut(id, property)
+                    .removed
+                    .insert(element.clone());
+                changes.properties.insert((id.clone(), property.clone()));
+            }
+            CrdtChange::MoveElement {
+                id,
+                property,
+                element,
+                position,
+            } => {
+                let sequence = self.get_sequence_mut(id, property);
+                Self::set_element_position(sequence, element, timestamp, position);
+                changes.properties.insert((id.clone(), property.clone()));
+            }
+        }
+    }
+
+    fn add_referrer(&mut self, id: &str, property: &Property) {
+        self.referrers
+            .entry(id.to_string())
+            .or_default()
+            .insert(property.clone());
+    }
+
+    fn get_sequence_mut(&mut self, id: &str, property: &str) -> &mut Sequence {
+        self.sequences
+            .entry((id.to_string(), property.to_string()))
+            .or_default()
+    }
+
+    fn set_element_position(
+        sequence: &mut Sequence,
+        element: &Timestamp,
+        timestamp: &Timestamp,
+        position: &[u32],
+    ) {
+        let mut register = sequence.positions.remove(element);
+        set_register(&mut register, timestamp, position.to_vec());
+        if let Some(register) = register {
+            sequence.positions.insert(element.clone(), register);
+        }
+    }
+
+    pub fn is_alive(&self, id: &str) -> bool {
+        self.models.contains_key(id) && !self.deleted.contains(id)
+    }
+
+    pub fn get_model(&self, id: &str) -> Option<&String> {
+        self.models.get(id)
+    }
+
+    pub fn get_parent(&self, id: &str) -> Option<&IndexEntryProperty> {
+        self.tree.get(id).map(|(parent, _, _)| parent)
+    }
+
+    pub fn get_referrers(&self, id: &str) -> Vec<Property> {
+        self.referrers
+            .get(id)
+            .map(|referrers| referrers.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // The moves are applied in timestamp order. A move received late is inserted in this
+    // order : the later moves are undone, and applied again after it.
+    fn add_move(&mut self, timestamp: &Timestamp, record: MoveRecord, changes: &mut Changes) {
+        if self.moves.contains_key(timestamp) {
+            return;
+        }
+        let later: Vec<Timestamp> = self
+            .moves
+            .range((Excluded(timestamp), Unbounded))
+            .map(|(timestamp, _)| timestamp.clone())
+            .collect();
+        for later_timestamp in later.iter().rev() {
+            self.undo_move(later_timestamp, changes);
+        }
+        self.moves.insert(timestamp.clone(), record);
+        self.apply_move(timestamp, changes);
+        for later_timestamp in later.iter() {
+            self.apply_move(later_timestamp, changes);
+        }
+    }
+
+    // A move that would put an object inside its own subtree is skipped,
+    // so that concurrent moves cannot create a cycle.
+    fn apply_move(&mut self, timestamp: &Timestamp, changes: &mut Changes) {
+        let (id, parent, position) = match self.moves.get(timestamp) {
+            Some(record) => (
+                record.id.clone(),
+                record.parent.clone(),
+                record.position.clone(),
+            ),
+            None => return,
+        };
+        let previous = self.tree.get(&id).cloned();
+        if let Some(record) = self.moves.get_mut(timestamp) {
+            record.previous = previous;
+        }
+        if id == self.main_id || self.is_in_subtree(&parent.id, &id) {
+            return;
+        }
+        self.set_place(&id, Some((parent, position, timestamp.clone())));
+        changes.objects.insert(id);
+    }
+
+    fn undo_move(&mut self, timestamp: &Timestamp, changes: &mut Changes) {
+        let (id, previous) = match self.moves.get(timestamp) {
+            Some(record) => (record.id.clone(), record.previous.clone()),
+            None => return,
+        };
+        if self.tree.get(&id).map(|(_, _, current)| current) != Some(timestamp) {
+            // the move was skipped
+            return;
+        }
+        self.set_place(&id, previous);
+        changes.objects.insert(id);
+    }
+
+    fn is_in_subtree(&self, id: &str, root: &str) -> bool {
+        let mut ancestor = Some(id);
+        while let Some(ancestor_id) = ancestor {
+            if ancestor_id == root {
+                return true;
+            }
+            ancestor = self
+                .get_parent(ancestor_id)
+                .map(|parent| parent.id.as_str());
+        }
+        false
+    }
+
+    fn set_place(&mut self, id: &str, place: Option<Place>) {
+        if let Some((parent, position, timestamp)) = self.tree.remove(id) {
+            if let Some(children) = self.children.get_mut(&to_property(&parent)) {
+                children.remove(&(position, timestamp, id.to_string()));
+            }
+        }
+        if let Some((parent, position, timestamp)) = place {
+            self.children
+                .entry(to_property(&parent))
+                .or_default()
+                .insert((position.clone(), timestamp.clone(), id.to_string()));
+            self.tree
+                .insert(id.to_string(), (parent, position, timestamp));
+        }
+    }
+
+    // the ordered living children of a property
+    pub fn get_children(&self, parent: &IndexEntryProperty) -> Vec<(Id, Vec<u32>)> {
+        self.children
+            .get(&to_property(parent))
+            .map(|children| {
+                children
+                    .iter()
+                    .filter(|(_, _, id)| self.is_alive(id))
+                    .map(|(position, _, id)| (id.clone(), position.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // the children that are part of the document : a single subobject field only keeps
+    // the first of the concurrently inserted objects
+    pub fn get_visible_children(&self, parent: &IndexEntryProperty, kernel: &HitKernel) -> Vec<Id> {
+        let children = self.get_children(parent).into_iter().map(|(id, _)| id);
+        if self.is_vec_subobject(parent, kernel) {
+            children.collect()
+        } else {
+            children.take(1).collect()
+        }
+    }
+
+    fn is_vec_subobject(&self, parent: &IndexEntryProperty, kernel: &HitKernel) -> bool {
+        self.models
+            .get(&parent.id)
+            .and_then(|model| kernel.get_model(model).ok())
+            .and_then(|model| {
+                model
+                    .get_field(&parent.property)
+                    .map(|field| field.borrow().is_vec_subobject())
+            })
+            .unwrap_or(false)
+    }
+
+    // an object is part of the document if it and its ancestors are alive,
+    // up to the main object
+    pub fn is_visible(&self, id: &str, kernel: &HitKernel) -> bool {
+        let mut current = id;
+        loop {
+            if !self.is_alive(current) {
+                return false;
+            }
+            if current == self.main_id {
+                return true;
+            }
+            let parent = match self.get_parent(current) {
+                Some(parent) => parent,
+                None => return false,
+            };
+            if !self.is_vec_subobject(parent, kernel)
+                && self
+                    .get_children(parent)
+                    .first()
+                    .map(|(first, _)| first.as_str())
+                    != Some(current)
+            {
+                return false;
+            }
+            current = &parent.id;
+        }
+    }
+
+    pub fn get_depth(&self, id: &str) -> usize {
+        let mut depth = 0;
+        let mut current = id;
+        while let Some(parent) = self.get_parent(current) {
+            depth += 1;
+            current = &parent.id;
+        }
+        depth
+    }
+
+    // the elements of a reference list that were not removed, in order
+    pub fn get_elements(&self, id: &str, property: &str) -> Vec<Element> {
+        let sequence = match self.sequences.get(&(id.to_string(), property.to_string())) {
+            Some(sequence) => sequence,
+            None => return vec![],
+        };
+        let mut elements: Vec<Element> = sequence
+            .references
+            .iter()
+            .filter(|(element, _)| !sequence.removed.contains(element))
+            .map(|(element, reference)| Element {
+                id: element.clone(),
+                reference: reference.clone(),
+                position: sequence.positions[element].1.clone(),
+            })
+            .collect();
+        elements.sort_by(|a, b| (&a.position, &a.id).cmp(&(&b.position, &b.id)));
+        elements
+    }
+
+    // The value of a field that is not a subobject field. The references to objects that
+    // are not part of the document are ignored.
+    pub fn get_value<F>(
+        &self,
+        id: &str,
+        property: &str,
+        is_vec_reference: bool,
+        is_visible: F,
+    ) -> Option<ObjectValue>
+    where
+        F: Fn(&str) -> bool,
+    {
+        if is_vec_reference {
+            let references: Vec<Reference> = self
+                .get_elements(id, property)
+                .into_iter()
+                .filter(|element| is_visible(&element.reference))
+                .map(|element| Reference {
+                    id: element.reference,
+                })
+                .collect();
+            if references.is_empty() {
+                return None;
+            }
+            return Some(ObjectValue::VecReference(references));
+        }
+        match self.registers.get(&(id.to_string(), property.to_string())) {
+            Some((_, ObjectValue::Reference(reference))) if !is_visible(&reference.id) => None,
+            Some((_, value)) => Some(value.clone()),
+            None => None,
+        }
+    }
+
+    // the objects of the document, parents first
+    fn collect_visible(
+        &self,
+        id: &str,
+        kernel: &Rc<HitKernel>,
+        visible: &mut Vec<(Id, ObjectValues)>,
+    ) -> Result<(), HitError> {
+        let model = kernel.get_model(&self.models[id])?;
+        let mut data = ObjectValues::new();
+        let mut subobjects = vec![];
+        for (property, field) in model.fields.iter() {
+            let parent = IndexEntryProperty {
+                id: id.to_string(),
+                property: property.clone(),
+            };
+            let list = self.get_visible_children(&parent, &**kernel);
+            if list.is_empty() {
+                continue;
+            }
+            let references: Vec<Reference> =
+                list.iter().map(|id| Reference { id: id.clone() }).collect();
+            let value = if field.borrow().is_vec_subobject() {
+                ObjectValue::VecSubObjects(references)
+            } else {
+                ObjectValue::SubObject(references[0].clone())
+            };
+            data.insert(property.clone(), value);
+            subobjects.extend(list);
+        }
+        visible.push((id.to_string(), data));
+        for child in subobjects.iter() {
+            self.collect_visible(child, kernel, visible)?;
+        }
+        Ok(())
+    }
+
+    // Computes the whole document, used to load it
+    pub fn materialize(&self, kernel: Rc<HitKernel>) -> Result<Hit, HitError> {
+        let mut visible = vec![];
+        self.collect_visible(&self.main_id, &kernel, &mut visible)?;
+        let visible_ids: HashSet<Id> = visible.iter().map(|(id, _)| id.clone()).collect();
+
+        let mut entries = LinkedHashMap::new();
+        for (id, mut data) in visible.into_iter() {
+            let model = kernel.get_model(&self.models[&id])?;
+            for (property, field) in model.fields.iter() {
+                if data.contains_key(property) {
+                    continue;
+                }
+                let value = self.get_value(
+                    &id,
+                    property,
+                    field.borrow().is_vec_reference(),
+                    |reference| visible_ids.contains(reference),
+                );
+                if let Some(value) = value {
+                    data.insert(property.clone(), value);
+                }
+            }
+            let parent = self.get_parent(&id).cloned();
+            entries.insert(
+                id.clone(),
+                AddedEntry {
+                    id: id.clone(),
+                    data,
+                    parent: if id == self.main_id { None } else { parent },
+                    model: model.get_name().clone(),
+                },
+            );
+        }
+        import_from_patches_and_entries(vec![], &self.main_id, kernel, &mut entries)
+    }
+}
@@ -72,4 +72,42 @@ pub enum HitError {
     CannotDeleteRootObject(),
     #[error("BeforeId is not present in this array: `{0}`")]
     InvalidBeforeId(String),
+    #[error("Index out of bounds in the list of strings: `{0}`")]
+    InvalidStringIndex(usize),
+    #[error("Model `{0}` is added twice to the kernel")]
+    DuplicateModel(String),
+    #[error("Model `{0}` implements the unknown interface `{1}`")]
+    UnknownInterface(String, String),
+    #[error("Field `{1}` of model `{0}` authorizes the unknown model or interface `{2}`")]
+    UnknownAuthorizedModel(String, String, String),
+    #[error("Model `{0}` extends the unknown model `{1}`")]
+    UnknownBaseModel(String, String),
+    #[error("Model `{0}` extends itself")]
+    CyclicInheritance(String),
+    #[error("Model `{0}` is abstract and cannot be instantiated")]
+    AbstractModel(String),
+    #[error("Field `{1}` of model `{0}` does not have the kind of the inherited field")]
+    InvalidFieldOverride(String, String),
+    #[error("Model `{0}` does not provide the field `{2}` of the interface `{1}`")]
+    MissingInterfaceField(String, String, String),
+    #[error("Undo and redo cannot be used inside a transaction")]
+    CannotUndoInTransaction(),
+    #[error("Missing operations in the log: expected sequence `{0}`, found `{1}`")]
+    InvalidOperationSequence(u64, u64),
+    #[error("The computed field `{0}` cannot be set")]
+    CannotSetComputedField(String),
+    #[error("The computed field `{1}` of object `{0}` depends on itself")]
+    CyclicComputedField(String, String),
+    #[error("The backlinks field `{0}` cannot be set")]
+    CannotSetBacklinksField(String),
+    #[error("The field `{1}` of object `{0}` already contains an object")]
+    OccupiedSubobjectField(String, String),
+    #[error("Invalid path `{0}`: {1}")]
+    InvalidPath(String, String),
+    #[error("Nothing found at path `{0}`")]
+    PathNotFound(String),
+    #[error("Invalid secondary index `{0}`: {1}")]
+    InvalidSecondaryIndex(String, String),
+    #[error("Secondary index `{0}` not found")]
+    SecondaryIndexNotFound(String),
 }
@@ -36,6 +36,9 @@ pub fn get_all_permissions(kernel: Rc<HitKernel>) -> Result<ObjectPermissions, H
 
     for model_name in kernel.clone().get_models() {
         let model = kernel.clone().get_model(&model_name)?;
+        if model.is_abstract {
+            continue;
+        }
         let mut allowed_models = HashMap::new();
         for target_model_name in list_of_models_clone.clone() {
             match get_allowed_fields(kernel.clone(), &model, &target_model_name)? {
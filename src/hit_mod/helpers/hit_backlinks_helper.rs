@@ -0,0 +1,108 @@
+use crate::hit_mod::helpers::update_computed_fields;
+use crate::index::IndexEntryProperty;
+use crate::model::field_types::FieldTypeBacklinks;
+use crate::model::Model;
+use crate::object_data::{ObjectValue, ObjectValues, Reference};
+use crate::{Hit, HitError};
+
+fn get_backlinks(hit: &Hit, id: &str, field: &FieldTypeBacklinks) -> Vec<Reference> {
+    let mut backlinks: Vec<Reference> = vec![];
+    for reference in hit.get_references(id).unwrap_or_default().iter() {
+        if reference.property != field.source_field
+            || backlinks.iter().any(|backlink| backlink.id == reference.id)
+        {
+            continue;
+        }
+        let is_source = match hit.get_model(&reference.id) {
+            Some(model) => model.is_a(&field.source_model),
+            None => false,
+        };
+        if is_source {
+            backlinks.push(Reference {
+                id: reference.id.clone(),
+            });
+        }
+    }
+    backlinks
+}
+
+// the value of a backlinks field, None for the other fields
+pub(crate) fn get_backlinks_value(hit: &Hit, id: &str, property: &str) -> Option<ObjectValue> {
+    let model = hit.get_model(id)?;
+    let field = model.get_field(property)?.borrow();
+    let field = field.downcast_ref::<FieldTypeBacklinks>()?;
+    Some(ObjectValue::VecReference(get_backlinks(hit, id, field)))
+}
+
+// the values of all the backlinks fields of an object
+pub(crate) fn get_backlinks_values(hit: &Hit, id: &str, model: &Model) -> ObjectValues {
+    let mut values = ObjectValues::new();
+    for (property, field) in model.fields.iter() {
+        let field = field.borrow();
+        if let Some(field) = field.downcast_ref::<FieldTypeBacklinks>() {
+            let backlinks = get_backlinks(hit, id, field);
+            values.insert(property.clone(), ObjectValue::VecReference(backlinks));
+        }
+    }
+    values
+}
+
+// the objects referenced by the value of a field, with the name of the field
+pub(crate) fn get_value_references(property: &str, value: &ObjectValue) -> Vec<IndexEntryProperty> {
+    let references = match value {
+        ObjectValue::Reference(reference) => vec![reference.clone()],
+        ObjectValue::VecReference(references) => references.clone(),
+        _ => vec![],
+    };
+    references
+        .into_iter()
+        .map(|reference| IndexEntryProperty {
+            id: reference.id,
+            property: property.to_string(),
+        })
+        .collect()
+}
+
+pub(crate) fn get_referenced_objects(values: &ObjectValues) -> Vec<IndexEntryProperty> {
+    values
+        .iter()
+        .flat_map(|(property, value)| get_value_references(property, value))
+        .collect()
+}
+
+// sends the backlinks of the objects whose references changed, `id` is the referenced
+// object and `property` the field of the referencing object
+pub(crate) fn dispatch_backlinks(
+    hit: &mut Hit,
+    changed: &[IndexEntryProperty],
+) -> Result<(), HitError> {
+    let mut backlinks_fields: Vec<IndexEntryProperty> = vec![];
+    for reference in changed.iter() {
+        let model = match hit.get_model(&reference.id) {
+            Some(model) => model,
+            None => continue,
+        };
+        for (name, field) in model.get_fields().iter() {
+            let is_changed = match field.borrow().downcast_ref::<FieldTypeBacklinks>() {
+                Some(field) => field.source_field == reference.property,
+                None => false,
+            };
+            let backlinks_field = IndexEntryProperty {
+                id: reference.id.clone(),
+                property: name.clone(),
+            };
+            if is_changed && !backlinks_fields.contains(&backlinks_field) {
+                backlinks_fields.push(backlinks_field);
+            }
+        }
+    }
+    for backlinks_field in backlinks_fields.iter() {
+        if let Some(value) =
+            get_backlinks_value(hit, &backlinks_field.id, &backlinks_field.property)
+        {
+            hit.index
+                .dispatch_field(&backlinks_field.id, &backlinks_field.property, value);
+        }
+    }
+    update_computed_fields(hit, &backlinks_fields)
+}
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::index::IndexEntryProperty;
+use crate::model::field_types::{ComputedDependency, FieldTypeComputed};
+use crate::object_data::Id;
+use crate::{Hit, HitError};
+
+// the computed fields of an object, with their dependencies
+fn get_computed_fields(hit: &Hit, id: &str) -> Vec<(String, Vec<ComputedDependency>)> {
+    let model = match hit.get_model(id) {
+        Some(model) => model,
+        None => return vec![],
+    };
+    let mut computed_fields = vec![];
+    for (name, field) in model.get_fields().iter() {
+        let field = field.borrow();
+        if let Some(field) = field.downcast_ref::<FieldTypeComputed>() {
+            computed_fields.push((name.clone(), field.dependencies.clone()));
+        }
+    }
+    computed_fields
+}
+
+// the computed fields that read the value of a field
+fn get_dependent_fields(hit: &Hit, changed: &IndexEntryProperty) -> Vec<IndexEntryProperty> {
+    let mut dependent_fields = vec![];
+    if !hit.index.contains(&changed.id) {
+        return dependent_fields;
+    }
+    let mut add_fields = |id: &str, is_dependency: &dyn Fn(&ComputedDependency) -> bool| {
+        for (name, dependencies) in get_computed_fields(hit, id).into_iter() {
+            if dependencies.iter().any(is_dependency) {
+                dependent_fields.push(IndexEntryProperty {
+                    id: id.to_string(),
+                    property: name,
+                });
+            }
+        }
+    };
+
+    // the object itself
+    add_fields(&changed.id, &|dependency| {
+        dependency.get_field() == changed.property
+    });
+
+    // its parent, when it reads the field of its subobjects
+    if let Some(parent) = hit.get_parent(&changed.id) {
+        add_fields(&parent.id, &|dependency| match dependency {
+            ComputedDependency::Subobjects {
+                field,
+                property: Some(property),
+            } => *field == parent.property && *property == changed.property,
+            _ => false,
+        });
+    }
+
+    // the objects that reference it
+    for reference in hit.get_references(&changed.id).unwrap_or_default().iter() {
+        add_fields(&reference.id, &|dependency| match dependency {
+            ComputedDependency::References {
+                field,
+                property: Some(property),
+            } => *field == reference.property && *property == changed.property,
+            _ => false,
+        });
+    }
+    dependent_fields
+}
+
+// sets the new value of a computed field, the listeners are only called when it changes
+fn compute_field(hit: &mut Hit, target: &IndexEntryProperty) -> Result<(), HitError> {
+    let model = match hit.get_model(&target.id) {
+        Some(model) => model,
+        None => return Ok(()),
+    };
+    let compute = match model.get_field(&target.property) {
+        Some(field) => match field.borrow().downcast_ref::<FieldTypeComputed>() {
+            Some(field) => field.compute.clone(),
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+    let value = compute(&target.id, hit)?;
+    if hit.get_value(&target.id, &target.property).as_ref() != Some(&value) {
+        hit.index.set_value(&target.id, &target.property, value)?;
+    }
+    Ok(())
+}
+
+// the fields to compute, and the fields that depend on each of them
+#[derive(Default)]
+struct DependencyGraph {
+    positions: HashMap<(Id, String), usize>,
+    fields: Vec<IndexEntryProperty>,
+    dependents: Vec<Vec<usize>>,
+    dependency_counts: Vec<usize>,
+}
+
+impl DependencyGraph {
+    // returns the position of the field, and whether it was added
+    fn add_field(&mut self, field: IndexEntryProperty) -> (usize, bool) {
+        let key = (field.id.clone(), field.property.clone());
+        if let Some(position) = self.positions.get(&key) {
+            return (*position, false);
+        }
+        let position = self.fields.len();
+        self.positions.insert(key, position);
+        self.fields.push(field);
+        self.dependents.push(vec![]);
+        self.dependency_counts.push(0);
+        (position, true)
+    }
+
+    fn add_dependent(&mut self, position: usize, dependent_position: usize) {
+        if !self.dependents[position].contains(&dependent_position) {
+            self.dependents[position].push(dependent_position);
+            self.dependency_counts[dependent_position] += 1;
+        }
+    }
+}
+
+// computes the fields and the fields that depend on them, each field after its dependencies
+fn compute_fields(hit: &mut Hit, targets: Vec<IndexEntryProperty>) -> Result<(), HitError> {
+    let mut graph = DependencyGraph::default();
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    for target in targets.into_iter() {
+        let (position, is_new) = graph.add_field(target);
+        if is_new {
+            queue.push_back(position);
+        }
+    }
+    while let Some(position) = queue.pop_front() {
+        for dependent_field in get_dependent_fields(hit, &graph.fields[position]).into_iter() {
+            let (dependent_position, is_new) = graph.add_field(dependent_field);
+            graph.add_dependent(position, dependent_position);
+            if is_new {
+                queue.push_back(dependent_position);
+            }
+        }
+    }
+
+    // the fields whose dependencies are computed
+    let mut ready: VecDeque<usize> = (0..graph.fields.len())
+        .filter(|position| graph.dependency_counts[*position] == 0)
+        .collect();
+    let mut computed_count = 0;
+    while let Some(position) = ready.pop_front() {
+        compute_field(hit, &graph.fields[position])?;
+        computed_count += 1;
+        for dependent_position in graph.dependents[position].iter() {
+            graph.dependency_counts[*dependent_position] -= 1;
+            if graph.dependency_counts[*dependent_position] == 0 {
+                ready.push_back(*dependent_position);
+            }
+        }
+    }
+    if computed_count < graph.fields.len() {
+        let position = (0..graph.fields.len())
+            .find(|position| graph.dependency_counts[*position] > 0)
+            .unwrap_or(0);
+        let field = &graph.fields[position];
+        return Err(HitError::CyclicComputedField(
+            field.id.clone(),
+            field.property.clone(),
+        ));
+    }
+    Ok(())
+}
+
+// recomputes the fields that depend on the changed fields
+pub(crate) fn update_computed_fields(
+    hit: &mut Hit,
+    changed: &[IndexEntryProperty],
+) -> Result<(), HitError> {
+    let mut targets = vec![];
+    for changed in changed.iter() {
+        targets.extend(get_dependent_fields(hit, changed));
+    }
+    if targets.is_empty() {
+        return Ok(());
+    }
+    compute_fields(hit, targets)
+}
+
+// computes every computed field of the objects, used for new and imported objects
+pub(crate) fn compute_object_fields(hit: &mut Hit, ids: &[Id]) -> Result<(), HitError> {
+    let mut targets = vec![];
+    for id in ids.iter() {
+        for (name, _) in get_computed_fields(hit, id).into_iter() {
+            targets.push(IndexEntryProperty {
+                id: id.clone(),
+                property: name,
+            });
+        }
+    }
+    if targets.is_empty() {
+        return Ok(());
+    }
+    compute_fields(hit, targets)
+}
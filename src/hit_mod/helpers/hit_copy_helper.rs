@@ -51,6 +51,8 @@ fn _copy_object(
             }
             crate::ObjectValue::SubObject(_) => {}
             crate::ObjectValue::VecSubObjects(_) => {}
+            // the copy gets the default value of the field
+            crate::ObjectValue::Null => {}
             _ => {
                 values.insert(field_name.to_string(), value.clone());
             }
@@ -1,19 +1,15 @@
-use crate::field_types::FieldTypeSubobjectArray;
 use crate::index::can_move_object as index_can_move_object;
-use crate::model::Model;
+use crate::model::{FieldKind, Model};
 use crate::IndexEntryProperty;
 use crate::{Hit, HitError};
 use std::rc::Rc;
 
-// TODO : that's not generic, it should use the allow_model method
-// this prevents from creating custom subobject fields with custom rules :/
 fn _can_move_object(
     index: &Hit,
     id: &str,
     target_model: Rc<Model>,
     property: &str,
 ) -> Result<(), HitError> {
-    // TODO : should this be implemented ?
     let model = index
         .get_model(id)
         .ok_or(HitError::NoModelForId(id.to_string()))?;
@@ -21,18 +17,12 @@ fn _can_move_object(
         .get_field(property)
         .ok_or(HitError::PropertyNotFound(property.to_string()))?
         .borrow();
-    let target_field = target_field
-        .downcast_ref::<FieldTypeSubobjectArray>()
-        .ok_or(HitError::InvalidMoveDestination())?;
-    for allowed_model in target_field.authorized_models.iter() {
-        if allowed_model == model.get_name() {
-            return Ok(());
-        }
-        for interface in model.interfaces.iter() {
-            if allowed_model == interface {
-                return Ok(());
-            }
-        }
+    match target_field.get_kind() {
+        FieldKind::SubobjectArray | FieldKind::Subobject => {}
+        _ => return Err(HitError::InvalidMoveDestination()),
+    }
+    if target_field.accepts_model(&model) {
+        return Ok(());
     }
     Err(HitError::ModelNotAllowed(model.get_name().clone()))
 }
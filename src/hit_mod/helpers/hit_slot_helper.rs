@@ -0,0 +1,67 @@
+use crate::field_types::SlotPolicy;
+use crate::index::IndexEntryProperty;
+use crate::model::{FieldKind, Model};
+use crate::object_data::ObjectValue;
+use crate::{Hit, HitError};
+
+// a single subobject field, that contains at most one object
+pub(crate) fn is_subobject_slot(model: &Model, property: &str) -> bool {
+    match model.get_field(property) {
+        Some(field) => field.borrow().get_kind() == FieldKind::Subobject,
+        None => false,
+    }
+}
+
+// the object of a single subobject field
+pub(crate) fn get_slot_occupant(hit: &Hit, target: &IndexEntryProperty) -> Option<String> {
+    match hit.get_value(&target.id, &target.property) {
+        Some(ObjectValue::SubObject(reference)) => Some(reference.id),
+        _ => None,
+    }
+}
+
+fn get_slot_policy(hit: &Hit, target: &IndexEntryProperty) -> SlotPolicy {
+    match hit.get_model(&target.id) {
+        Some(model) => model.get_slot_policy(&target.property).unwrap_or_default(),
+        None => SlotPolicy::Error,
+    }
+}
+
+// empties the single subobject field before an insert or a move, according to its policy
+pub(crate) fn free_subobject_slot(
+    hit: &mut Hit,
+    target: &IndexEntryProperty,
+) -> Result<(), HitError> {
+    let occupant = match get_slot_occupant(hit, target) {
+        Some(occupant) => occupant,
+        None => return Ok(()),
+    };
+    match get_slot_policy(hit, target) {
+        SlotPolicy::Error => {}
+        SlotPolicy::Replace => {
+            hit.remove_object(&occupant)?;
+        }
+        SlotPolicy::MoveTo(property) => {
+            let destination = IndexEntryProperty {
+                id: target.id.clone(),
+                property,
+            };
+            // the policies are not chained, an occupied destination is an error
+            if destination != *target && get_slot_occupant(hit, &destination).is_some() {
+                return Err(HitError::OccupiedSubobjectField(
+                    destination.id,
+                    destination.property,
+                ));
+            }
+            hit.move_object(&occupant, destination, None)?;
+        }
+    }
+    // the policy can leave the object in place, when it moves it to the same field
+    match get_slot_occupant(hit, target) {
+        Some(_) => Err(HitError::OccupiedSubobjectField(
+            target.id.clone(),
+            target.property.clone(),
+        )),
+        None => Ok(()),
+    }
+}
@@ -1,9 +1,18 @@
 mod can_create_object;
+mod hit_backlinks_helper;
+mod hit_computed_helper;
 mod hit_copy_helper;
 mod hit_move_helper;
+mod hit_slot_helper;
 
 pub use can_create_object::get_all_permissions;
 pub use can_create_object::get_all_targets;
 pub use can_create_object::ObjectPermissions;
+pub(crate) use hit_backlinks_helper::{
+    dispatch_backlinks, get_backlinks_value, get_backlinks_values, get_referenced_objects,
+    get_value_references,
+};
+pub(crate) use hit_computed_helper::{compute_object_fields, update_computed_fields};
 pub use hit_copy_helper::copy_object;
 pub use hit_move_helper::can_move_object;
+pub(crate) use hit_slot_helper::{free_subobject_slot, get_slot_occupant, is_subobject_slot};
@@ -5,18 +5,26 @@ use crate::model::validators::ValidatorContext;
 use crate::model::Model;
 use crate::object_data::Id;
 use crate::object_data::{ObjectValue, ObjectValues};
+use crate::operations::{get_next_id, Operation, OperationRecorder};
 use crate::plugins::Plugins;
+use crate::secondary_index::SecondaryIndexes;
 use crate::utils::ModelPropertyVectors;
 use crate::HitError;
 use crate::Kernel;
 use crate::{errors::ValidationError, events::FieldListenerRef};
 use crate::{events::Listeners, hit_mod::hit_entry::HitEntry};
-use crate::{helpers::copy_object, index::Index};
-use crate::{hit_mod::helpers::can_move_object, ModelField};
+use crate::{helpers::copy_object, hit_mod::transaction::TransactionState, index::Index};
+use crate::{hit_mod::helpers::can_move_object, FieldKind, ModelField};
+use crate::hit_mod::helpers::{
+    compute_object_fields, dispatch_backlinks, free_subobject_slot, get_backlinks_value,
+    get_backlinks_values, get_referenced_objects, get_slot_occupant, get_value_references, is_subobject_slot,
+    update_computed_fields,
+};
 
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::slice;
 
 pub type HitPlugins = Plugins;
 pub type HitKernel = dyn Kernel;
@@ -41,6 +49,9 @@ pub struct Hit {
     pub kernel: Rc<HitKernel>,
     pub(crate) errors: ModelPropertyVectors<ValidationError>,
     pub(crate) errors_subscriptions: Listeners<Vec<ValidationError>>,
+    pub(crate) transaction: TransactionState,
+    pub(crate) operations: OperationRecorder,
+    pub(crate) secondary_indexes: SecondaryIndexes,
 }
 
 impl Hit {
@@ -72,15 +83,17 @@ impl Hit {
                 value.references.clone(),
             );
             let model = hit.get_model_or_error(&id)?;
-            self.model_index.borrow_mut().map.insert(id.clone(), model);
-            self.index.index.insert(id.clone(), new_entry);
+            self.secondary_indexes.add_entry(&model, id, &value.data);
+            self.set_object_model(id, Some(model));
+            self.index.insert_entry(id, new_entry);
         }
 
         // insert main
         let main = hit.get(&main_id).unwrap();
         let model = hit.get_model_or_error(main_id)?;
 
-        self.insert(&model.get_name(), main_id, main.get_data(), parent, None)?;
+        let data = main.entry.borrow().data.clone();
+        self.insert(&model.get_name(), main_id, data, parent, None)?;
         Ok(())
     }
 
@@ -92,22 +105,30 @@ impl Hit {
     ) -> Result<Hit, HitError> {
         let mut model_index = ModelIndex::new();
         let model = kernel.get_model(model_type)?;
-        //TODO : initialize the values in the order defined by the model
-
-        model_index.map.insert(id.to_string(), model);
+        if model.is_abstract {
+            return Err(HitError::AbstractModel(model_type.to_string()));
+        }
+        model_index.map.insert(id.to_string(), model.clone());
         let model_index = Rc::new(RefCell::new(model_index));
 
         let mut hit = Hit {
             index: Index::new(id, LinkedHashMap::new())?,
             model_index: model_index,
             plugins: kernel.get_plugins(),
+            secondary_indexes: SecondaryIndexes::new(
+                kernel.get_secondary_index_definitions().to_vec(),
+            ),
             kernel: kernel,
             errors: ModelPropertyVectors::new(),
             errors_subscriptions: Listeners::new(),
+            transaction: TransactionState::new(),
+            operations: OperationRecorder::new(),
         };
+        let values = hit.add_default_values(&model, values, None)?;
         for (key, value) in values.iter() {
             hit.set(id, key, value.clone())?;
         }
+        compute_object_fields(&mut hit, &[id.to_string()])?;
         hit.validate_all()?;
         Ok(hit)
     }
@@ -160,6 +181,19 @@ impl Hit {
                     &before_id,
                 )?;
             }
+            update_computed_fields(self, slice::from_ref(&target))?;
+            dispatch_backlinks(
+                self,
+                &[IndexEntryProperty {
+                    id: id.to_string(),
+                    property: target.property.clone(),
+                }],
+            )?;
+            self.emit_operation(Operation::InsertReference {
+                id: id.to_string(),
+                target,
+                before_id,
+            });
             Ok(())
         } else {
             Err(HitError::InvalidReference(id.to_string()))
@@ -185,6 +219,7 @@ impl Hit {
             .ok_or(HitError::PropertyNotFound((&parent.property).into()))?;
         let target_property = target_property.borrow();
         if target_property.is_vec_reference() {
+            let before_id = self.get_next_reference_id(id, &parent);
             self.index.remove_reference(id, parent.clone())?;
 
             for plugin in self.plugins.reference_plugins.clone().iter() {
@@ -192,6 +227,19 @@ impl Hit {
                     .borrow_mut()
                     .on_after_remove_reference(self, &id.to_string(), &parent)?;
             }
+            update_computed_fields(self, slice::from_ref(&parent))?;
+            dispatch_backlinks(
+                self,
+                &[IndexEntryProperty {
+                    id: id.to_string(),
+                    property: parent.property.clone(),
+                }],
+            )?;
+            self.emit_operation(Operation::RemoveReference {
+                id: id.to_string(),
+                target: parent,
+                before_id,
+            });
             Ok(())
         } else {
             Err(HitError::InvalidDataType())
@@ -218,6 +266,7 @@ impl Hit {
             .ok_or(HitError::PropertyNotFound((&target.property).into()))?;
         let target_property = target_property.borrow();
         if target_property.is_vec_reference() {
+            let original_before_id = self.get_next_reference_id(id, &target);
             self.index
                 .move_reference(id, target.clone(), before_id.clone())?;
 
@@ -226,6 +275,13 @@ impl Hit {
                     .borrow_mut()
                     .on_after_remove_reference(self, &id.to_string(), &target)?;
             }
+            update_computed_fields(self, slice::from_ref(&target))?;
+            self.emit_operation(Operation::MoveReference {
+                id: id.to_string(),
+                target,
+                before_id,
+                original_before_id,
+            });
             Ok(())
         } else {
             Err(HitError::InvalidDataType())
@@ -236,6 +292,13 @@ impl Hit {
         self.index.get_references(id)
     }
 
+    fn get_next_reference_id(&self, id: &str, target: &IndexEntryProperty) -> Option<Id> {
+        match self.get_value(&target.id, &target.property)? {
+            ObjectValue::VecReference(references) => get_next_id(&references, id),
+            _ => None,
+        }
+    }
+
     pub fn find_references_recursive(
         &self,
         id: &str,
@@ -252,6 +315,7 @@ impl Hit {
             id.to_string(),
             "remove_object model".to_string(),
         ))?;
+        let backlinks = get_backlinks_values(self, id, &model);
 
         // before plugins call
         for plugin in self.plugins.delete_plugins.clone().iter() {
@@ -259,12 +323,25 @@ impl Hit {
                 &HitEntry {
                     entry: entry.clone(),
                     model: model.clone(),
+                    backlinks: backlinks.clone(),
                 },
                 self,
             )?;
         }
 
+        let parent = entry
+            .borrow()
+            .get_parent()
+            .ok_or(HitError::CannotDeleteRootObject())?;
+        let before_id = self.get_next_sibling_id(id);
+        let entries = self.get_subtree_entries(id)?;
         let id_list = self.index.remove_object(id)?;
+        for entry in entries.iter() {
+            if let Some(model) = self.get_model(&entry.id) {
+                self.secondary_indexes
+                    .remove_entry(&model, &entry.id, &entry.data);
+            }
+        }
 
         // after plugins call
         for plugin in self.plugins.delete_plugins.clone().iter() {
@@ -272,6 +349,7 @@ impl Hit {
                 &HitEntry {
                     entry: entry.clone(),
                     model: model.clone(),
+                    backlinks: backlinks.clone(),
                 },
                 self,
             )?;
@@ -279,9 +357,20 @@ impl Hit {
 
         //remove model indexes of the deleted objects
         for id in id_list.iter() {
-            let mut model_index = self.model_index.borrow_mut();
-            model_index.map.remove(id);
+            self.set_object_model(id, None);
         }
+        update_computed_fields(self, slice::from_ref(&parent))?;
+        let referenced: Vec<IndexEntryProperty> = entries
+            .iter()
+            .flat_map(|entry| get_referenced_objects(&entry.data))
+            .collect();
+        dispatch_backlinks(self, &referenced)?;
+        self.emit_operation(Operation::Remove {
+            id: id.to_string(),
+            parent,
+            before_id,
+            entries,
+        });
         Ok(id_list)
     }
 
@@ -306,9 +395,26 @@ impl Hit {
         if !self.model_index.borrow().map.contains_key(id) {
             return Err(HitError::IDNotFound(id.into(), "move_object".into()));
         }
+        let is_slot = is_subobject_slot(&target_model, &target.property);
+        if is_slot {
+            if let Some(before_id) = before_id {
+                return Err(HitError::InvalidBeforeId(before_id));
+            }
+            match get_slot_occupant(self, &target) {
+                Some(occupant) if occupant == id => return Ok(()),
+                Some(_) => {
+                    return self.transaction(|hit| {
+                        free_subobject_slot(hit, &target)?;
+                        hit.move_object(id, target.clone(), None)
+                    })
+                }
+                None => {}
+            }
+        }
         let original_parent = self
             .get_parent(id)
             .ok_or(HitError::CannotMoveRootObject())?;
+        let original_before_id = self.get_next_sibling_id(id);
 
         for plugin in self.plugins.plugins.iter() {
             plugin.borrow_mut().on_before_move_subobject(
@@ -320,8 +426,12 @@ impl Hit {
         }
 
         self.can_move_object(id, &target.id, target_model.get_name(), &target.property)?;
-        self.index
-            .move_object(id, target.clone(), before_id.clone())?;
+        if is_slot {
+            self.index.move_object_to_slot(id, target.clone())?;
+        } else {
+            self.index
+                .move_object(id, target.clone(), before_id.clone())?;
+        }
         let plugins = { self.plugins.plugins.clone() };
         for plugin in plugins.iter() {
             plugin.borrow_mut().on_after_move_subobject(
@@ -332,6 +442,14 @@ impl Hit {
                 self,
             )?;
         }
+        update_computed_fields(self, &[original_parent.clone(), target.clone()])?;
+        self.emit_operation(Operation::Move {
+            id: id.to_string(),
+            target,
+            before_id,
+            original_parent,
+            original_before_id,
+        });
         Ok(())
     }
 
@@ -346,9 +464,30 @@ impl Hit {
             return Err(HitError::IDNotFound(id.into(), "copy_object".into()));
         }
         self.can_move_object(&id, &target.id, target_model.get_name(), &target.property)?;
+        // the object of an occupied single subobject field is handled before the copy
+        if is_subobject_slot(&target_model, &target.property)
+            && get_slot_occupant(self, &target).is_some()
+        {
+            return self.transaction(|hit| {
+                free_subobject_slot(hit, &target)?;
+                hit.copy_object(id, target.clone(), before_id)
+            });
+        }
 
-        let id = copy_object(self, &id, target, before_id)?;
-        return Ok(id);
+        // the inserts of the copy are recorded as a single operation
+        self.operations.suppress();
+        let new_id = copy_object(self, &id, target.clone(), before_id.clone());
+        self.operations.resume();
+        let new_id = new_id?;
+        let entries = self.get_subtree_entries(&new_id)?;
+        self.emit_operation(Operation::Copy {
+            id,
+            new_id: new_id.clone(),
+            target,
+            before_id,
+            entries,
+        });
+        return Ok(new_id);
     }
 
     pub fn get_model(&self, id: &str) -> Option<Rc<Model>> {
@@ -378,18 +517,31 @@ impl Hit {
     pub fn get(&self, id: &str) -> Option<HitEntry> {
         let index_entry = self.index.get(id)?;
         let model = self.model_index.borrow();
-        let model = model.map.get(id)?;
+        let model = model.map.get(id)?.clone();
+        let backlinks = get_backlinks_values(self, id, &model);
         Some(HitEntry {
             entry: index_entry,
-            model: model.clone(),
+            model,
+            backlinks,
         })
     }
 
     pub fn get_value(&self, id: &str, property: &str) -> Option<ObjectValue> {
-        self.index.get_value(id, property)
+        get_backlinks_value(self, id, property).or_else(|| self.index.get_value(id, property))
     }
 
     pub fn set(&mut self, id: &str, property: &str, value: ObjectValue) -> Result<(), HitError> {
+        self._set(id, property, value, false)
+    }
+
+    // accept_null allows to restore the null value of a required field, like insert does
+    pub(crate) fn _set(
+        &mut self,
+        id: &str,
+        property: &str,
+        value: ObjectValue,
+        accept_null: bool,
+    ) -> Result<(), HitError> {
         let entry = self
             .get(id)
             .ok_or(HitError::IDNotFound(id.to_string(), "set".into()))?;
@@ -397,6 +549,15 @@ impl Hit {
             .model
             .get_field(property)
             .ok_or(HitError::PropertyNotFound(property.to_string()))?;
+        match model_field.borrow().get_kind() {
+            FieldKind::Computed => {
+                return Err(HitError::CannotSetComputedField(property.to_string()))
+            }
+            FieldKind::Backlinks => {
+                return Err(HitError::CannotSetBacklinksField(property.to_string()))
+            }
+            _ => {}
+        }
 
         let old_value = self.get_value(id, property);
 
@@ -413,7 +574,9 @@ impl Hit {
         }
 
         //does the field accept the object value
-        if !model_field.borrow().accepts_for_set(
+        let is_accepted_null = accept_null && value == ObjectValue::Null;
+        if !is_accepted_null
+            && !model_field.borrow().accepts_for_set(
             &value,
             &ValidatorContext {
                 id: id,
@@ -425,6 +588,15 @@ impl Hit {
         }
 
         self.index.set_value(id, property, value.clone())?;
+        self.secondary_indexes.set_value(
+            &entry.model,
+            &IndexEntryProperty {
+                id: id.into(),
+                property: property.into(),
+            },
+            &value,
+            &old_value,
+        );
 
         for plugin in { self.plugins.plugins.clone() }.iter() {
             plugin.borrow_mut().on_after_set_value(
@@ -438,8 +610,70 @@ impl Hit {
             )?;
         }
 
-        self._validate_field(model_field, id, property, value)?;
+        self._validate_field(model_field, id, property, value.clone())?;
+        update_computed_fields(
+            self,
+            &[IndexEntryProperty {
+                id: id.into(),
+                property: property.into(),
+            }],
+        )?;
+        let mut referenced = get_value_references(property, &value);
+        if let Some(old_value) = &old_value {
+            referenced.extend(get_value_references(property, old_value));
+        }
+        dispatch_backlinks(self, &referenced)?;
+
+        self.emit_operation(Operation::Set {
+            id: id.to_string(),
+            property: property.to_string(),
+            value,
+            old_value: old_value.unwrap_or(ObjectValue::Null),
+        });
+        Ok(())
+    }
+
+    // puts the values in the order of the model fields and adds the defaults of the fields
+    // that are not in the values
+    fn add_default_values(
+        &self,
+        model: &Model,
+        mut values: ObjectValues,
+        parent: Option<&IndexEntryProperty>,
+    ) -> Result<ObjectValues, HitError> {
+        let mut ordered_values = ObjectValues::new();
+        for property in model.fields.keys() {
+            if let Some(value) = values.remove(property) {
+                ordered_values.insert(property.to_string(), value);
+            } else if let Some(default) = model.get_default(property) {
+                let value = default.get_value(parent, self)?;
+                ordered_values.insert(property.to_string(), value);
+            }
+        }
+        // the values of the unknown fields are kept for the errors of the caller
+        ordered_values.extend(values);
+        Ok(ordered_values)
+    }
 
+    // sets the defaults of the null fields of every object, used by the imports.
+    // The values go through `set`, so the plugins index them and they are validated
+    pub(crate) fn fill_default_values(&mut self) -> Result<(), HitError> {
+        let ids: Vec<Id> = self.index.iter().map(|(id, _)| id.clone()).collect();
+        for id in ids.iter() {
+            let model = self.get_model_or_error(id)?;
+            let parent = self.get_parent(id);
+            for property in model.fields.keys() {
+                let default = match model.get_default(property) {
+                    Some(default) => default,
+                    None => continue,
+                };
+                if self.get_value(id, property) != Some(ObjectValue::Null) {
+                    continue;
+                }
+                let value = default.get_value(parent.as_ref(), self)?;
+                self.set(id, property, value)?;
+            }
+        }
         Ok(())
     }
 
@@ -453,6 +687,16 @@ impl Hit {
         // and validate the data of the new object
         let mut ordered_values: ObjectValues = LinkedHashMap::new();
         for (property, model_field) in new_object_model.fields.iter() {
+            match model_field.borrow().get_kind() {
+                // the computed fields are set after the insert
+                FieldKind::Computed => {
+                    ordered_values.insert(property.to_string(), ObjectValue::Null);
+                    continue;
+                }
+                // the backlinks are read from the references
+                FieldKind::Backlinks => continue,
+                _ => {}
+            }
             //does the field accept the object value
             match values.get(property) {
                 Some(value) => {
@@ -493,6 +737,23 @@ impl Hit {
             .kernel
             .get_model(&model_type.to_string())
             .map_err(|_| HitError::ModelDoesNotExist(model_type.to_string()))?;
+        if new_object_model.is_abstract {
+            return Err(HitError::AbstractModel(model_type.to_string()));
+        }
+        let target_model = self.get_model_or_error(&parent.id)?;
+        let is_slot = is_subobject_slot(&target_model, &parent.property);
+        if is_slot {
+            if let Some(before_id) = before_id {
+                return Err(HitError::InvalidBeforeId(before_id));
+            }
+            if get_slot_occupant(self, &parent).is_some() {
+                return self.transaction(|hit| {
+                    free_subobject_slot(hit, &parent)?;
+                    hit.insert(model_type, id, values, parent.clone(), None)
+                });
+            }
+        }
+        let values = self.add_default_values(&new_object_model, values, Some(&parent))?;
 
         // before_add_entry hook
         for plugin in self.get_plugins().plugins.iter() {
@@ -506,14 +767,12 @@ impl Hit {
             )?;
         }
 
-        let target_model = self.get_model_or_error(&parent.id)?;
-
         // verify that the model field exists and is of the right type
         let field = target_model
             .get_field(&parent.property)
             .ok_or(HitError::PropertyNotFound(parent.property.clone()))?;
         let field = field.borrow();
-        if !field.is_vec_subobject() {
+        if !field.is_vec_subobject() && !is_slot {
             return Err(HitError::CannotInsertObjectInThisDataType());
         }
         // check if model is authorized
@@ -523,12 +782,18 @@ impl Hit {
 
         let values = self.validate_inserted_values(&new_object_model, id, &values)?;
         // update the data
-        self.index
-            .insert(id, values.clone(), parent.clone(), before_id.clone())?;
-        self.model_index
-            .borrow_mut()
-            .map
-            .insert(id.to_string(), new_object_model.clone());
+        if is_slot {
+            self.index.insert_in_slot(id, values.clone(), parent.clone())?;
+        } else {
+            self.index
+                .insert(id, values.clone(), parent.clone(), before_id.clone())?;
+        }
+        self.set_object_model(id, Some(new_object_model.clone()));
+        self.secondary_indexes
+            .add_entry(&new_object_model, id, &values);
+        compute_object_fields(self, &[id.to_string()])?;
+        update_computed_fields(self, slice::from_ref(&parent))?;
+        dispatch_backlinks(self, &get_referenced_objects(&values))?;
 
         // after_add_entry hook
         for plugin in { self.get_plugins().plugins.clone() }.iter() {
@@ -542,6 +807,13 @@ impl Hit {
             )?;
         }
 
+        self.emit_operation(Operation::Insert {
+            model: model_type.to_string(),
+            id: id.to_string(),
+            values,
+            parent,
+            before_id,
+        });
         Ok(())
     }
 
@@ -629,8 +901,9 @@ impl Hit {
                 index: Rc::new(self),
             },
         )?;
+        self.record_errors_change(id, property);
         self.errors.delete(id, property);
-        match validation_errors.clone() {
+        match validation_errors {
             None => {}
             Some(validation_errors) => {
                 for error in validation_errors.into_iter() {
@@ -640,18 +913,24 @@ impl Hit {
         }
 
         // dispatch event
+        if self.transaction.is_active() {
+            self.transaction.add_pending_validation(id, property);
+        } else {
+            self.dispatch_validation_errors(id, property);
+        }
 
+        Ok(())
+    }
+
+    pub(crate) fn dispatch_validation_errors(&mut self, id: &str, property: &str) {
+        let validation_errors = match self.errors.get(id, property) {
+            Some(validation_errors) => validation_errors.clone(),
+            None => vec![],
+        };
         self.errors_subscriptions.dispatch_value(
             &Self::get_validation_subscription_key(id, property),
-            &{
-                match validation_errors {
-                    Some(validation_errors) => validation_errors,
-                    None => vec![],
-                }
-            },
+            &validation_errors,
         );
-
-        Ok(())
     }
 
     pub fn validate_field(&mut self, id: &str, property: &str) -> Result<(), HitError> {
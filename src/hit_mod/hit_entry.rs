@@ -8,10 +8,15 @@ use std::rc::Rc;
 pub struct HitEntry {
     pub(in crate) entry: Rc<RefCell<IndexEntry>>,
     pub(in crate) model: Rc<Model>,
+    // the values of the backlinks fields, read when the entry was created
+    pub(in crate) backlinks: ObjectValues,
 }
 
 impl HitEntry {
     pub fn get(&self, property: &str) -> ObjectValue {
+        if let Some(value) = self.backlinks.get(property) {
+            return value.clone();
+        }
         let entry = self.entry.borrow();
         return entry.get(property).clone();
     }
@@ -42,6 +47,10 @@ impl HitEntry {
 
     pub fn get_data(&self) -> ObjectValues {
         let entry = self.entry.borrow();
-        entry.data.clone()
+        let mut data = entry.data.clone();
+        for (property, value) in self.backlinks.iter() {
+            data.insert(property.clone(), value.clone());
+        }
+        data
     }
 }
@@ -0,0 +1,129 @@
+use crate::hit_mod::Hit;
+use crate::operations::{History, Operation};
+use crate::HitError;
+
+impl Hit {
+    // Starts recording the mutations of this instance. Only the last `max_depth` steps can be undone.
+    pub fn enable_history(&mut self, max_depth: usize) {
+        self.operations.history = Some(History::new(max_depth));
+    }
+
+    pub fn disable_history(&mut self) {
+        self.operations.history = None;
+    }
+
+    pub fn clear_history(&mut self) {
+        if let Some(history) = &mut self.operations.history {
+            history.clear();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        match &self.operations.history {
+            Some(history) => history.can_undo(),
+            None => false,
+        }
+    }
+
+    pub fn can_redo(&self) -> bool {
+        match &self.operations.history {
+            Some(history) => history.can_redo(),
+            None => false,
+        }
+    }
+
+    // The operations recorded until the matching end_undo_group call are undone as one step.
+    // Groups can be nested, and a transaction is always a single step.
+    pub fn begin_undo_group(&mut self) {
+        if let Some(history) = &mut self.operations.history {
+            history.begin_group();
+        }
+    }
+
+    pub fn end_undo_group(&mut self) {
+        if let Some(history) = &mut self.operations.history {
+            history.end_group();
+        }
+    }
+
+    // Cancels the last step. Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> Result<bool, HitError> {
+        if self.transaction.is_active() {
+            return Err(HitError::CannotUndoInTransaction());
+        }
+        let step = match &mut self.operations.history {
+            Some(history) => {
+                history.close_group();
+                history.pop_undo()
+            }
+            None => None,
+        };
+        let step = match step {
+            Some(step) => step,
+            None => return Ok(false),
+        };
+        let inverse: Vec<Operation> = step.iter().rev().flat_map(|op| op.invert()).collect();
+
+        match self.apply_history_step(&inverse) {
+            Ok(()) => {
+                if let Some(history) = &mut self.operations.history {
+                    history.push_redo(step);
+                }
+                Ok(true)
+            }
+            Err(error) => {
+                if let Some(history) = &mut self.operations.history {
+                    history.push_undo(step);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    // Applies again the last undone step. Returns false if there was nothing to redo.
+    pub fn redo(&mut self) -> Result<bool, HitError> {
+        if self.transaction.is_active() {
+            return Err(HitError::CannotUndoInTransaction());
+        }
+        let step = match &mut self.operations.history {
+            Some(history) => history.pop_redo(),
+            None => None,
+        };
+        let step = match step {
+            Some(step) => step,
+            None => return Ok(false),
+        };
+
+        match self.apply_history_step(&step) {
+            Ok(()) => {
+                if let Some(history) = &mut self.operations.history {
+                    history.push_undo(step);
+                }
+                Ok(true)
+            }
+            Err(error) => {
+                if let Some(history) = &mut self.operations.history {
+                    history.push_redo(step);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    fn apply_history_step(&mut self, operations: &[Operation]) -> Result<(), HitError> {
+        self.set_history_paused(true);
+        let result = self.transaction(|hit| {
+            operations
+                .iter()
+                .try_for_each(|operation| hit.apply_operation(operation))
+        });
+        self.set_history_paused(false);
+        result
+    }
+
+    pub(crate) fn set_history_paused(&mut self, paused: bool) {
+        if let Some(history) = &mut self.operations.history {
+            history.set_paused(paused);
+        }
+    }
+}
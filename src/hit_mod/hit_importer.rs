@@ -1,10 +1,14 @@
 use linked_hash_map::LinkedHashMap;
 
 use crate::index::IndexImporter;
-use crate::object_data::ObjectValues;
+use crate::object_data::{Id, ObjectValues};
 use crate::HitError;
 use crate::{events::Listeners, index::IndexEntryProperty};
 use crate::{hit_mod::hit::ModelIndex, utils::ModelPropertyVectors};
+use crate::hit_mod::helpers::compute_object_fields;
+use crate::hit_mod::transaction::TransactionState;
+use crate::operations::OperationRecorder;
+use crate::secondary_index::SecondaryIndexes;
 use crate::{
     hit_mod::{Hit, HitKernel, HitPlugins},
     ObjectValue,
@@ -32,6 +36,10 @@ impl IndexModelImporter {
         }
     }
 
+    pub fn set_main_object_id(&mut self, id: &str) {
+        self.index.set_main_object_id(id);
+    }
+
     pub fn add_item(
         &mut self,
         model_type: &str,
@@ -70,6 +78,8 @@ impl IndexModelImporter {
         let errors = ModelPropertyVectors::new();
         // validate every item
         let index = self.index.finish_import()?;
+        let secondary_indexes =
+            SecondaryIndexes::new(self.kernel.get_secondary_index_definitions().to_vec());
         let mut hit = Hit {
             index: index,
             model_index: self.model_index,
@@ -77,7 +87,16 @@ impl IndexModelImporter {
             kernel: self.kernel,
             errors: errors,
             errors_subscriptions: Listeners::new(),
+            transaction: TransactionState::new(),
+            operations: OperationRecorder::new(),
+            secondary_indexes,
         };
+        hit.add_entries_to_secondary_indexes();
+        if hit.kernel.has_defaults_on_import() {
+            hit.fill_default_values()?;
+        }
+        let ids: Vec<Id> = hit.index.iter().map(|(id, _)| id.clone()).collect();
+        compute_object_fields(&mut hit, &ids)?;
         hit.validate_all()?;
 
         Ok(hit)
@@ -0,0 +1,159 @@
+use crate::events::FieldListenerRef;
+use crate::hit_mod::Hit;
+use crate::import::AddedEntry;
+use crate::object_data::{Id, ObjectValue};
+use crate::operations::{get_next_id, restore_entries_operations, Operation, OperationRecord};
+use crate::HitError;
+
+impl Hit {
+    // The listener receives every operation successfully applied to this instance.
+    // The operations of a transaction are only emitted when it is committed.
+    pub fn subscribe_operations(&mut self, listener: FieldListenerRef<OperationRecord>) -> String {
+        let listener_id = listener.borrow().get_unique_id().to_string();
+        self.operations.subscribe(listener);
+        listener_id
+    }
+
+    pub fn unsubscribe_operations(&mut self, listener_id: &str) -> Result<(), HitError> {
+        self.operations.unsubscribe(listener_id)
+    }
+
+    // sequence number of the last emitted operation, 0 if there is none
+    pub fn get_operation_sequence(&self) -> u64 {
+        self.operations.get_sequence()
+    }
+
+    // used when the instance is restored from a saved state, so that the records
+    // of the log that are already part of this state are skipped by apply_log
+    pub fn set_operation_sequence(&mut self, sequence: u64) {
+        self.operations.set_sequence(sequence);
+    }
+
+    // Replays the operations through the public API (validation, plugins, listeners).
+    // If one of them fails, none of them is applied.
+    pub fn apply_operations(&mut self, operations: &[Operation]) -> Result<(), HitError> {
+        self.transaction(|hit| {
+            operations
+                .iter()
+                .try_for_each(|operation| hit.apply_operation(operation))
+        })
+    }
+
+    // Applies the records that follow the current sequence number, so that a log can be
+    // applied several times. Returns the number of applied records.
+    pub fn apply_log(&mut self, records: &[OperationRecord]) -> Result<usize, HitError> {
+        let mut expected_sequence = self.get_operation_sequence() + 1;
+        let mut operations = vec![];
+        for record in records.iter() {
+            if record.sequence < expected_sequence {
+                continue;
+            }
+            if record.sequence > expected_sequence {
+                return Err(HitError::InvalidOperationSequence(
+                    expected_sequence,
+                    record.sequence,
+                ));
+            }
+            operations.push(record.operation.clone());
+            expected_sequence += 1;
+        }
+        self.apply_operations(&operations)?;
+        // each applied operation emits exactly one operation, but the sequence of the log
+        // is kept even if a plugin emitted more
+        self.set_operation_sequence(expected_sequence - 1);
+        Ok(operations.len())
+    }
+
+    pub(crate) fn emit_operation(&mut self, operation: Operation) {
+        if self.operations.is_suppressed() {
+            return;
+        }
+        if self.transaction.is_active() {
+            self.transaction.pending_operations.push(operation);
+        } else {
+            self.operations.record(vec![operation]);
+        }
+    }
+
+    // replays an operation through the public API, so that plugins and listeners are called
+    pub(crate) fn apply_operation(&mut self, operation: &Operation) -> Result<(), HitError> {
+        match operation {
+            Operation::Set {
+                id,
+                property,
+                value,
+                ..
+            } => self._set(id, property, value.clone(), true),
+            Operation::Insert {
+                model,
+                id,
+                values,
+                parent,
+                before_id,
+            } => self.insert(model, id, values.clone(), parent.clone(), before_id.clone()),
+            Operation::Remove { id, .. } => {
+                self.remove_object(id)?;
+                Ok(())
+            }
+            Operation::Move {
+                id,
+                target,
+                before_id,
+                ..
+            } => self.move_object(id, target.clone(), before_id.clone()),
+            Operation::Copy {
+                before_id, entries, ..
+            } => {
+                // the copies are recreated with their original ids
+                self.operations.suppress();
+                let result = restore_entries_operations(entries, before_id)
+                    .iter()
+                    .try_for_each(|operation| self.apply_operation(operation));
+                self.operations.resume();
+                result?;
+                self.emit_operation(operation.clone());
+                Ok(())
+            }
+            Operation::InsertReference {
+                id,
+                target,
+                before_id,
+            } => self.insert_reference(id, target.clone(), before_id.clone()),
+            Operation::RemoveReference { id, target, .. } => {
+                self.remove_reference(id, target.clone())
+            }
+            Operation::MoveReference {
+                id,
+                target,
+                before_id,
+                ..
+            } => self.move_reference(id, target.clone(), before_id.clone()),
+        }
+    }
+
+    // the object and its descendants, parents first
+    pub(crate) fn get_subtree_entries(&self, id: &str) -> Result<Vec<AddedEntry>, HitError> {
+        let (_, id_list) = self.find_references_recursive(id)?;
+        let mut entries = vec![];
+        for id in id_list.iter() {
+            let entry = self
+                .get(id)
+                .ok_or(HitError::IDNotFound(id.clone(), "get_subtree_entries".into()))?;
+            entries.push(AddedEntry {
+                id: id.clone(),
+                data: entry.entry.borrow().data.clone(),
+                parent: entry.get_parent(),
+                model: entry.get_model().get_name().clone(),
+            });
+        }
+        Ok(entries)
+    }
+
+    pub(crate) fn get_next_sibling_id(&self, id: &str) -> Option<Id> {
+        let parent = self.get_parent(id)?;
+        match self.get_value(&parent.id, &parent.property)? {
+            ObjectValue::VecSubObjects(subobjects) => get_next_id(&subobjects, id),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+
+use crate::hit_mod::Hit;
+use crate::object_data::ObjectValue;
+use crate::path::{get_field_path, get_object_path, resolve_path, PathTarget};
+use crate::{HitError, IndexEntryProperty, ValidationError};
+
+impl Hit {
+    // The object or the field at a path such as `/sub_items[2]/name`
+    pub fn resolve_path(&self, path: &str) -> Result<PathTarget, HitError> {
+        resolve_path(self, path)
+    }
+
+    // The path of an object from the main object, with the positions of the subobjects
+    pub fn path_of(&self, id: &str) -> Result<String, HitError> {
+        get_object_path(self, id)
+    }
+
+    pub fn path_of_field(&self, id: &str, property: &str) -> Result<String, HitError> {
+        get_field_path(self, id, property)
+    }
+
+    fn resolve_field_path(&self, path: &str) -> Result<IndexEntryProperty, HitError> {
+        match self.resolve_path(path)? {
+            PathTarget::Field(field) => Ok(field),
+            PathTarget::Object(_) => Err(HitError::InvalidPath(
+                path.to_string(),
+                "the path designates an object, not a field".into(),
+            )),
+        }
+    }
+
+    pub fn get_value_by_path(&self, path: &str) -> Result<Option<ObjectValue>, HitError> {
+        let field = self.resolve_field_path(path)?;
+        Ok(self.get_value(&field.id, &field.property))
+    }
+
+    pub fn set_by_path(&mut self, path: &str, value: ObjectValue) -> Result<(), HitError> {
+        let field = self.resolve_field_path(path)?;
+        self.set(&field.id, &field.property, value)
+    }
+
+    // The validation errors of the document, by path of the field
+    pub fn get_validation_report(&self) -> BTreeMap<String, Vec<ValidationError>> {
+        let mut report = BTreeMap::new();
+        for (id, property, errors) in self.errors.iter() {
+            if errors.is_empty() {
+                continue;
+            }
+            // the errors of the removed objects are ignored
+            if let Ok(path) = self.path_of_field(id, property) {
+                report.insert(path, errors.clone());
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,34 @@
+use crate::hit_mod::{Hit, HitEntry};
+use crate::object_data::Id;
+use crate::query::{get_candidates, matches, Query, QueryIndex};
+
+impl Hit {
+    // The ids of the objects that match the query, sorted
+    pub fn query(&self, query: &Query) -> Vec<Id> {
+        self.run_query(query, None)
+    }
+
+    // Same as `query`, the model conditions read the objects of the models from the index
+    // instead of the whole document
+    pub fn query_with_index(&self, query: &Query, index: &dyn QueryIndex) -> Vec<Id> {
+        self.run_query(query, Some(index))
+    }
+
+    pub fn query_entries(&self, query: &Query) -> Vec<HitEntry> {
+        self.query(query)
+            .iter()
+            .filter_map(|id| self.get(id))
+            .collect()
+    }
+
+    fn run_query(&self, query: &Query, index: Option<&dyn QueryIndex>) -> Vec<Id> {
+        let mut candidates = match get_candidates(self, query, index) {
+            Some(candidates) => candidates,
+            None => self.index.iter().map(|(id, _)| id.clone()).collect(),
+        };
+        candidates.sort();
+        candidates.dedup();
+        candidates.retain(|id| matches(self, id, query));
+        candidates
+    }
+}
@@ -0,0 +1,33 @@
+use std::ops::Bound;
+
+use crate::hit_mod::Hit;
+use crate::object_data::{Id, ObjectValue};
+use crate::HitError;
+
+impl Hit {
+    // The ids of the objects whose field has the value, or starts with it for a prefix index
+    pub fn index_lookup(&self, name: &str, value: &ObjectValue) -> Result<Vec<Id>, HitError> {
+        self.secondary_indexes.lookup(name, value)
+    }
+
+    // The ids of the objects whose field is between the bounds, with a range index
+    pub fn index_range(
+        &self,
+        name: &str,
+        min: Bound<&ObjectValue>,
+        max: Bound<&ObjectValue>,
+    ) -> Result<Vec<Id>, HitError> {
+        self.secondary_indexes.range(name, min, max)
+    }
+
+    // indexes the values of all the objects, once an import is finished
+    pub(crate) fn add_entries_to_secondary_indexes(&mut self) {
+        let model_index = self.model_index.borrow();
+        for (id, entry) in self.index.iter() {
+            if let Some(model) = model_index.map.get(id) {
+                self.secondary_indexes
+                    .add_entry(model, id, &entry.borrow().data);
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::hit_mod::Hit;
+use crate::model::field_types::FieldTypeStringVec;
+use crate::object_data::ObjectValue;
+use crate::HitError;
+
+// The changes of a list of strings are made by setting the whole list, so that they
+// are validated, notified, recorded in the history and emitted like any other value.
+impl Hit {
+    fn get_strings(&self, id: &str, property: &str) -> Result<Vec<String>, HitError> {
+        let model = self
+            .get_model(id)
+            .ok_or(HitError::IDNotFound(id.to_string(), "get_strings".into()))?;
+        let field = model
+            .get_field(property)
+            .ok_or(HitError::PropertyNotFound(property.to_string()))?;
+        if !field.borrow().is::<FieldTypeStringVec>() {
+            return Err(HitError::InvalidDataType());
+        }
+        match self.get_value(id, property) {
+            Some(ObjectValue::VecString(strings)) => Ok(strings),
+            _ => Ok(vec![]),
+        }
+    }
+
+    pub fn push_string(&mut self, id: &str, property: &str, value: &str) -> Result<(), HitError> {
+        let mut strings = self.get_strings(id, property)?;
+        strings.push(value.to_string());
+        self.set(id, property, ObjectValue::VecString(strings))
+    }
+
+    pub fn insert_string_at(
+        &mut self,
+        id: &str,
+        property: &str,
+        index: usize,
+        value: &str,
+    ) -> Result<(), HitError> {
+        let mut strings = self.get_strings(id, property)?;
+        if index > strings.len() {
+            return Err(HitError::InvalidStringIndex(index));
+        }
+        strings.insert(index, value.to_string());
+        self.set(id, property, ObjectValue::VecString(strings))
+    }
+
+    // returns the removed string
+    pub fn remove_string_at(
+        &mut self,
+        id: &str,
+        property: &str,
+        index: usize,
+    ) -> Result<String, HitError> {
+        let mut strings = self.get_strings(id, property)?;
+        if index >= strings.len() {
+            return Err(HitError::InvalidStringIndex(index));
+        }
+        let removed = strings.remove(index);
+        self.set(id, property, ObjectValue::VecString(strings))?;
+        Ok(removed)
+    }
+}
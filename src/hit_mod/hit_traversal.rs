@@ -0,0 +1,30 @@
+use crate::hit_mod::Hit;
+use crate::traversal::{get_child_entries, Ancestors, Descendants, TraversalEntry, TraversalOrder};
+
+impl Hit {
+    // The subobjects of an object and their own subobjects, in the order of the model fields
+    pub fn descendants(&self, id: &str, order: TraversalOrder) -> Descendants<'_> {
+        Descendants::new(self, id, order)
+    }
+
+    pub fn ancestors(&self, id: &str) -> Ancestors<'_> {
+        Ancestors::new(self, id)
+    }
+
+    // The subobjects of a subobject field
+    pub fn children(&self, id: &str, property: &str) -> impl Iterator<Item = TraversalEntry> {
+        get_child_entries(self, id, Some(property), 1).into_iter()
+    }
+
+    // The other objects of the parent field of an object
+    pub fn siblings(&self, id: &str) -> impl Iterator<Item = TraversalEntry> {
+        let siblings = match self.get_parent(id) {
+            Some(parent) => get_child_entries(self, &parent.id, Some(&parent.property), 0),
+            None => vec![],
+        };
+        let id = id.to_string();
+        siblings
+            .into_iter()
+            .filter(move |sibling| sibling.get_id() != id)
+    }
+}
@@ -1,7 +1,15 @@
 pub mod helpers;
 mod hit;
 mod hit_entry;
+mod hit_history;
 mod hit_importer;
+mod hit_operations;
+mod hit_path;
+mod hit_query;
+mod hit_secondary_index;
+mod hit_strings;
+mod hit_traversal;
+mod transaction;
 
 pub use self::hit::Hit;
 pub use self::hit::HitKernel;
@@ -0,0 +1,187 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::errors::ValidationError;
+use crate::hit_mod::Hit;
+use crate::index::IndexEntryProperty;
+use crate::model::Model;
+use crate::object_data::Id;
+use crate::operations::Operation;
+use crate::utils::UndoLog;
+use crate::HitError;
+
+// A change made to the models or the validation errors of the objects during a
+// transaction, undone on rollback. The changes of the entries are kept by the index.
+#[derive(Clone)]
+enum HitChange {
+    // the model of the object before it was changed
+    Model(Id, Option<Rc<Model>>),
+    // the validation errors of the field before they were changed
+    Errors(Id, String, Option<Vec<ValidationError>>),
+}
+
+#[derive(Clone)]
+pub(crate) struct TransactionState {
+    depth: usize,
+    // validation events are deferred like the field events of the index
+    pending_validations: Vec<IndexEntryProperty>,
+    pending_validation_ids: HashSet<(Id, String)>,
+    // operations are recorded once the outermost transaction is committed
+    pub pending_operations: Vec<Operation>,
+    changes: UndoLog<HitChange>,
+}
+
+impl TransactionState {
+    pub fn new() -> Self {
+        TransactionState {
+            depth: 0,
+            pending_validations: vec![],
+            pending_validation_ids: HashSet::new(),
+            pending_operations: vec![],
+            changes: UndoLog::new(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.depth > 0
+    }
+
+    pub fn add_pending_validation(&mut self, id: &str, property: &str) {
+        if self
+            .pending_validation_ids
+            .insert((id.to_string(), property.to_string()))
+        {
+            self.pending_validations.push(IndexEntryProperty {
+                id: id.to_string(),
+                property: property.to_string(),
+            });
+        }
+    }
+
+    fn take_pending_validations(&mut self) -> Vec<IndexEntryProperty> {
+        self.pending_validation_ids.clear();
+        self.pending_validations.drain(..).collect()
+    }
+}
+
+impl Hit {
+    // Runs all the mutations of the closure as a single unit : if the closure returns
+    // an error, the index, the secondary indexes, the models, the validation errors and
+    // the state of the transaction plugins are restored as they were before the call.
+    // Field and validation listeners are only notified once the outermost transaction
+    // is committed.
+    pub fn transaction<T, F>(&mut self, mutations: F) -> Result<T, HitError>
+    where
+        F: FnOnce(&mut Hit) -> Result<T, HitError>,
+    {
+        let is_outermost = !self.transaction.is_active();
+        self.index.begin_transaction();
+        self.secondary_indexes.begin_transaction();
+        self.transaction.changes.begin();
+        let pending_operations_count = self.transaction.pending_operations.len();
+        for plugin in self.plugins.transaction_plugins.iter() {
+            plugin.borrow_mut().on_begin_transaction();
+        }
+        self.index.defer_dispatches();
+        self.transaction.depth += 1;
+
+        let result = mutations(self);
+
+        self.transaction.depth -= 1;
+        match result {
+            Ok(value) => {
+                self.index.commit_transaction();
+                self.secondary_indexes.commit_transaction();
+                self.transaction.changes.commit();
+                for plugin in self.plugins.transaction_plugins.iter() {
+                    plugin.borrow_mut().on_commit_transaction();
+                }
+                if is_outermost {
+                    self.dispatch_pending_events();
+                }
+                Ok(value)
+            }
+            Err(error) => {
+                self.index.rollback_transaction();
+                self.secondary_indexes.rollback_transaction();
+                for change in self.transaction.changes.rollback() {
+                    self.undo_change(change);
+                }
+                self.transaction
+                    .pending_operations
+                    .truncate(pending_operations_count);
+                for plugin in self.plugins.transaction_plugins.iter() {
+                    plugin.borrow_mut().on_rollback_transaction();
+                }
+                if is_outermost {
+                    self.index.discard_dispatches();
+                    self.transaction.take_pending_validations();
+                }
+                Err(error)
+            }
+        }
+    }
+
+    pub fn is_in_transaction(&self) -> bool {
+        self.transaction.is_active()
+    }
+
+    // sets or removes the model of an object
+    pub(crate) fn set_object_model(&mut self, id: &str, model: Option<Rc<Model>>) {
+        let previous = {
+            let mut model_index = self.model_index.borrow_mut();
+            match model {
+                Some(model) => model_index.map.insert(id.to_string(), model),
+                None => model_index.map.remove(id),
+            }
+        };
+        self.transaction
+            .changes
+            .record(HitChange::Model(id.to_string(), previous));
+    }
+
+    // records the validation errors of a field before they are changed
+    pub(crate) fn record_errors_change(&mut self, id: &str, property: &str) {
+        if self.transaction.changes.is_recording() {
+            let errors = self.errors.get(id, property).cloned();
+            self.transaction.changes.record(HitChange::Errors(
+                id.to_string(),
+                property.to_string(),
+                errors,
+            ));
+        }
+    }
+
+    fn undo_change(&mut self, change: HitChange) {
+        match change {
+            HitChange::Model(id, model) => {
+                let mut model_index = self.model_index.borrow_mut();
+                match model {
+                    Some(model) => model_index.map.insert(id, model),
+                    None => model_index.map.remove(&id),
+                };
+            }
+            HitChange::Errors(id, property, errors) => {
+                self.errors.delete(&id, &property);
+                for error in errors.unwrap_or_default().into_iter() {
+                    self.errors.add(&id, &property, error);
+                }
+            }
+        }
+    }
+
+    fn dispatch_pending_events(&mut self) {
+        for target in self.index.take_pending_dispatches().iter() {
+            if let Some(value) = self.get_value(&target.id, &target.property) {
+                self.index
+                    .dispatch_field(&target.id, &target.property, value);
+            }
+        }
+        let pending_validations = self.transaction.take_pending_validations();
+        for target in pending_validations.iter() {
+            self.dispatch_validation_errors(&target.id, &target.property);
+        }
+        let pending_operations = self.transaction.pending_operations.drain(..).collect();
+        self.operations.record(pending_operations);
+    }
+}
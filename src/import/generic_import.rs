@@ -34,12 +34,37 @@ pub fn import_data_object_values<'index>(
         }
     }
 
-    new_index.add_item(model.get_name(), &id, new_data.clone(), parent.clone())?;
+    add_object_values(model, id, parent, new_index, new_data)
+}
+
+// imports the values as they are, Null values included, in the same order
+pub fn import_object_values(
+    model: Rc<Model>,
+    id: String,
+    parent: Option<IndexEntryProperty>,
+    new_index: &mut IndexModelImporter,
+    values: ObjectValues,
+) -> Result<(), HitError> {
+    for key in values.keys() {
+        //checks that the model field exists
+        get_model_field(model.clone(), key.clone())?;
+    }
+    add_object_values(model, id, parent, new_index, values)
+}
+
+fn add_object_values(
+    model: Rc<Model>,
+    id: String,
+    parent: Option<IndexEntryProperty>,
+    new_index: &mut IndexModelImporter,
+    values: ObjectValues,
+) -> Result<(), HitError> {
+    new_index.add_item(model.get_name(), &id, values.clone(), parent.clone())?;
 
     for plugin in new_index.get_plugins().init_plugins.iter() {
         plugin
             .borrow_mut()
-            .on_init_add_entry(model.clone(), &id, new_data.clone(), parent.clone());
+            .on_init_add_entry(model.clone(), &id, values.clone(), parent.clone());
     }
     return Ok(());
 }
@@ -0,0 +1,899 @@
+use std::collections::{HashMap, HashSet};
+
+use linked_hash_map::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+
+use super::patches::{get_entries, import_from_patches_and_entries};
+use crate::{AddedEntry, Hit, HitError, Id, IndexEntryProperty, ObjectValue, Reference};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MergeSide {
+    Ours,
+    Theirs,
+}
+
+// Each conflict is resolved automatically, the resolution is described for each case
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MergeConflict {
+    // both sides set a different value : our value is kept
+    Field {
+        id: Id,
+        property: String,
+        base: ObjectValue,
+        ours: ObjectValue,
+        theirs: ObjectValue,
+    },
+    // one side deleted an object that the other side edited (or that contains an edited object) :
+    // the object is kept
+    DeletedAndEdited { id: Id, deleted_by: MergeSide },
+    // both sides moved the object to a different place : our move is kept
+    Move {
+        id: Id,
+        base: Option<IndexEntryProperty>,
+        ours: Option<IndexEntryProperty>,
+        theirs: Option<IndexEntryProperty>,
+    },
+    // both sides changed the order of a list differently : our order is kept
+    Order { id: Id, property: String },
+    // the moves of both sides put the object inside its own descendants : their move is cancelled
+    MoveCycle { id: Id },
+    // the referenced object was deleted : the reference is removed
+    DanglingReference {
+        id: Id,
+        property: String,
+        reference: Id,
+    },
+}
+
+pub struct MergeResult {
+    pub hit: Hit,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+type Entries = LinkedHashMap<Id, AddedEntry>;
+
+fn merge_value<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T) -> Option<T> {
+    if ours == theirs || theirs == base {
+        Some(ours.clone())
+    } else if ours == base {
+        Some(theirs.clone())
+    } else {
+        None
+    }
+}
+
+fn is_list(value: &ObjectValue) -> bool {
+    matches!(
+        value,
+        ObjectValue::VecReference(_) | ObjectValue::VecSubObjects(_) | ObjectValue::SubObject(_)
+    )
+}
+
+fn is_structural(value: &ObjectValue) -> bool {
+    matches!(value, ObjectValue::VecSubObjects(_) | ObjectValue::SubObject(_))
+}
+
+fn get_ids(value: &ObjectValue) -> Vec<Id> {
+    match value {
+        ObjectValue::VecReference(references) | ObjectValue::VecSubObjects(references) => {
+            references.iter().map(|r| r.id.clone()).collect()
+        }
+        ObjectValue::SubObject(reference) => vec![reference.id.clone()],
+        _ => vec![],
+    }
+}
+
+fn get_value(entry: Option<&AddedEntry>, property: &str) -> ObjectValue {
+    entry
+        .and_then(|entry| entry.data.get(property).cloned())
+        .unwrap_or(ObjectValue::Null)
+}
+
+// the elements of list that are also in other, in the order of list
+fn keep_common(list: &[Id], other: &[Id]) -> Vec<Id> {
+    list.iter().filter(|id| other.contains(id)).cloned().collect()
+}
+
+// Three-way merge of a list of ids. The order of the side that reordered the common
+// elements is used, then the elements removed by the other side are removed and the
+// elements it added are inserted after their predecessor.
+// The second element is true if both sides reordered the list differently.
+fn merge_list(base: &[Id], ours: &[Id], theirs: &[Id]) -> (Vec<Id>, bool) {
+    let ours_reordered = keep_common(ours, base) != keep_common(base, ours);
+    let theirs_reordered = keep_common(theirs, base) != keep_common(base, theirs);
+    let conflict = ours_reordered
+        && theirs_reordered
+        && keep_common(&keep_common(ours, theirs), base)
+            != keep_common(&keep_common(theirs, ours), base);
+    let (primary, secondary) = if theirs_reordered && !ours_reordered {
+        (theirs, ours)
+    } else {
+        (ours, theirs)
+    };
+
+    let mut result: Vec<Id> = primary
+        .iter()
+        .filter(|id| !base.contains(id) || secondary.contains(id))
+        .cloned()
+        .collect();
+    for (position, id) in secondary.iter().enumerate() {
+        if base.contains(id) || result.contains(id) {
+            continue;
+        }
+        insert_after_predecessor(&mut result, secondary, position);
+    }
+    (result, conflict)
+}
+
+// inserts source[position] after the closest element that precedes it in source
+fn insert_after_predecessor(result: &mut Vec<Id>, source: &[Id], position: usize) {
+    let predecessor = source[..position]
+        .iter()
+        .rev()
+        .find_map(|previous| result.iter().position(|r| r == previous));
+    match predecessor {
+        Some(index) => result.insert(index + 1, source[position].clone()),
+        None => result.insert(0, source[position].clone()),
+    }
+}
+
+fn to_references(ids: Vec<Id>) -> Vec<Reference> {
+    ids.into_iter().map(|id| Reference { id }).collect()
+}
+
+// an object is edited if it was moved or if one of its values changed (its children excepted)
+fn is_edited(base: &AddedEntry, other: &AddedEntry) -> bool {
+    if base.parent != other.parent {
+        return true;
+    }
+    let mut properties = base.data.keys().chain(other.data.keys());
+    properties.any(|property| {
+        let base_value = get_value(Some(base), property);
+        let other_value = get_value(Some(other), property);
+        !is_structural(&base_value) && !is_structural(&other_value) && base_value != other_value
+    })
+}
+
+fn merge_entry(
+    base: Option<&AddedEntry>,
+    ours: &AddedEntry,
+    theirs: &AddedEntry,
+    conflicts: &mut Vec<MergeConflict>,
+) -> AddedEntry {
+    let base_parent = base.and_then(|base| base.parent.clone());
+    let parent = match merge_value(&base_parent, &ours.parent, &theirs.parent) {
+        Some(parent) => parent,
+        None => {
+            conflicts.push(MergeConflict::Move {
+                id: ours.id.clone(),
+                base: base_parent,
+                ours: ours.parent.clone(),
+                theirs: theirs.parent.clone(),
+            });
+            ours.parent.clone()
+        }
+    };
+
+    let mut data = ours.data.clone();
+    let properties: Vec<String> = ours
+        .data
+        .keys()
+        .chain(theirs.data.keys().filter(|key| !ours.data.contains_key(*key)))
+        .cloned()
+        .collect();
+    for property in properties.iter() {
+        let base_value = get_value(base, property);
+        let ours_value = get_value(Some(ours), property);
+        let theirs_value = get_value(Some(theirs), property);
+        // the subobject lists are rebuilt once all the parents are known
+        if is_structural(&ours_value) || is_structural(&theirs_value) {
+            continue;
+        }
+        let value = if is_list(&ours_value) || is_list(&theirs_value) {
+            let (ids, conflict) = merge_list(
+                &get_ids(&base_value),
+                &get_ids(&ours_value),
+                &get_ids(&theirs_value),
+            );
+            if conflict {
+                conflicts.push(MergeConflict::Order {
+                    id: ours.id.clone(),
+                    property: property.clone(),
+                });
+            }
+            if ids.is_empty() && ours_value == ObjectValue::Null {
+                ObjectValue::Null
+            } else {
+                ObjectValue::VecReference(to_references(ids))
+            }
+        } else {
+            match merge_value(&base_value, &ours_value, &theirs_value) {
+                Some(value) => value,
+                None => {
+                    conflicts.push(MergeConflict::Field {
+                        id: ours.id.clone(),
+                        property: property.clone(),
+                        base: base_value,
+                        ours: ours_value.clone(),
+                        theirs: theirs_value,
+                    });
+                    ours_value
+                }
+            }
+        };
+        data.insert(property.clone(), value);
+    }
+
+    AddedEntry {
+        id: ours.id.clone(),
+        data,
+        parent,
+        model: ours.model.clone(),
+    }
+}
+
+fn merge_entries(
+    base: &Entries,
+    ours: &Entries,
+    theirs: &Entries,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Entries {
+    let mut ids: Vec<&Id> = base.keys().collect();
+    for id in ours.keys().chain(theirs.keys()) {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    let mut merged = LinkedHashMap::new();
+    for id in ids {
+        let entry = match (base.get(id), ours.get(id), theirs.get(id)) {
+            (base, Some(ours), Some(theirs)) => Some(merge_entry(base, ours, theirs, conflicts)),
+            (None, Some(entry), None) | (None, None, Some(entry)) => Some(entry.clone()),
+            (Some(base), Some(ours), None) if is_edited(base, ours) => {
+                conflicts.push(MergeConflict::DeletedAndEdited {
+                    id: id.clone(),
+                    deleted_by: MergeSide::Theirs,
+                });
+                Some(ours.clone())
+            }
+            (Some(base), None, Some(theirs)) if is_edited(base, theirs) => {
+                conflicts.push(MergeConflict::DeletedAndEdited {
+                    id: id.clone(),
+                    deleted_by: MergeSide::Ours,
+                });
+                Some(theirs.clone())
+            }
+            _ => None,
+        };
+        if let Some(entry) = entry {
+            merged.insert(id.clone(), entry);
+        }
+    }
+    merged
+}
+
+// restores the deleted parents of the objects that were kept
+fn restore_deleted_parents(
+    merged: &mut Entries,
+    ours: &Entries,
+    theirs: &Entries,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    loop {
+        let missing_parent = merged.values().find_map(|entry| match &entry.parent {
+            Some(parent) if !merged.contains_key(&parent.id) => Some(parent.id.clone()),
+            _ => None,
+        });
+        let id = match missing_parent {
+            Some(id) => id,
+            None => return,
+        };
+        let (entry, deleted_by) = match (ours.get(&id), theirs.get(&id)) {
+            (Some(entry), _) => (entry.clone(), MergeSide::Theirs),
+            (None, Some(entry)) => (entry.clone(), MergeSide::Ours),
+            // an object cannot be moved into an object that does not exist on its side
+            (None, None) => return,
+        };
+        conflicts.push(MergeConflict::DeletedAndEdited { id: id.clone(), deleted_by });
+        merged.insert(id, entry);
+    }
+}
+
+// cancels their moves that would make an object its own ancestor
+fn cancel_cyclic_moves(
+    merged: &mut Entries,
+    base: &Entries,
+    ours: &Entries,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    let ids: Vec<Id> = merged.keys().cloned().collect();
+    for id in ids.iter() {
+        let mut visited = vec![id.clone()];
+        let mut current = merged.get(id).and_then(|entry| entry.parent.clone());
+        let mut cycle_start = None;
+        while let Some(parent) = current {
+            if let Some(position) = visited.iter().position(|visited| *visited == parent.id) {
+                cycle_start = Some(position);
+                break;
+            }
+            visited.push(parent.id.clone());
+            current = merged.get(&parent.id).and_then(|entry| entry.parent.clone());
+        }
+        // the objects before the cycle are only below it, their moves are kept
+        let cycle = match cycle_start {
+            Some(position) => &visited[position..],
+            None => continue,
+        };
+        // the move made by their side is the one to cancel
+        for cycle_id in cycle.iter() {
+            let base_parent = match base.get(cycle_id) {
+                Some(entry) => entry.parent.clone(),
+                // the objects created by one side have not been moved
+                None => continue,
+            };
+            let ours_parent = ours.get(cycle_id).and_then(|entry| entry.parent.clone());
+            if let Some(entry) = merged.get_mut(cycle_id) {
+                if entry.parent != ours_parent && ours_parent == base_parent {
+                    entry.parent = base_parent;
+                    conflicts.push(MergeConflict::MoveCycle {
+                        id: cycle_id.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+// rebuilds the subobject lists from the parents of the merged objects
+fn rebuild_subobject_lists(
+    merged: &mut Entries,
+    base: &Entries,
+    ours: &Entries,
+    theirs: &Entries,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    let mut children: HashMap<(Id, String), Vec<Id>> = HashMap::new();
+    for entry in merged.values() {
+        if let Some(parent) = &entry.parent {
+            children
+                .entry((parent.id.clone(), parent.property.clone()))
+                .or_default()
+                .push(entry.id.clone());
+        }
+    }
+
+    let ids: Vec<Id> = merged.keys().cloned().collect();
+    for id in ids.iter() {
+        let properties: Vec<String> = [base.get(id), ours.get(id), theirs.get(id)]
+            .iter()
+            .filter_map(|entry| *entry)
+            .flat_map(|entry| {
+                entry
+                    .data
+                    .iter()
+                    .filter(|(_, value)| is_structural(value))
+                    .map(|(key, _)| key.clone())
+            })
+            .chain(
+                children
+                    .keys()
+                    .filter(|(parent_id, _)| parent_id == id)
+                    .map(|(_, property)| property.clone()),
+            )
+            .collect();
+
+        for property in properties.iter() {
+            let property_children = children
+                .get(&(id.clone(), property.clone()))
+                .cloned()
+                .unwrap_or_default();
+            let sides = [
+                get_ids(&get_value(ours.get(id), property)),
+                get_ids(&get_value(theirs.get(id), property)),
+                get_ids(&get_value(base.get(id), property)),
+            ];
+            let (ids, conflict) = merge_list(&sides[2], &sides[0], &sides[1]);
+            let mut list: Vec<Id> = ids
+                .into_iter()
+                .filter(|child| property_children.contains(child))
+                .collect();
+            // the restored objects and the objects moved by the other side
+            for child in property_children.iter() {
+                if list.contains(child) {
+                    continue;
+                }
+                let side = sides.iter().find_map(|side| {
+                    side.iter()
+                        .position(|c| c == child)
+                        .map(|position| (side, position))
+                });
+                match side {
+                    Some((side, position)) => insert_after_predecessor(&mut list, side, position),
+                    None => list.push(child.clone()),
+                }
+            }
+            if conflict
+                && !conflicts.contains(&MergeConflict::Order {
+                    id: id.clone(),
+                    property: property.clone(),
+                })
+            {
+                conflicts.push(MergeConflict::Order {
+                    id: id.clone(),
+                    property: property.clone(),
+                });
+            }
+
+            let is_single = matches!(
+                get_value(ours.get(id).or(theirs.get(id)), property),
+                ObjectValue::SubObject(_)
+            );
+            let value = if list.is_empty() {
+                ObjectValue::Null
+            } else if is_single {
+                ObjectValue::SubObject(Reference {
+                    id: list[0].clone(),
+                })
+            } else {
+                ObjectValue::VecSubObjects(to_references(list))
+            };
+            if let Some(entry) = merged.get_mut(id) {
+                entry.data.insert(property.clone(), value);
+            }
+        }
+    }
+}
+
+fn remove_dangling_references(merged: &mut Entries, conflicts: &mut Vec<MergeConflict>) {
+    let existing: HashSet<Id> = merged.keys().cloned().collect();
+    for (_, entry) in merged.iter_mut() {
+        for (property, value) in entry.data.iter_mut() {
+            let dangling: Vec<Id> = match value {
+                ObjectValue::Reference(reference) => vec![reference.id.clone()],
+                ObjectValue::VecReference(references) => {
+                    references.iter().map(|r| r.id.clone()).collect()
+                }
+                _ => continue,
+            }
+            .into_iter()
+            .filter(|reference| !existing.contains(reference))
+            .collect();
+            for reference in dangling.iter() {
+                conflicts.push(MergeConflict::DanglingReference {
+                    id: entry.id.clone(),
+                    property: property.clone(),
+                    reference: reference.clone(),
+                });
+            }
+            if dangling.is_empty() {
+                continue;
+            }
+            *value = match value {
+                ObjectValue::VecReference(references) => ObjectValue::VecReference(
+                    references
+                        .iter()
+                        .filter(|r| !dangling.contains(&r.id))
+                        .cloned()
+                        .collect(),
+                ),
+                _ => ObjectValue::Null,
+            };
+        }
+    }
+}
+
+// Three-way merge of two versions of the base document.
+// The changes made on only one side are applied, the conflicting changes are
+// resolved automatically and reported in the result.
+pub fn merge(base: &Hit, ours: &Hit, theirs: &Hit) -> Result<MergeResult, HitError> {
+    let base_entries = get_entries(base);
+    let ours_entries = get_entries(ours);
+    let theirs_entries = get_entries(theirs);
+    let mut conflicts = vec![];
+
+    let mut merged = merge_entries(
+        &base_entries,
+        &ours_entries,
+        &theirs_entries,
+        &mut conflicts,
+    );
+    restore_deleted_parents(&mut merged, &ours_entries, &theirs_entries, &mut conflicts);
+    cancel_cyclic_moves(&mut merged, &base_entries, &ours_entries, &mut conflicts);
+    restore_deleted_parents(&mut merged, &ours_entries, &theirs_entries, &mut conflicts);
+    rebuild_subobject_lists(
+        &mut merged,
+        &base_entries,
+        &ours_entries,
+        &theirs_entries,
+        &mut conflicts,
+    );
+    remove_dangling_references(&mut merged, &mut conflicts);
+
+    let hit = import_from_patches_and_entries(
+        vec![],
+        ours.get_main_object_id(),
+        ours.kernel.clone(),
+        &mut merged,
+    )?;
+    Ok(MergeResult { hit, conflicts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_kernel::{create_test_kernel, sub_items};
+    use crate::LinkedHashMap;
+    use std::rc::Rc;
+
+    fn references(ids: &[&str]) -> Vec<Reference> {
+        ids.iter().map(|id| Reference { id: id.to_string() }).collect()
+    }
+
+    fn a_references() -> IndexEntryProperty {
+        IndexEntryProperty {
+            id: "a".to_string(),
+            property: "references".to_string(),
+        }
+    }
+
+    fn create_hit() -> Hit {
+        let mut hit = Hit::new("main", "test/test", Rc::new(create_test_kernel())).unwrap();
+        for (id, parent) in [("a", "main"), ("b", "main"), ("c", "main"), ("d", "a")].iter() {
+            hit.insert("test/test", id, LinkedHashMap::new(), sub_items(parent), None)
+                .unwrap();
+        }
+        hit.insert_reference("b", a_references(), None).unwrap();
+        hit
+    }
+
+    fn set_name(hit: &mut Hit, id: &str, name: &str) {
+        hit.set(id, "name", ObjectValue::String(name.to_string()))
+            .unwrap();
+    }
+
+    #[test]
+    fn it_should_combine_the_changes_of_both_sides() {
+        let base = create_hit();
+        let mut ours = create_hit();
+        set_name(&mut ours, "a", "ours");
+        ours.insert("test/test", "e", LinkedHashMap::new(), sub_items("main"), Some("b".into()))
+            .unwrap();
+        ours.insert_reference("c", a_references(), None).unwrap();
+        let mut theirs = create_hit();
+        set_name(&mut theirs, "b", "theirs");
+        theirs.move_object("d", sub_items("c"), None).unwrap();
+        theirs
+            .move_object("c", sub_items("main"), Some("a".into()))
+            .unwrap();
+        theirs.insert_reference("d", a_references(), Some("b".into()))
+            .unwrap();
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+
+        assert_eq!(result.conflicts, vec![]);
+        let hit = result.hit;
+        assert_eq!(
+            hit.get_value("a", "name"),
+            Some(ObjectValue::String("ours".into()))
+        );
+        assert_eq!(
+            hit.get_value("b", "name"),
+            Some(ObjectValue::String("theirs".into()))
+        );
+        assert_eq!(
+            hit.get_value("main", "sub_items"),
+            Some(ObjectValue::VecSubObjects(references(&["c", "a", "e", "b"])))
+        );
+        assert_eq!(
+            hit.get_value("c", "sub_items"),
+            Some(ObjectValue::VecSubObjects(references(&["d"])))
+        );
+        assert_eq!(hit.get_parent("d"), Some(sub_items("c")));
+        assert_eq!(
+            hit.get_value("a", "references"),
+            Some(ObjectValue::VecReference(references(&["d", "b", "c"])))
+        );
+        assert_eq!(hit.get_references("c").unwrap(), vec![a_references()]);
+    }
+
+    #[test]
+    fn it_should_keep_our_value_when_both_sides_set_a_field() {
+        let base = create_hit();
+        let mut ours = create_hit();
+        set_name(&mut ours, "a", "ours");
+        set_name(&mut ours, "b", "same");
+        let mut theirs = create_hit();
+        set_name(&mut theirs, "a", "theirs");
+        set_name(&mut theirs, "b", "same");
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+
+        assert_eq!(
+            result.conflicts,
+            vec![MergeConflict::Field {
+                id: "a".into(),
+                property: "name".into(),
+                base: ObjectValue::Null,
+                ours: ObjectValue::String("ours".into()),
+                theirs: ObjectValue::String("theirs".into()),
+            }]
+        );
+        assert_eq!(
+            result.hit.get_value("a", "name"),
+            Some(ObjectValue::String("ours".into()))
+        );
+    }
+
+    #[test]
+    fn it_should_keep_an_object_deleted_on_one_side_and_edited_on_the_other() {
+        let base = create_hit();
+        let mut ours = create_hit();
+        ours.remove_reference("b", a_references()).unwrap();
+        ours.remove_object("a").unwrap();
+        ours.remove_object("c").unwrap();
+        let mut theirs = create_hit();
+        set_name(&mut theirs, "d", "edited");
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+
+        // a is restored because d is kept
+        assert_eq!(
+            result.conflicts,
+            vec![
+                MergeConflict::DeletedAndEdited {
+                    id: "d".into(),
+                    deleted_by: MergeSide::Ours,
+                },
+                MergeConflict::DeletedAndEdited {
+                    id: "a".into(),
+                    deleted_by: MergeSide::Ours,
+                },
+            ]
+        );
+        let hit = result.hit;
+        assert!(hit.get("c").is_none());
+        assert_eq!(
+            hit.get_value("main", "sub_items"),
+            Some(ObjectValue::VecSubObjects(references(&["a", "b"])))
+        );
+        assert_eq!(
+            hit.get_value("d", "name"),
+            Some(ObjectValue::String("edited".into()))
+        );
+        // the restored object is the version of the side that kept it
+        assert_eq!(
+            hit.get_value("a", "references"),
+            Some(ObjectValue::VecReference(references(&["b"])))
+        );
+    }
+
+    #[test]
+    fn it_should_remove_the_references_to_deleted_objects() {
+        let base = create_hit();
+        let mut ours = create_hit();
+        ours.remove_object("c").unwrap();
+        let mut theirs = create_hit();
+        theirs.insert_reference("c", a_references(), None).unwrap();
+        theirs
+            .set(
+                "b",
+                "reference",
+                ObjectValue::Reference(Reference { id: "c".into() }),
+            )
+            .unwrap();
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+
+        assert_eq!(
+            result.conflicts,
+            vec![
+                MergeConflict::DanglingReference {
+                    id: "a".into(),
+                    property: "references".into(),
+                    reference: "c".into(),
+                },
+                MergeConflict::DanglingReference {
+                    id: "b".into(),
+                    property: "reference".into(),
+                    reference: "c".into(),
+                },
+            ]
+        );
+        let hit = result.hit;
+        assert!(hit.get("c").is_none());
+        assert_eq!(
+            hit.get_value("a", "references"),
+            Some(ObjectValue::VecReference(references(&["b"])))
+        );
+        assert_eq!(hit.get_value("b", "reference"), Some(ObjectValue::Null));
+    }
+
+    #[test]
+    fn it_should_cancel_their_move_when_both_moves_create_a_cycle() {
+        let base = create_hit();
+        let mut ours = create_hit();
+        ours.move_object("b", sub_items("c"), None).unwrap();
+        let mut theirs = create_hit();
+        theirs.move_object("c", sub_items("b"), None).unwrap();
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+
+        assert_eq!(
+            result.conflicts,
+            vec![MergeConflict::MoveCycle { id: "c".into() }]
+        );
+        let hit = result.hit;
+        assert_eq!(hit.get_parent("b"), Some(sub_items("c")));
+        assert_eq!(hit.get_parent("c"), Some(sub_items("main")));
+        assert_eq!(
+            hit.get_value("main", "sub_items"),
+            Some(ObjectValue::VecSubObjects(references(&["a", "c"])))
+        );
+    }
+
+    #[test]
+    fn it_should_only_cancel_the_moves_inside_the_cycle() {
+        let base = create_hit();
+        let mut ours = create_hit();
+        ours.move_object("c", sub_items("d"), None).unwrap();
+        let mut theirs = create_hit();
+        theirs.move_object("d", sub_items("c"), None).unwrap();
+        // b and the new object are below the cycle, they are not part of it
+        theirs.move_object("b", sub_items("d"), None).unwrap();
+        theirs
+            .insert("test/test", "b1", LinkedHashMap::new(), sub_items("d"), None)
+            .unwrap();
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+
+        assert_eq!(
+            result.conflicts,
+            vec![MergeConflict::MoveCycle { id: "d".into() }]
+        );
+        let hit = result.hit;
+        assert_eq!(hit.get_parent("d"), Some(sub_items("a")));
+        assert_eq!(hit.get_parent("c"), Some(sub_items("d")));
+        assert_eq!(hit.get_parent("b"), Some(sub_items("d")));
+        assert_eq!(hit.get_parent("b1"), Some(sub_items("d")));
+        assert_eq!(
+            hit.get_value("main", "sub_items"),
+            Some(ObjectValue::VecSubObjects(references(&["a"])))
+        );
+    }
+
+    #[test]
+    fn it_should_report_each_field_set_differently_on_both_sides() {
+        let reference = |id: &str| ObjectValue::Reference(Reference { id: id.into() });
+        let base = create_hit();
+        let mut ours = create_hit();
+        set_name(&mut ours, "a", "ours");
+        set_name(&mut ours, "c", "same");
+        ours.set("b", "reference", reference("c")).unwrap();
+        let mut theirs = create_hit();
+        set_name(&mut theirs, "a", "theirs");
+        set_name(&mut theirs, "c", "same");
+        set_name(&mut theirs, "d", "theirs only");
+        theirs.set("b", "reference", reference("d")).unwrap();
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+
+        assert_eq!(
+            result.conflicts,
+            vec![
+                MergeConflict::Field {
+                    id: "a".into(),
+                    property: "name".into(),
+                    base: ObjectValue::Null,
+                    ours: ObjectValue::String("ours".into()),
+                    theirs: ObjectValue::String("theirs".into()),
+                },
+                MergeConflict::Field {
+                    id: "b".into(),
+                    property: "reference".into(),
+                    base: ObjectValue::Null,
+                    ours: reference("c"),
+                    theirs: reference("d"),
+                },
+            ]
+        );
+        let hit = result.hit;
+        assert_eq!(hit.get_value("b", "reference"), Some(reference("c")));
+        assert_eq!(
+            hit.get_value("c", "name"),
+            Some(ObjectValue::String("same".into()))
+        );
+        assert_eq!(
+            hit.get_value("d", "name"),
+            Some(ObjectValue::String("theirs only".into()))
+        );
+        assert_eq!(
+            hit.get_references("c").unwrap(),
+            vec![IndexEntryProperty {
+                id: "b".into(),
+                property: "reference".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn it_should_keep_an_object_moved_on_one_side_and_deleted_on_the_other() {
+        let base = create_hit();
+        let mut ours = create_hit();
+        ours.move_object("c", sub_items("b"), None).unwrap();
+        let mut theirs = create_hit();
+        theirs.remove_object("c").unwrap();
+        // not edited by our side
+        theirs.remove_object("d").unwrap();
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+
+        assert_eq!(
+            result.conflicts,
+            vec![MergeConflict::DeletedAndEdited {
+                id: "c".into(),
+                deleted_by: MergeSide::Theirs,
+            }]
+        );
+        let hit = result.hit;
+        assert!(hit.get("d").is_none());
+        assert_eq!(hit.get_value("a", "sub_items"), Some(ObjectValue::Null));
+        assert_eq!(
+            hit.get_value("b", "sub_items"),
+            Some(ObjectValue::VecSubObjects(references(&["c"])))
+        );
+        assert_eq!(
+            hit.get_value("main", "sub_items"),
+            Some(ObjectValue::VecSubObjects(references(&["a", "b"])))
+        );
+    }
+
+    #[test]
+    fn it_should_merge_the_reorders_of_both_sides() {
+        let main_items = |hit: &Hit| hit.get_value("main", "sub_items");
+        let base = create_hit();
+        let mut ours = create_hit();
+        ours.move_object("c", sub_items("main"), Some("a".into()))
+            .unwrap();
+
+        // the same order on both sides
+        let mut theirs = create_hit();
+        theirs
+            .move_object("c", sub_items("main"), Some("a".into()))
+            .unwrap();
+        let result = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(result.conflicts, vec![]);
+        assert_eq!(
+            main_items(&result.hit),
+            Some(ObjectValue::VecSubObjects(references(&["c", "a", "b"])))
+        );
+
+        // a different order : our order is kept
+        let mut theirs = create_hit();
+        theirs.move_object("a", sub_items("main"), None).unwrap();
+        let result = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(
+            result.conflicts,
+            vec![MergeConflict::Order {
+                id: "main".into(),
+                property: "sub_items".into(),
+            }]
+        );
+        assert_eq!(
+            main_items(&result.hit),
+            Some(ObjectValue::VecSubObjects(references(&["c", "a", "b"])))
+        );
+
+        // an insertion on the other side follows its predecessor
+        let mut theirs = create_hit();
+        theirs
+            .insert("test/test", "e", LinkedHashMap::new(), sub_items("main"), Some("c".into()))
+            .unwrap();
+        let result = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(result.conflicts, vec![]);
+        assert_eq!(
+            main_items(&result.hit),
+            Some(ObjectValue::VecSubObjects(references(&["c", "a", "b", "e"])))
+        );
+    }
+}
@@ -1,6 +1,11 @@
 pub(crate) mod generic_import;
 mod patches;
 mod diff;
+mod structural_diff;
+mod merge;
 
 pub use diff::{Patch,AddedEntry,PatchPropertyDifference, create_patch};
 pub use patches::{import_from_patches, apply_patches, duplicate_hit};
+pub(crate) use patches::import_from_patches_and_entries;
+pub use structural_diff::{DiffChange, create_diff};
+pub use merge::{MergeConflict, MergeResult, MergeSide, merge};
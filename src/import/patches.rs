@@ -47,7 +47,7 @@ pub fn import_from_patches_and_entries<'a>(
     return Ok(hit);
 }
 
-pub fn apply_patches(hit: &Hit, patches: Vec<Patch>) -> Result<Hit, HitError> {
+pub(crate) fn get_entries(hit: &Hit) -> LinkedHashMap<String, AddedEntry> {
     let mut entries = LinkedHashMap::new();
     for entry in hit.index.iter() {
         let model = hit.get_model(&entry.0).unwrap();
@@ -58,6 +58,11 @@ pub fn apply_patches(hit: &Hit, patches: Vec<Patch>) -> Result<Hit, HitError> {
             model: model.get_name().to_string(),
         });
     }
+    entries
+}
+
+pub fn apply_patches(hit: &Hit, patches: Vec<Patch>) -> Result<Hit, HitError> {
+    let mut entries = get_entries(hit);
     import_from_patches_and_entries(patches, hit.get_main_object_id(), hit.kernel.clone(), &mut entries)
 }
 
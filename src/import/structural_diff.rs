@@ -0,0 +1,422 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Hit, HitError, Id, IndexEntryProperty, ObjectValue};
+
+// A change between two versions of a document, described in terms of the structure
+// of the document instead of raw property values
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DiffChange {
+    // the fields of the added object are reported as FieldChanged
+    Added {
+        id: Id,
+        model: String,
+        parent: Option<IndexEntryProperty>,
+        position: usize,
+    },
+    Removed {
+        id: Id,
+        model: String,
+        parent: Option<IndexEntryProperty>,
+        position: usize,
+    },
+    FieldChanged {
+        id: Id,
+        property: String,
+        old_value: ObjectValue,
+        new_value: ObjectValue,
+    },
+    Moved {
+        id: Id,
+        from: IndexEntryProperty,
+        to: IndexEntryProperty,
+        position: usize,
+    },
+    // the order of the objects or references present in both versions of the property changed
+    Reordered {
+        id: Id,
+        property: String,
+        old_order: Vec<Id>,
+        new_order: Vec<Id>,
+    },
+    ReferenceAdded {
+        id: Id,
+        property: String,
+        reference: Id,
+        position: usize,
+    },
+    ReferenceRemoved {
+        id: Id,
+        property: String,
+        reference: Id,
+    },
+}
+
+impl DiffChange {
+    // The path of the changed object or field in the new version of the document, or in the
+    // old version for the removed objects
+    pub fn get_path(&self, old: &Hit, new: &Hit) -> Result<String, HitError> {
+        match self {
+            DiffChange::Added { id, .. } | DiffChange::Moved { id, .. } => new.path_of(id),
+            DiffChange::Removed { id, .. } => old.path_of(id),
+            DiffChange::FieldChanged { id, property, .. }
+            | DiffChange::Reordered { id, property, .. }
+            | DiffChange::ReferenceAdded { id, property, .. }
+            | DiffChange::ReferenceRemoved { id, property, .. } => new.path_of_field(id, property),
+        }
+    }
+}
+
+fn get_ids(value: &ObjectValue) -> Vec<Id> {
+    match value {
+        ObjectValue::VecSubObjects(references) | ObjectValue::VecReference(references) => {
+            references.iter().map(|r| r.id.clone()).collect()
+        }
+        ObjectValue::SubObject(reference) => vec![reference.id.clone()],
+        _ => vec![],
+    }
+}
+
+fn get_position(hit: &Hit, id: &str) -> usize {
+    hit.get_parent_index(id).unwrap_or(0)
+}
+
+fn is_structural(value: &ObjectValue) -> bool {
+    matches!(
+        value,
+        ObjectValue::SubObject(_) | ObjectValue::VecSubObjects(_)
+    )
+}
+
+fn diff_order(
+    id: &str,
+    property: &str,
+    old_ids: &[Id],
+    new_ids: &[Id],
+    changes: &mut Vec<DiffChange>,
+) {
+    let old_order: Vec<Id> = old_ids
+        .iter()
+        .filter(|id| new_ids.contains(id))
+        .cloned()
+        .collect();
+    let new_order: Vec<Id> = new_ids
+        .iter()
+        .filter(|id| old_ids.contains(id))
+        .cloned()
+        .collect();
+    if old_order != new_order {
+        changes.push(DiffChange::Reordered {
+            id: id.to_string(),
+            property: property.to_string(),
+            old_order,
+            new_order,
+        });
+    }
+}
+
+fn diff_references(
+    id: &str,
+    property: &str,
+    old_value: &ObjectValue,
+    new_value: &ObjectValue,
+    changes: &mut Vec<DiffChange>,
+) {
+    let old_ids = get_ids(old_value);
+    let new_ids = get_ids(new_value);
+    for reference in old_ids.iter().filter(|r| !new_ids.contains(r)) {
+        changes.push(DiffChange::ReferenceRemoved {
+            id: id.to_string(),
+            property: property.to_string(),
+            reference: reference.clone(),
+        });
+    }
+    for (position, reference) in new_ids.iter().enumerate() {
+        if !old_ids.contains(reference) {
+            changes.push(DiffChange::ReferenceAdded {
+                id: id.to_string(),
+                property: property.to_string(),
+                reference: reference.clone(),
+                position,
+            });
+        }
+    }
+    diff_order(id, property, &old_ids, &new_ids, changes);
+}
+
+fn diff_added_entry(new: &Hit, id: &str, changes: &mut Vec<DiffChange>) -> Result<(), HitError> {
+    let entry = new
+        .get(id)
+        .ok_or(HitError::IDNotFound(id.to_string(), "create_diff".into()))?;
+    changes.push(DiffChange::Added {
+        id: id.to_string(),
+        model: entry.get_model().get_name().clone(),
+        parent: entry.get_parent(),
+        position: get_position(new, id),
+    });
+    for (property, value) in entry.entry.borrow().data.iter() {
+        if value == &ObjectValue::Null || is_structural(value) {
+            continue;
+        }
+        changes.push(DiffChange::FieldChanged {
+            id: id.to_string(),
+            property: property.clone(),
+            old_value: ObjectValue::Null,
+            new_value: value.clone(),
+        });
+    }
+    Ok(())
+}
+
+fn diff_existing_entry(
+    old: &Hit,
+    new: &Hit,
+    id: &str,
+    changes: &mut Vec<DiffChange>,
+) -> Result<(), HitError> {
+    let model = new
+        .get_model(id)
+        .ok_or(HitError::NoModelForId(id.to_string()))?;
+    match (old.get_parent(id), new.get_parent(id)) {
+        (Some(from), Some(to)) if from != to => changes.push(DiffChange::Moved {
+            id: id.to_string(),
+            from,
+            to,
+            position: get_position(new, id),
+        }),
+        _ => {}
+    }
+
+    for (property, _) in model.get_fields().iter() {
+        let old_value = old.get_value(id, property).unwrap_or(ObjectValue::Null);
+        let new_value = new.get_value(id, property).unwrap_or(ObjectValue::Null);
+        if old_value == new_value {
+            continue;
+        }
+        match (&old_value, &new_value) {
+            (ObjectValue::VecReference(_), _) | (_, ObjectValue::VecReference(_)) => {
+                diff_references(id, property, &old_value, &new_value, changes);
+            }
+            (ObjectValue::VecSubObjects(_), _)
+            | (_, ObjectValue::VecSubObjects(_))
+            | (ObjectValue::SubObject(_), _)
+            | (_, ObjectValue::SubObject(_)) => {
+                // added, removed and moved objects are reported on their own
+                diff_order(
+                    id,
+                    property,
+                    &get_ids(&old_value),
+                    &get_ids(&new_value),
+                    changes,
+                );
+            }
+            _ => changes.push(DiffChange::FieldChanged {
+                id: id.to_string(),
+                property: property.clone(),
+                old_value,
+                new_value,
+            }),
+        }
+    }
+    Ok(())
+}
+
+// Lists the changes from old to new. The objects of the new version are visited
+// parents first, then the removed objects of the old version.
+pub fn create_diff(old: &Hit, new: &Hit) -> Result<Vec<DiffChange>, HitError> {
+    let mut changes = vec![];
+    let (_, new_ids) = new.find_references_recursive(new.get_main_object_id())?;
+    for id in new_ids.iter() {
+        if old.contains_key(id) {
+            diff_existing_entry(old, new, id, &mut changes)?;
+        } else {
+            diff_added_entry(new, id, &mut changes)?;
+        }
+    }
+
+    let (_, old_ids) = old.find_references_recursive(old.get_main_object_id())?;
+    for id in old_ids.iter().filter(|id| !new.contains_key(id)) {
+        let model = old
+            .get_model(id)
+            .ok_or(HitError::NoModelForId(id.to_string()))?;
+        changes.push(DiffChange::Removed {
+            id: id.clone(),
+            model: model.get_name().clone(),
+            parent: old.get_parent(id),
+            position: get_position(old, id),
+        });
+    }
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_kernel::{create_test_kernel, sub_items};
+    use crate::{LinkedHashMap, Reference};
+    use std::rc::Rc;
+
+    fn references(ids: &[&str]) -> Vec<Reference> {
+        ids.iter().map(|id| Reference { id: id.to_string() }).collect()
+    }
+
+    fn create_hit() -> Hit {
+        let mut hit = Hit::new("main", "test/test", Rc::new(create_test_kernel())).unwrap();
+        for (id, parent) in [("a", "main"), ("b", "main"), ("c", "main"), ("d", "a")].iter() {
+            hit.insert("test/test", id, LinkedHashMap::new(), sub_items(parent), None)
+                .unwrap();
+        }
+        hit.insert_reference(
+            "b",
+            IndexEntryProperty {
+                id: "a".to_string(),
+                property: "references".to_string(),
+            },
+            None,
+        )
+        .unwrap();
+        hit
+    }
+
+    #[test]
+    fn it_should_not_report_anything_for_identical_documents() {
+        let old = create_hit();
+        let new = create_hit();
+        assert_eq!(create_diff(&old, &new), Ok(vec![]));
+    }
+
+    #[test]
+    fn it_should_report_moves_and_reorders() {
+        let old = create_hit();
+        let mut new = create_hit();
+        new.move_object("d", sub_items("c"), None).unwrap();
+        new.move_object("c", sub_items("main"), Some("a".to_string()))
+            .unwrap();
+
+        let changes = create_diff(&old, &new).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                DiffChange::Reordered {
+                    id: "main".to_string(),
+                    property: "sub_items".to_string(),
+                    old_order: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                    new_order: vec!["c".to_string(), "a".to_string(), "b".to_string()],
+                },
+                DiffChange::Moved {
+                    id: "d".to_string(),
+                    from: sub_items("a"),
+                    to: sub_items("c"),
+                    position: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_report_reference_changes() {
+        let old = create_hit();
+        let mut new = create_hit();
+        let target = IndexEntryProperty {
+            id: "a".to_string(),
+            property: "references".to_string(),
+        };
+        new.insert_reference("c", target.clone(), Some("b".to_string()))
+            .unwrap();
+        new.remove_reference("b", target.clone()).unwrap();
+        new.set(
+            "b",
+            "reference",
+            ObjectValue::Reference(Reference {
+                id: "c".to_string(),
+            }),
+        )
+        .unwrap();
+
+        let changes = create_diff(&old, &new).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                DiffChange::ReferenceRemoved {
+                    id: "a".to_string(),
+                    property: "references".to_string(),
+                    reference: "b".to_string(),
+                },
+                DiffChange::ReferenceAdded {
+                    id: "a".to_string(),
+                    property: "references".to_string(),
+                    reference: "c".to_string(),
+                    position: 0,
+                },
+                DiffChange::FieldChanged {
+                    id: "b".to_string(),
+                    property: "reference".to_string(),
+                    old_value: ObjectValue::Null,
+                    new_value: ObjectValue::Reference(Reference {
+                        id: "c".to_string()
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_report_added_and_removed_objects_with_their_fields() {
+        let old = create_hit();
+        let mut new = create_hit();
+        new.remove_object("a").unwrap();
+        let mut values = LinkedHashMap::new();
+        values.insert("name".to_string(), ObjectValue::String("e".to_string()));
+        new.insert("test/test", "e", values, sub_items("main"), Some("b".to_string()))
+            .unwrap();
+
+        let changes = create_diff(&old, &new).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                DiffChange::Added {
+                    id: "e".to_string(),
+                    model: "test/test".to_string(),
+                    parent: Some(sub_items("main")),
+                    position: 0,
+                },
+                DiffChange::FieldChanged {
+                    id: "e".to_string(),
+                    property: "name".to_string(),
+                    old_value: ObjectValue::Null,
+                    new_value: ObjectValue::String("e".to_string()),
+                },
+                DiffChange::Removed {
+                    id: "a".to_string(),
+                    model: "test/test".to_string(),
+                    parent: Some(sub_items("main")),
+                    position: 0,
+                },
+                DiffChange::Removed {
+                    id: "d".to_string(),
+                    model: "test/test".to_string(),
+                    parent: Some(sub_items("a")),
+                    position: 0,
+                },
+            ]
+        );
+        let paths: Vec<String> = changes
+            .iter()
+            .map(|change| change.get_path(&old, &new).unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/sub_items[0]",
+                "/sub_items[0]/name",
+                "/sub_items[0]",
+                "/sub_items[0]/sub_items[0]"
+            ]
+        );
+        // the old version can still be used
+        assert!(old.get("a").is_some());
+        assert_eq!(
+            old.get_value("main", "sub_items"),
+            Some(ObjectValue::VecSubObjects(references(&["a", "b", "c"])))
+        );
+    }
+}
@@ -1,31 +1,43 @@
 use crate::index::list_helpers::{
-    get_parent_index_entry, get_parent_property_value, mutate_insert_in_reference_array,
+    dispatch_event, get_parent_index_entry, get_parent_property_value,
+    mutate_insert_in_reference_array,
 };
-use crate::index::move_object::move_object;
+use crate::index::move_object::{move_object, move_object_to_slot};
 use crate::index::reference_helpers::remove_reference_from_parent_array_from_property;
 use crate::index::reference_index_helpers::{
     index_object_references, index_reference, unindex_reference, unindex_reference_from_property,
 };
 use crate::index::remove_helpers::{get_references, remove_object_helper};
-use crate::index::subobject_helpers::insert_subobject_in_array;
+use crate::index::subobject_helpers::{insert_subobject_in_array, set_subobject_in_slot};
 use crate::index::{IndexEntry, IndexEntryProperty, IndexEntryRef};
 use crate::object_data::Id;
 use crate::object_data::ObjectValue;
 use crate::object_data::ObjectValues;
 use crate::object_data::Reference;
+use crate::utils::UndoLog;
 use crate::HitError;
 use std::collections::BTreeMap;
-use std::collections::{btree_map::Iter, HashMap};
+use std::collections::{btree_map::Iter, HashMap, HashSet};
 
 use super::{
-    find_references_before_deletion::find_references_recursive,
+    find_references_before_deletion::find_references_recursive, index_changes::IndexChange,
     reference_helpers::mutate_remove_from_reference_array,
 };
 
+// the fields that changed while the events are deferred, in the order of their first change
+#[derive(Clone, Default)]
+struct PendingDispatches {
+    targets: Vec<IndexEntryProperty>,
+    ids: HashSet<(Id, String)>,
+}
+
 #[derive(Clone)]
 pub struct Index {
     pub(in crate) index: BTreeMap<Id, IndexEntryRef>,
     id: Id,
+    // when set, field events are queued instead of being dispatched (see transactions)
+    pending_dispatches: Option<PendingDispatches>,
+    undo_log: UndoLog<IndexChange>,
 }
 
 impl Index {
@@ -33,13 +45,22 @@ impl Index {
         Index {
             index: BTreeMap::new(),
             id: id.to_string(),
+            pending_dispatches: None,
+            undo_log: UndoLog::new(),
         }
     }
 
+    // the id of the main object can be read after the objects during an import
+    pub(in crate::index) fn set_main_object_id(&mut self, id: &str) {
+        self.id = id.to_string();
+    }
+
     pub fn new(id: &str, values: ObjectValues) -> Result<Index, HitError> {
         let mut index = Index {
             index: BTreeMap::new(),
             id: id.to_string(),
+            pending_dispatches: None,
+            undo_log: UndoLog::new(),
         };
         //Disallow references and subobjects
         for (_, value) in values.iter() {
@@ -100,15 +121,20 @@ impl Index {
             ObjectValue::Bool(_) => {}
             ObjectValue::Date(_) => {}
             ObjectValue::F32(_) => {}
+            ObjectValue::I32(_) => {}
             ObjectValue::Reference(value) => {
                 index_reference(self, &value, property, id)?;
             }
             ObjectValue::String(_) => {}
+            ObjectValue::VecString(_) => {}
             _ => return Err(HitError::CanOnlySetScalarValues()),
         }
 
-        let entry = self.get(id).ok_or(HitError::IDNotFound(id.to_string(), "set_value".into()))?;
-        entry.borrow_mut().set(property, value)?;
+        let entry = self
+            .get_for_update(id)
+            .ok_or(HitError::IDNotFound(id.to_string(), "set_value".into()))?;
+        entry.borrow_mut().set(property, value.clone())?;
+        self.dispatch_value(entry, property, value);
         Ok(())
     }
 
@@ -131,10 +157,32 @@ impl Index {
         //dispatch value to parent property
         let (entry, parent) =
             get_parent_index_entry(self, &id)?.ok_or(HitError::InvalidParentID(id.to_string()))?;
-        Index::dispatch_value_property(entry, &parent.property);
+        self.dispatch_value_property(entry, &parent.property);
         Ok(())
     }
 
+    // inserts the object in a single subobject field, which must be empty
+    pub fn insert_in_slot(
+        &mut self,
+        id: &str,
+        values: ObjectValues,
+        parent: IndexEntryProperty,
+    ) -> Result<(), HitError> {
+        match self.get_value(&parent.id, &parent.property) {
+            None | Some(ObjectValue::Null) => {}
+            Some(_) => {
+                return Err(HitError::OccupiedSubobjectField(
+                    parent.id,
+                    parent.property,
+                ))
+            }
+        }
+        self.insert_raw(id, values.clone(), Some(parent.clone()))?;
+        index_object_references(self, values, id)?;
+        set_subobject_in_slot(self, parent.clone(), id)?;
+        dispatch_event(self, &parent.id, &parent.property)
+    }
+
     /**
      * Used for import
      */
@@ -148,10 +196,7 @@ impl Index {
         if self.index.contains_key(id) {
             return Err(HitError::DuplicateID(id.to_string()));
         }
-        self.index.insert(
-            id.to_string(),
-            IndexEntry::new(id.to_string(), values, parent.clone()),
-        );
+        self.insert_entry(id, IndexEntry::new(id.to_string(), values, parent.clone()));
         Ok(())
     }
 
@@ -181,7 +226,7 @@ impl Index {
         before_id: Option<Id>,
     ) -> Result<(), HitError> {
         let target_entry = {
-            self.get_mut(&target.id)
+            self.get_for_update(&target.id)
                 .ok_or(HitError::IDNotFound(target.id.to_string(), "move_reference".into()))?
         };
         let data = get_parent_property_value(&target_entry, &target);
@@ -216,8 +261,8 @@ impl Index {
             ObjectValue::VecReference(data.clone()),
         );
 
-        Index::dispatch_value(
-            target_entry.clone(),
+        self.dispatch_value(
+            target_entry,
             &target.property,
             ObjectValue::VecReference(data),
         );
@@ -232,7 +277,7 @@ impl Index {
     ) -> Result<(), HitError> {
         {
             let target_entry = {
-                self.get_mut(&target.id)
+                self.get_for_update(&target.id)
                     .ok_or(HitError::IDNotFound(target.id.to_string(), "insert_reference".into()))?
             };
             let data = get_parent_property_value(&target_entry, &target);
@@ -269,11 +314,11 @@ impl Index {
         //send the value as an event
         {
             let target_entry = {
-                self.get_mut(&target.id)
+                self.get(&target.id)
                     .ok_or(HitError::IDNotFound(target.id.to_string(), "insert_reference".into()))?
             };
             let data = get_parent_property_value(&target_entry, &target);
-            Index::dispatch_value(target_entry.clone(), &target.property, data);
+            self.dispatch_value(target_entry, &target.property, data);
         }
 
         Ok(())
@@ -291,7 +336,7 @@ impl Index {
         let entry = self
             .get(&parent.clone().id)
             .ok_or(HitError::IDNotFound(parent.clone().id.to_string(), "remove_reference".into()))?;
-        Index::dispatch_value(entry, &parent.property, value);
+        self.dispatch_value(entry, &parent.property, value);
         Ok(())
     }
 
@@ -317,7 +362,7 @@ impl Index {
         remove_object_helper(self, id)?;
 
         //remove from ref index the references in the object's data
-        Index::dispatch_value_property(parent_entry, &parent.property);
+        self.dispatch_value_property(parent_entry, &parent.property);
         Ok(id_list)
     }
 
@@ -330,13 +375,106 @@ impl Index {
         move_object(self, id, property, before_id)
     }
 
-    fn dispatch_value_property(entry: IndexEntryRef, property: &str) {
+    pub fn move_object_to_slot(
+        &mut self,
+        id: &str,
+        property: IndexEntryProperty,
+    ) -> Result<(), HitError> {
+        move_object_to_slot(self, id, property)
+    }
+
+    fn dispatch_value_property(&mut self, entry: IndexEntryRef, property: &str) {
         let value = entry.borrow().get(property).clone();
-        Index::dispatch_value(entry, property, value)
+        self.dispatch_value(entry, property, value)
+    }
+
+    pub(in crate::index) fn dispatch_value(
+        &mut self,
+        entry: IndexEntryRef,
+        property: &str,
+        value: ObjectValue,
+    ) {
+        match &mut self.pending_dispatches {
+            Some(pending) => {
+                let id = entry.borrow().get_id().to_string();
+                if pending.ids.insert((id.clone(), property.to_string())) {
+                    pending.targets.push(IndexEntryProperty {
+                        id,
+                        property: property.to_string(),
+                    });
+                }
+            }
+            None => entry.borrow_mut().dispatch_value(property, value),
+        }
+    }
+
+    pub(crate) fn defer_dispatches(&mut self) {
+        if self.pending_dispatches.is_none() {
+            self.pending_dispatches = Some(PendingDispatches::default());
+        }
+    }
+
+    // the fields that changed while the events were deferred, their current value is sent
+    // once the events are dispatched again
+    pub(crate) fn take_pending_dispatches(&mut self) -> Vec<IndexEntryProperty> {
+        self.pending_dispatches
+            .take()
+            .map(|pending| pending.targets)
+            .unwrap_or_default()
+    }
+
+    // sends a value that is not stored in the entry, such as the backlinks
+    pub(crate) fn dispatch_field(&mut self, id: &str, property: &str, value: ObjectValue) {
+        if let Some(entry) = self.get(id) {
+            self.dispatch_value(entry, property, value);
+        }
+    }
+
+    pub(crate) fn discard_dispatches(&mut self) {
+        self.pending_dispatches = None;
+    }
+
+    // records the state of an entry before it is changed, to restore it on rollback
+    pub(in crate::index) fn record_update(&mut self, entry: &IndexEntryRef) {
+        if self.undo_log.is_recording() {
+            let state = entry.borrow().get_state();
+            self.undo_log
+                .record(IndexChange::Updated(entry.clone(), state));
+        }
+    }
+
+    // the entry, whose state is recorded before the caller changes it
+    pub(in crate::index) fn get_for_update(&mut self, id: &str) -> Option<IndexEntryRef> {
+        let entry = self.get(id)?;
+        self.record_update(&entry);
+        Some(entry)
+    }
+
+    pub(crate) fn insert_entry(&mut self, id: &str, entry: IndexEntryRef) {
+        self.index.insert(id.to_string(), entry);
+        self.undo_log.record(IndexChange::Inserted(id.to_string()));
+    }
+
+    pub(in crate::index) fn remove_entry(&mut self, id: &str) {
+        if let Some(entry) = self.index.remove(id) {
+            self.undo_log
+                .record(IndexChange::Removed(id.to_string(), entry));
+        }
+    }
+
+    // only the entries changed by the transaction are restored on rollback
+    pub(crate) fn begin_transaction(&mut self) {
+        self.undo_log.begin();
     }
 
-    fn dispatch_value(entry: IndexEntryRef, property: &str, value: ObjectValue) {
-        entry.borrow_mut().dispatch_value(property, value);
+    pub(crate) fn commit_transaction(&mut self) {
+        self.undo_log.commit();
+    }
+
+    pub(crate) fn rollback_transaction(&mut self) {
+        for change in self.undo_log.rollback() {
+            change.undo(&mut self.index);
+        }
     }
 }
 
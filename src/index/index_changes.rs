@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use crate::index::index_entry::IndexEntryState;
+use crate::index::IndexEntryRef;
+use crate::object_data::Id;
+
+// A change made to the index during a transaction, undone on rollback.
+// The entries are kept by reference so that the listeners of restored
+// (including deleted) entries are still attached after a rollback
+#[derive(Clone)]
+pub(in crate::index) enum IndexChange {
+    // the state of the entry before it was changed
+    Updated(IndexEntryRef, IndexEntryState),
+    Inserted(Id),
+    Removed(Id, IndexEntryRef),
+}
+
+impl IndexChange {
+    pub(in crate::index) fn undo(self, entries: &mut BTreeMap<Id, IndexEntryRef>) {
+        match self {
+            IndexChange::Updated(entry, state) => entry.borrow_mut().restore_state(state),
+            IndexChange::Inserted(id) => {
+                entries.remove(&id);
+            }
+            IndexChange::Removed(id, entry) => {
+                entries.insert(id, entry);
+            }
+        }
+    }
+}
@@ -16,6 +16,14 @@ pub struct IndexEntryProperty {
     pub property: String,
 }
 
+// copy of an entry without its listeners, used to roll back transactions
+#[derive(Clone)]
+pub(crate) struct IndexEntryState {
+    data: ObjectValues,
+    parent: Option<IndexEntryProperty>,
+    references: Vec<IndexEntryProperty>,
+}
+
 pub struct IndexEntry {
     id: Id,
     pub(in crate) data: ObjectValues,
@@ -78,8 +86,7 @@ impl IndexEntry {
         property: &str,
         value: ObjectValue,
     ) -> Result<(), HitError> {
-        self.data.insert(property.to_string(), value.clone());
-        self.dispatch_value(property, value);
+        self.data.insert(property.to_string(), value);
         Ok(())
     }
 
@@ -88,6 +95,20 @@ impl IndexEntry {
             .dispatch_value(&property, &value.clone());
     }
 
+    pub(in crate::index) fn get_state(&self) -> IndexEntryState {
+        IndexEntryState {
+            data: self.data.clone(),
+            parent: self.parent.clone(),
+            references: self.references.clone(),
+        }
+    }
+
+    pub(in crate::index) fn restore_state(&mut self, state: IndexEntryState) {
+        self.data = state.data;
+        self.parent = state.parent;
+        self.references = state.references;
+    }
+
     pub fn add_listener(&mut self, property: &str, listener: FieldListenerRef<ObjectValue>) {
         self.property_change_listeners.insert(property, listener);
     }
@@ -16,6 +16,9 @@ impl IndexImporter {
         }
     }
 
+    pub fn set_main_object_id(&mut self, id: &str) {
+        self.index.set_main_object_id(id);
+    }
 
     pub fn add_item(
         &mut self,
@@ -31,10 +31,10 @@ pub fn get_parent_index_entry_from_parent(
     }
 }
 
-pub fn dispatch_event(index: &Index, id: &str, property: &str) -> Result<(), HitError> {
+pub fn dispatch_event(index: &mut Index, id: &str, property: &str) -> Result<(), HitError> {
     let entry = index.get(id).ok_or(HitError::IDNotFound(id.to_string(), "dispatch_event".to_string()))?;
     let value = entry.borrow().get(&property).clone();
-    entry.borrow_mut().dispatch_value(&property, value);
+    index.dispatch_value(entry, &property, value);
     Ok(())
 }
 
@@ -1,5 +1,6 @@
 mod find_references_before_deletion;
 mod index;
+mod index_changes;
 mod index_entry;
 mod index_importer;
 mod list_helpers;
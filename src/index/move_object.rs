@@ -1,8 +1,9 @@
 use crate::index::list_helpers::dispatch_event;
 use crate::index::subobject_helpers::{
-    insert_subobject_in_array, remove_subobject_from_parent_array,
+    insert_subobject_in_array, remove_subobject_from_parent_array, set_subobject_in_slot,
 };
 use crate::index::{Index, IndexEntryProperty};
+use crate::object_data::ObjectValue;
 use crate::HitError;
 
 fn set_object_parent(
@@ -11,7 +12,7 @@ fn set_object_parent(
     new_parent: IndexEntryProperty,
 ) -> Result<(), HitError> {
     index
-        .get_mut(id)
+        .get_for_update(id)
         .ok_or(HitError::IDNotFound(id.to_string(), "set_object_parent".to_string()))?
         .borrow_mut()
         .set_parent(Some(new_parent));
@@ -83,7 +84,41 @@ pub fn move_object(
     }
 
     _can_move_object(index, id, target_parent.clone(), before_id.clone())?;
+    _move_object(index, id, target_parent, |index, target_parent| {
+        insert_subobject_in_array(index, target_parent, id, before_id)
+    })
+}
+
+// moves the object to a single subobject field, which must be empty
+pub fn move_object_to_slot(
+    index: &mut Index,
+    id: &str,
+    target_parent: IndexEntryProperty,
+) -> Result<(), HitError> {
+    _can_move_object(index, id, target_parent.clone(), None)?;
+    match index.get_value(&target_parent.id, &target_parent.property) {
+        None | Some(ObjectValue::Null) => {}
+        Some(_) => {
+            return Err(HitError::OccupiedSubobjectField(
+                target_parent.id,
+                target_parent.property,
+            ))
+        }
+    }
+    _move_object(index, id, target_parent, |index, target_parent| {
+        set_subobject_in_slot(index, target_parent, id)
+    })
+}
 
+fn _move_object<F>(
+    index: &mut Index,
+    id: &str,
+    target_parent: IndexEntryProperty,
+    insert: F,
+) -> Result<(), HitError>
+where
+    F: FnOnce(&mut Index, IndexEntryProperty) -> Result<(), HitError>,
+{
     let original_parent = index
         .get(id)
         .ok_or(HitError::IDNotFound(id.to_string(), "move_object".to_string()))?
@@ -94,7 +129,7 @@ pub fn move_object(
 
     set_object_parent(index, id, target_parent.clone())?;
 
-    insert_subobject_in_array(index, target_parent.clone(), id, before_id)?;
+    insert(index, target_parent.clone())?;
 
     //dispatch event
     dispatch_event(index, &target_parent.id, &target_parent.property)?;
@@ -5,13 +5,13 @@ use crate::object_data::Reference;
 use crate::HitError;
 
 pub fn index_reference(
-    index: &Index,
+    index: &mut Index,
     reference: &Reference,
     key: &str,
     id: &str,
 ) -> Result<(), HitError> {
     let entry = index
-        .get(&reference.id)
+        .get_for_update(&reference.id)
         .ok_or(HitError::InvalidReference(reference.id.to_string()))?;
     entry.borrow_mut().references.push(IndexEntryProperty {
         id: id.to_string(),
@@ -46,7 +46,9 @@ pub fn unindex_reference(
     parent: IndexEntryProperty,
     id: &str,
 ) -> Result<(), HitError> {
-    let entry = index.get(id).ok_or(HitError::IDNotFound(id.to_string(), "unindex_reference".to_string()))?;
+    let entry = index
+        .get_for_update(id)
+        .ok_or(HitError::IDNotFound(id.to_string(), "unindex_reference".to_string()))?;
     entry.borrow_mut().references.retain(|x| x != &parent);
     Ok(())
 }
@@ -87,3 +89,36 @@ pub fn unindex_reference_from_property(
     }
     Ok(())
 }
+
+// removes the references held by a deleted object from the reference index of their targets
+pub fn unindex_object_references(index: &mut Index, id: &str) -> Result<(), HitError> {
+    let data = {
+        let entry = index.get(id).ok_or(HitError::IDNotFound(
+            id.to_string(),
+            "unindex_object_references".to_string(),
+        ))?;
+        let entry = entry.borrow();
+        entry.data.clone()
+    };
+    for (key, value) in data.iter() {
+        let references = match value {
+            ObjectValue::Reference(reference) => vec![reference.clone()],
+            ObjectValue::VecReference(references) => references.clone(),
+            _ => continue,
+        };
+        for reference in references.iter() {
+            // the target may be a child that was already deleted
+            if index.contains(&reference.id) {
+                unindex_reference(
+                    index,
+                    IndexEntryProperty {
+                        id: id.to_string(),
+                        property: key.to_string(),
+                    },
+                    &reference.id,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
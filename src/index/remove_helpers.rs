@@ -1,3 +1,4 @@
+use crate::index::reference_index_helpers::unindex_object_references;
 use crate::index::subobject_helpers::remove_subobject_from_parent_array;
 use crate::index::Index;
 use crate::index::IndexEntryProperty;
@@ -12,11 +13,14 @@ pub fn get_references(index: &Index, id: &str) -> Result<Vec<IndexEntryProperty>
 
 pub fn remove_object_helper(index: &mut Index, id: &str) -> Result<(), HitError> {
     remove_object_children(index, id)?;
+    // otherwise the targets keep the removed object in their references, and can
+    // never be removed, nor referenced again by an object that gets the same id
+    unindex_object_references(index, id)?;
 
     //remove object from id list in parent
     remove_subobject_from_parent_array(index, id)?;
     //remove object from index
-    index.index.remove(&id.to_string());
+    index.remove_entry(id);
 
     Ok(())
 }
@@ -19,6 +19,7 @@ pub fn insert_subobject_in_array(
     let (parent_index_entry, parent) = get_parent_index_entry_from_parent(index, parent)?;
     let reference_array = get_parent_property_value(&parent_index_entry, &parent);
     let new_reference_array = mutate_insert_in_subobject_array(reference_array, id, before_id)?;
+    index.record_update(&parent_index_entry);
     parent_index_entry.borrow_mut().data.insert(
         parent.property,
         ObjectValue::VecSubObjects(new_reference_array),
@@ -26,6 +27,25 @@ pub fn insert_subobject_in_array(
     Ok(())
 }
 
+// a single subobject field holds at most one object
+pub fn set_subobject_in_slot(
+    index: &mut Index,
+    parent: IndexEntryProperty,
+    id: &str,
+) -> Result<(), HitError> {
+    let (parent_index_entry, parent) = get_parent_index_entry_from_parent(index, parent)?;
+    match get_parent_property_value(&parent_index_entry, &parent) {
+        ObjectValue::Null => {}
+        _ => return Err(HitError::OccupiedSubobjectField(parent.id, parent.property)),
+    }
+    index.record_update(&parent_index_entry);
+    parent_index_entry.borrow_mut().data.insert(
+        parent.property,
+        ObjectValue::SubObject(Reference { id: id.into() }),
+    );
+    Ok(())
+}
+
 fn mutate_remove_from_subobject_array(
     data: ObjectValue,
     id: &str,
@@ -39,11 +59,12 @@ fn mutate_remove_from_subobject_array(
             }
             Ok(Some(data))
         }
+        ObjectValue::SubObject(reference) if reference.id == id => Ok(None),
         _ => Err(HitError::CannotRemoveObjectFromThisDataType()),
     }
 }
 
-fn remove_from_subobject_array(index: &Index, id: &str) -> Result<(), HitError> {
+fn remove_from_subobject_array(index: &mut Index, id: &str) -> Result<(), HitError> {
     let (array_of_refs, parent) = {
         let entry = index.get(id).ok_or(HitError::IDNotFound(id.to_string(), "remove_from_subobject_array entry".into()))?;
         let entry = entry.borrow();
@@ -59,7 +80,7 @@ fn remove_from_subobject_array(index: &Index, id: &str) -> Result<(), HitError>
     // let refs_as_vec = get_object_value_as_vec_reference(array_of_refs.clone())?;
     let new_value = mutate_remove_from_subobject_array(array_of_refs, id)?;
     let parent_index_entry = index
-        .get(&parent.id)
+        .get_for_update(&parent.id)
         .ok_or(HitError::IDNotFound(id.to_string(), "remove_from_subobject_array parent_index".into()))?;
     match new_value {
         Some(new_data) => {
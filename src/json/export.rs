@@ -1,9 +1,12 @@
 use crate::hit_mod::{Hit, HitEntry};
 use crate::index::IndexEntryProperty;
 use crate::json::utils::*;
-use crate::object_data::{ObjectValue, Reference};
+use crate::model::field_types::FieldTypeBacklinks;
+use crate::model::ModelFieldRef;
+use crate::object_data::{ObjectValue, ObjectValues, Reference};
 use serde_json::{json, Map, Value};
 use std::collections::HashMap;
+use std::io::{BufWriter, Write};
 
 fn reference_to_data(reference: &Reference) -> JSONObject {
     let mut ref_object = Map::new();
@@ -44,7 +47,7 @@ fn vecstring_to_json_object(strings: &Vec<String>) -> JSONObject {
     return new_value;
 }
 
-fn object_value_to_json(value: &ObjectValue) -> Result<Value, String> {
+pub(crate) fn object_value_to_json(value: &ObjectValue) -> Result<Value, String> {
     match value {
         ObjectValue::Bool(value) => Ok(Value::Bool(*value)),
         ObjectValue::Date(value) => {
@@ -55,7 +58,7 @@ fn object_value_to_json(value: &ObjectValue) -> Result<Value, String> {
             );
             new_value.insert(
                 String::from("value"),
-                Value::String(value.get_date_as_string()),
+                Value::String(value.get_date_as_rfc3339()),
             );
             return Ok(Value::Object(new_value));
         }
@@ -64,11 +67,7 @@ fn object_value_to_json(value: &ObjectValue) -> Result<Value, String> {
                 .ok_or(String::from("Invalid number"))?;
             return Ok(Value::Number(number));
         }
-        ObjectValue::I32(value) => {
-            let number = serde_json::Number::from_f64(*value as f64)
-                .ok_or(String::from("Invalid number"))?;
-            return Ok(Value::Number(number));
-        }
+        ObjectValue::I32(value) => Ok(Value::Number((*value).into())),
         ObjectValue::Null => Ok(Value::Null),
         ObjectValue::String(value) => Ok(Value::String(String::from(value))),
         ObjectValue::Reference(value) => {
@@ -100,7 +99,7 @@ fn object_value_to_json(value: &ObjectValue) -> Result<Value, String> {
         }
     }
 }
-fn export_parent(parent: Option<IndexEntryProperty>) -> Value {
+pub(crate) fn export_parent(parent: Option<IndexEntryProperty>) -> Value {
     match parent {
         None => Value::Null,
         Some(parent) => json!({
@@ -110,10 +109,30 @@ fn export_parent(parent: Option<IndexEntryProperty>) -> Value {
     }
 }
 
-fn export_object(object: HitEntry) -> Result<Value, String> {
+// the backlinks are only written when their field is `exported`
+fn get_exported_backlinks(
+    hit: &Hit,
+    id: &str,
+    property: &str,
+    field: &ModelFieldRef,
+) -> Option<ObjectValue> {
+    let field = field.borrow();
+    let field = field.downcast_ref::<FieldTypeBacklinks>()?;
+    if !field.exported {
+        return None;
+    }
+    hit.get_value(id, property)
+}
+
+fn export_object(hit: &Hit, object: HitEntry) -> Result<Value, String> {
     let model = &object.get_model();
+    let id = object.get_id();
     let mut data = HashMap::new();
-    for (key, _field) in model.fields.iter() {
+    for (key, field) in model.fields.iter() {
+        if let Some(backlinks) = get_exported_backlinks(hit, &id, key, field) {
+            data.insert(key, object_value_to_json(&backlinks)?);
+            continue;
+        }
         let entry = object.entry.borrow();
         let entry = entry.get(key);
         match entry {
@@ -136,18 +155,62 @@ pub fn export(index: &Hit) -> Result<Value, String> {
     let mut data = vec![];
     for (id, entry) in index.index.iter() {
         let model = { index.get_model(id).ok_or("Model not found")? };
-        let exported_object = export_object(HitEntry {
-            entry: entry.clone(),
-            model: model.clone(),
-        })?;
+        let exported_object = export_object(
+            index,
+            HitEntry {
+                entry: entry.clone(),
+                model: model.clone(),
+                // export_object reads the exported backlinks itself
+                backlinks: ObjectValues::new(),
+            },
+        )?;
         data.push(exported_object);
     }
     return Ok(json!({
         "id": index.get_main_object_id(),
+        "version": index.kernel.get_schema_version(),
         "data": data
     }));
 }
 
+// Writes the same document as `export`, one object at a time, without building the whole
+// JSON in memory. `progress` receives the number of objects written and the total.
+pub fn export_to_writer<W: Write>(
+    index: &Hit,
+    writer: W,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<(), String> {
+    let mut writer = BufWriter::new(writer);
+    let total = index.index.iter().count();
+    let id = serde_json::to_string(index.get_main_object_id()).map_err(|e| e.to_string())?;
+    write!(
+        writer,
+        "{{\"id\":{},\"version\":{},\"data\":[",
+        id,
+        index.kernel.get_schema_version()
+    )
+    .map_err(|e| e.to_string())?;
+    for (position, (id, entry)) in index.index.iter().enumerate() {
+        if position > 0 {
+            writer.write_all(b",").map_err(|e| e.to_string())?;
+        }
+        let model = { index.get_model(id).ok_or("Model not found")? };
+        let exported_object = export_object(
+            index,
+            HitEntry {
+                entry: entry.clone(),
+                model: model.clone(),
+                // export_object reads the exported backlinks itself
+                backlinks: ObjectValues::new(),
+            },
+        )?;
+        serde_json::to_writer(&mut writer, &exported_object).map_err(|e| e.to_string())?;
+        progress(position + 1, total);
+    }
+    writer.write_all(b"]}").map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::json::export::export;
@@ -210,7 +273,8 @@ mod tests {
                  }
               }
            ],
-           "id": "id1"
+           "id": "id1",
+           "version": 0
         });
         let (dump, json_data) = import(json_data);
         let (dump2, json_data) = import(json_data);
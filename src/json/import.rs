@@ -2,22 +2,60 @@ use crate::hit_mod::{Hit, HitKernel, IndexModelImporter};
 use crate::index::IndexEntryProperty;
 use crate::json::utils::*;
 use crate::json::JSONImportError;
+use crate::model::{FieldKind, ModelFieldRef};
 use crate::object_data::{DateTimeUtc, ObjectValue, ObjectValues};
+use crate::operations::OperationRecord;
+use crate::HitError;
 use chrono::{DateTime, Utc};
 use linked_hash_map::LinkedHashMap;
-use serde_json::Value;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json::{json, Value};
 
+use std::fmt;
+use std::io::{BufReader, Read};
 use std::rc::Rc;
 
 use crate::import::generic_import::{finish_import, import_data_object_values};
 
-fn json_to_object_value(value: &Value) -> Result<ObjectValue, JSONImportError> {
+fn json_to_integer(value: &serde_json::Number) -> Result<ObjectValue, JSONImportError> {
+    // the exports made before the integers were exported as such contain floats
+    let value = match value.as_i64() {
+        Some(value) => Some(value),
+        None => value
+            .as_f64()
+            .filter(|value| value.fract() == 0.0)
+            .map(|value| value as i64),
+    };
+    match value {
+        Some(value) if value >= i32::MIN as i64 && value <= i32::MAX as i64 => {
+            Ok(ObjectValue::I32(value as i32))
+        }
+        _ => Err(JSONImportError::InvalidTypeShouldBeAnInteger()),
+    }
+}
+
+fn json_to_date(value: &str) -> Result<ObjectValue, JSONImportError> {
+    // the exports made before the sub-second precision use RFC 2822
+    let date = DateTime::parse_from_rfc3339(value).or_else(|_| DateTime::parse_from_rfc2822(value));
+    match date {
+        Ok(date) => Ok(ObjectValue::Date(DateTimeUtc::new(date.with_timezone(&Utc)))),
+        Err(_error) => Err(JSONImportError::InvalidDateFormat()),
+    }
+}
+
+// the field is used to know the type of the numbers
+fn json_to_object_value(value: &Value, field: &ModelFieldRef) -> Result<ObjectValue, JSONImportError> {
     match value {
         Value::Null => Ok(ObjectValue::Null),
-        Value::Number(value) => match value.as_f64() {
-            Some(value) => Ok(ObjectValue::F32(value as f32)),
-            None => Err(JSONImportError::InvalidTypeShouldBeANumber()),
-        },
+        Value::Number(value) => {
+            if field.borrow().get_kind() == FieldKind::Integer {
+                return json_to_integer(value);
+            }
+            match value.as_f64() {
+                Some(value) => Ok(ObjectValue::F32(value as f32)),
+                None => Err(JSONImportError::InvalidTypeShouldBeANumber()),
+            }
+        }
         Value::String(value) => Ok(ObjectValue::String(String::from(value))),
         Value::Bool(value) => Ok(ObjectValue::Bool(*value)),
         Value::Object(value) => {
@@ -35,15 +73,12 @@ fn json_to_object_value(value: &Value) -> Result<ObjectValue, JSONImportError> {
             } else if _type == String::from(JSON_FIELD_TYPE_SUBOBJECT_ARRAY) {
                 let array_of_ids = get_array_of_ids(sub_value)?;
                 return Ok(ObjectValue::VecSubObjects(array_of_ids));
+            } else if _type == JSON_FIELD_TYPE_STRING_ARRAY {
+                let strings = get_array_of_strings(sub_value)?;
+                Ok(ObjectValue::VecString(strings))
             } else if _type == String::from(JSON_FIELD_TYPE_DATE) {
                 let sub_value = get_value_as_string(sub_value)?;
-                let sub_value = DateTime::parse_from_rfc2822(&sub_value);
-                match sub_value {
-                    Ok(sub_value) => Ok(ObjectValue::Date(DateTimeUtc::new(
-                        sub_value.with_timezone(&Utc),
-                    ))),
-                    Err(_error) => Err(JSONImportError::InvalidDateFormat()),
-                }
+                json_to_date(&sub_value)
             } else {
                 return Err(JSONImportError::InvalidSubObjectType());
             }
@@ -82,7 +117,14 @@ fn import_data<'index>(
     let sub_data = get_value_as_object(sub_data)?;
     let mut new_data: ObjectValues = LinkedHashMap::new();
     for d in sub_data.iter() {
-        new_data.insert(String::from(d.0), json_to_object_value(d.1)?);
+        let field = model.get_field(d.0).ok_or(JSONImportError::HitError(
+            HitError::PropertyNotFound(d.0.to_string()),
+        ))?;
+        // the exported backlinks are read from the imported references
+        if field.borrow().get_kind() == FieldKind::Backlinks {
+            continue;
+        }
+        new_data.insert(String::from(d.0), json_to_object_value(d.1, field)?);
     }
 
     import_data_object_values(model, id, parent, new_index, new_data)
@@ -106,7 +148,19 @@ pub fn import_from_string<'a>(
 }
 
 pub fn import<'a>(value: &Value, kernel: Rc<HitKernel>) -> Result<Hit, JSONImportError> {
-    let value = get_value_as_object(&value)?;
+    let document = get_value_as_object(&value)?;
+    let version = get_document_version(document)?;
+    check_document_version(version, kernel.as_ref())?;
+    if let Some(migrations) = kernel.get_migrations() {
+        if version < kernel.get_schema_version() {
+            let mut value = value.clone();
+            migrations
+                .migrate_to_kernel(&mut value, kernel.as_ref())
+                .map_err(|error| JSONImportError::MigrationError(Box::new(error)))?;
+            return import(&value, kernel);
+        }
+    }
+    let value = document;
     let id = get_object_property_as_string(value, String::from("id"))?;
     let mut new_index = IndexModelImporter::new(&id, kernel.clone());
     let data = get_object_property(value, String::from("data"))?;
@@ -119,3 +173,169 @@ pub fn import<'a>(value: &Value, kernel: Rc<HitKernel>) -> Result<Hit, JSONImpor
     let new_index = finish_import(new_index, kernel).map_err(JSONImportError::HitError)?;
     Ok(new_index)
 }
+
+// Imports an export made when the sequence number of the document was `sequence`,
+// then applies the records of the log that came after it
+pub fn import_with_operation_log(
+    value: &Value,
+    sequence: u64,
+    log: &[OperationRecord],
+    kernel: Rc<HitKernel>,
+) -> Result<Hit, JSONImportError> {
+    let mut hit = import(value, kernel)?;
+    hit.set_operation_sequence(sequence);
+    hit.apply_log(log).map_err(JSONImportError::HitError)?;
+    Ok(hit)
+}
+
+// State of a streaming import. The error of an object is kept, so that it is returned
+// instead of the error of the deserializer.
+struct StreamImport<'a> {
+    kernel: Rc<HitKernel>,
+    new_index: IndexModelImporter,
+    error: Option<JSONImportError>,
+    progress: &'a mut dyn FnMut(usize),
+    count: usize,
+    // the objects of an older document, imported once the whole document is migrated
+    older_entries: Option<Vec<Value>>,
+}
+
+impl<'a> StreamImport<'a> {
+    fn import_entry(&mut self, entry: &Value) -> Result<(), JSONImportError> {
+        let entry = get_value_as_object(entry)?;
+        import_data(entry, self.kernel.clone(), &mut self.new_index)?;
+        self.count += 1;
+        (self.progress)(self.count);
+        Ok(())
+    }
+}
+
+struct DocumentVisitor<'s, 'a> {
+    state: &'s mut StreamImport<'a>,
+}
+
+impl<'de, 's, 'a> Visitor<'de> for DocumentVisitor<'s, 'a> {
+    // the id of the main object, whether the objects were found, and the version
+    type Value = (Option<String>, bool, u32);
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a hit document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut id = None;
+        let mut has_data = false;
+        // the version is checked before importing the objects, and again at the end
+        // when it comes after them
+        let mut version = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "id" => id = Some(map.next_value::<String>()?),
+                "version" => version = Some(map.next_value::<u32>()?),
+                "data" => {
+                    if let Err(error) =
+                        check_document_version(version.unwrap_or(0), self.state.kernel.as_ref())
+                    {
+                        self.state.error = Some(error);
+                        return Err(de::Error::custom("invalid version"));
+                    }
+                    if version.unwrap_or(0) < self.state.kernel.get_schema_version() {
+                        self.state.older_entries = Some(map.next_value::<Vec<Value>>()?);
+                    } else {
+                        map.next_value_seed(EntriesSeed {
+                            state: &mut *self.state,
+                        })?;
+                    }
+                    has_data = true;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if let Err(error) = check_document_version(version.unwrap_or(0), self.state.kernel.as_ref())
+        {
+            self.state.error = Some(error);
+            return Err(de::Error::custom("invalid version"));
+        }
+        Ok((id, has_data, version.unwrap_or(0)))
+    }
+}
+
+struct EntriesSeed<'s, 'a> {
+    state: &'s mut StreamImport<'a>,
+}
+
+impl<'de, 's, 'a> DeserializeSeed<'de> for EntriesSeed<'s, 'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, 's, 'a> Visitor<'de> for EntriesSeed<'s, 'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of objects")
+    }
+
+    // only one object is in memory at a time
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(entry) = seq.next_element::<Value>()? {
+            if let Err(error) = self.state.import_entry(&entry) {
+                self.state.error = Some(error);
+                return Err(de::Error::custom("invalid object"));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Imports a document without loading the whole JSON in memory. The objects are imported
+// one by one, `progress` receives the number of objects imported so far. The documents
+// of an older version are loaded in memory to be migrated, `progress` is called once.
+pub fn import_from_reader<R: Read>(
+    reader: R,
+    kernel: Rc<HitKernel>,
+    progress: &mut dyn FnMut(usize),
+) -> Result<Hit, JSONImportError> {
+    // the id can come after the objects
+    let mut state = StreamImport {
+        kernel: kernel.clone(),
+        new_index: IndexModelImporter::new("", kernel.clone()),
+        error: None,
+        progress,
+        count: 0,
+        older_entries: None,
+    };
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let result = deserializer.deserialize_map(DocumentVisitor { state: &mut state });
+    let (id, has_data, version) = match result {
+        Ok(result) => result,
+        Err(_error) => return Err(state.error.take().unwrap_or(JSONImportError::InvalidJSON())),
+    };
+    deserializer
+        .end()
+        .map_err(|_error| JSONImportError::InvalidJSON())?;
+    let id = id.ok_or(JSONImportError::HitError(HitError::PropertyNotFound(
+        "id".to_string(),
+    )))?;
+    if !has_data {
+        return Err(JSONImportError::HitError(HitError::PropertyNotFound(
+            "data".to_string(),
+        )));
+    }
+    if let Some(entries) = state.older_entries.take() {
+        let count = entries.len();
+        let document = json!({ "id": id, "version": version, "data": entries });
+        let hit = import(&document, kernel)?;
+        (state.progress)(count);
+        return Ok(hit);
+    }
+
+    let mut new_index = state.new_index;
+    new_index.set_main_object_id(&id);
+    finish_import(new_index, kernel).map_err(JSONImportError::HitError)
+}
@@ -2,6 +2,9 @@ pub mod export;
 pub mod import;
 mod utils;
 
+pub(crate) use utils::{check_document_version, get_document_version};
+
+use crate::migrations::MigrationError;
 use crate::HitError;
 
 #[derive(Debug)]
@@ -12,18 +15,26 @@ pub enum JSONImportError {
     InvalidTypeShouldBeAnArray(),
     InvalidTypeShouldBeAString(),
     InvalidTypeShouldBeANumber(),
+    InvalidTypeShouldBeAnInteger(),
     InvalidSubObjectType(),
     ShouldNotBeAnArray(),
     InvalidJSON(),
     InvalidDateFormat(),
+    // the version of the document, and the version of the kernel
+    InvalidSchemaVersion(u32, u32),
+    // a migration registered in the kernel failed
+    MigrationError(Box<MigrationError>),
 }
 #[cfg(test)]
 mod tests {
-    use crate::json::import::import;
+    use crate::json::export::{export, export_to_writer};
+    use crate::json::import::{import, import_from_reader};
     use crate::json::JSONImportError;
     use crate::object_data::ObjectValue;
-    use crate::test_kernel::create_test_kernel;
-    use crate::HitError;
+    use crate::test_kernel::{
+        create_hit_with_all_types, create_test_kernel, create_test_kernel_with_all_types,
+    };
+    use crate::{Hit, HitError, IndexEntryProperty, LinkedHashMap};
     use serde_json::json;
     use std::rc::Rc;
 
@@ -142,4 +153,189 @@ mod tests {
             _ => panic!("Wrong data type for subitems"),
         }
     }
+
+    fn create_large_hit() -> Hit {
+        let mut hit = Hit::new("id", "test/test", Rc::new(create_test_kernel())).unwrap();
+        for index in 0..200 {
+            let mut values = LinkedHashMap::new();
+            values.insert("name".to_string(), ObjectValue::String(format!("name {}", index)));
+            values.insert("age".to_string(), ObjectValue::F32(index as f32));
+            hit.insert(
+                "test/test",
+                &format!("id{}", index),
+                values,
+                IndexEntryProperty {
+                    id: "id".to_string(),
+                    property: "sub_items".to_string(),
+                },
+                None,
+            )
+            .unwrap();
+        }
+        hit
+    }
+
+    #[test]
+    pub fn test_streaming_export() {
+        let hit = create_large_hit();
+        let mut output = vec![];
+        let mut progress = vec![];
+        export_to_writer(&hit, &mut output, &mut |count, total| progress.push((count, total)))
+            .expect("Export failed");
+
+        let value: serde_json::Value = serde_json::from_slice(&output).expect("Invalid JSON");
+        assert_eq!(value, export(&hit).unwrap());
+        assert_eq!(progress.len(), 201);
+        assert_eq!(progress.last(), Some(&(201, 201)));
+    }
+
+    #[test]
+    pub fn test_streaming_import() {
+        let hit = create_large_hit();
+        let mut output = vec![];
+        export_to_writer(&hit, &mut output, &mut |_, _| {}).unwrap();
+        let mut counts = vec![];
+
+        let imported = import_from_reader(
+            &output[..],
+            Rc::new(create_test_kernel()),
+            &mut |count| counts.push(count),
+        )
+        .expect("Import failed");
+
+        assert_eq!(export(&imported).unwrap(), export(&hit).unwrap());
+        assert_eq!(counts, (1..=201).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    pub fn test_streaming_import_with_the_id_after_the_data() {
+        let hit = create_large_hit();
+        // the keys of a serde_json object are sorted
+        let output = export(&hit).unwrap().to_string();
+        assert!(output.starts_with("{\"data\""));
+
+        let imported =
+            import_from_reader(output.as_bytes(), Rc::new(create_test_kernel()), &mut |_| {})
+                .expect("Import failed");
+        assert_eq!(imported.get_main_object_id(), "id");
+        assert_eq!(export(&imported).unwrap(), export(&hit).unwrap());
+    }
+
+    #[test]
+    pub fn test_streaming_import_errors() {
+        let kernel = Rc::new(create_test_kernel());
+        let result = import_from_reader(&b"{\"id\": \"id\", \"data\": ["[..], kernel.clone(), &mut |_| {});
+        assert!(matches!(result, Err(JSONImportError::InvalidJSON())));
+
+        let json_data = json!({
+            "data": [{"model": "test/unknown", "id": "id", "data": {}, "parent": null}],
+            "id": "id"
+        })
+        .to_string();
+        let result = import_from_reader(json_data.as_bytes(), kernel.clone(), &mut |_| {});
+        assert!(matches!(
+            result,
+            Err(JSONImportError::HitError(HitError::ModelDoesNotExist(_)))
+        ));
+
+        let result = import_from_reader(&b"{\"data\": []}"[..], kernel.clone(), &mut |_| {});
+        assert!(matches!(
+            result,
+            Err(JSONImportError::HitError(HitError::PropertyNotFound(_)))
+        ));
+
+        // the documents of another version of the models must be migrated first
+        let json_data = json!({"id": "id", "version": 1, "data": []}).to_string();
+        let result = import_from_reader(json_data.as_bytes(), kernel.clone(), &mut |_| {});
+        assert!(matches!(
+            result,
+            Err(JSONImportError::InvalidSchemaVersion(1, 0))
+        ));
+    }
+
+    fn assert_same_values(imported: &Hit, hit: &Hit) {
+        for (id, model) in [("id", "test/values"), ("id2", "test/test"), ("id3", "test/test")] {
+            let model = hit.kernel.get_model(model).unwrap();
+            for property in model.fields.keys() {
+                assert_eq!(
+                    imported.get_value(id, property),
+                    hit.get_value(id, property),
+                    "{}.{}",
+                    id,
+                    property
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_json_round_trip_of_every_value() {
+        let hit = create_hit_with_all_types();
+        let json_data = export(&hit).unwrap();
+        assert_eq!(json_data["data"][0]["data"]["integer"], json!(i32::MIN));
+
+        let kernel = Rc::new(create_test_kernel_with_all_types());
+        let imported = import(&json_data, kernel.clone()).expect("Import failed");
+        assert_same_values(&imported, &hit);
+        assert_eq!(export(&imported).unwrap(), json_data);
+
+        let mut output = vec![];
+        export_to_writer(&hit, &mut output, &mut |_, _| {}).unwrap();
+        let imported = import_from_reader(&output[..], kernel, &mut |_| {}).expect("Import failed");
+        assert_same_values(&imported, &hit);
+    }
+
+    fn import_values(values: serde_json::Value) -> Result<Hit, JSONImportError> {
+        let json_data = json!({
+            "data": [{"model": "test/values", "id": "id", "data": values, "parent": null}],
+            "id": "id"
+        });
+        import(&json_data, Rc::new(create_test_kernel_with_all_types()))
+    }
+
+    #[test]
+    pub fn test_json_import_of_integers() {
+        // the integers were exported as floats before
+        let hit = import_values(json!({"integer": 12.0, "float": 12})).expect("Import failed");
+        assert_eq!(hit.get_value("id", "integer"), Some(ObjectValue::I32(12)));
+        assert_eq!(hit.get_value("id", "float"), Some(ObjectValue::F32(12.0)));
+
+        for value in [json!(1.5), json!(i64::from(i32::MAX) + 1)].iter() {
+            let result = import_values(json!({ "integer": value }));
+            assert!(matches!(
+                result,
+                Err(JSONImportError::InvalidTypeShouldBeAnInteger())
+            ));
+        }
+        let result = import_values(json!({"unknown": 12}));
+        assert!(matches!(
+            result,
+            Err(JSONImportError::HitError(HitError::PropertyNotFound(_)))
+        ));
+    }
+
+    #[test]
+    pub fn test_json_import_of_dates() {
+        let hit = import_values(json!({
+            "date": {"type": "date", "value": "2021-03-04T05:06:07.123456789Z"}
+        }))
+        .expect("Import failed");
+        match hit.get_value("id", "date") {
+            Some(ObjectValue::Date(date)) => assert_eq!(date.get_nanoseconds(), 123_456_789),
+            _ => panic!("Date not found"),
+        }
+
+        // the dates were exported with a precision of a second before
+        let hit = import_values(json!({
+            "date": {"type": "date", "value": "Thu, 04 Mar 2021 05:06:07 +0000"}
+        }))
+        .expect("Import failed");
+        match hit.get_value("id", "date") {
+            Some(ObjectValue::Date(date)) => {
+                assert_eq!(date.get_timestamp(), 1614834367);
+                assert_eq!(date.get_nanoseconds(), 0);
+            }
+            _ => panic!("Date not found"),
+        }
+    }
 }
@@ -45,6 +45,35 @@ pub fn get_value_as_u32(value: &Value) -> Result<u32, JSONImportError> {
     }
 } */
 
+// the documents exported before the versions were added have the version 0
+pub fn get_document_version(document: &JSONObject) -> Result<u32, JSONImportError> {
+    match document.get("version") {
+        None => Ok(0),
+        Some(Value::Number(version)) => version
+            .as_u64()
+            .filter(|version| *version <= u32::MAX as u64)
+            .map(|version| version as u32)
+            .ok_or(JSONImportError::InvalidTypeShouldBeAnInteger()),
+        Some(_) => Err(JSONImportError::InvalidTypeShouldBeANumber()),
+    }
+}
+
+// the older documents are accepted when the kernel has the migrations to upgrade them
+pub fn check_document_version(version: u32, kernel: &HitKernel) -> Result<(), JSONImportError> {
+    let can_migrate = kernel
+        .get_migrations()
+        .is_some_and(|migrations| migrations.can_migrate(version, kernel.get_schema_version()));
+    if version > kernel.get_schema_version()
+        || (version < kernel.get_schema_version() && !can_migrate)
+    {
+        return Err(JSONImportError::InvalidSchemaVersion(
+            version,
+            kernel.get_schema_version(),
+        ));
+    }
+    Ok(())
+}
+
 pub fn get_object_property(
     object: &JSONObject,
     property: String,
@@ -88,3 +117,12 @@ pub fn get_array_of_ids(sub_value: &Value) -> Result<Vec<Reference>, JSONImportE
     }
     return Ok(array_of_ids);
 }
+
+pub fn get_array_of_strings(sub_value: &Value) -> Result<Vec<String>, JSONImportError> {
+    let sub_value = get_value_as_array(sub_value)?;
+    let mut strings = vec![];
+    for sub_sub_value in sub_value.iter() {
+        strings.push(get_value_as_string(sub_sub_value)?);
+    }
+    Ok(strings)
+}
@@ -0,0 +1,627 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use linked_hash_map::LinkedHashMap;
+
+use crate::kernel::DefaultKernel;
+use crate::migrations::Migrations;
+use crate::plugins::{
+    AfterImportPlugin, DeletePlugin, InitEntryPlugin, Plugin, ReferencePlugin, TransactionPlugin,
+};
+use crate::secondary_index::{SecondaryIndexDefinition, SecondaryIndexKind};
+use crate::utils::kernel_init;
+use crate::{HitError, Interface, Model, Plugins};
+
+// Creates a kernel without implementing the Kernel trait
+pub struct KernelBuilder {
+    models: LinkedHashMap<String, Rc<Model>>,
+    duplicated_models: Vec<String>,
+    interfaces: HashSet<String>,
+    interface_definitions: LinkedHashMap<String, Rc<Interface>>,
+    instantiable_models: Vec<String>,
+    plugins: Plugins,
+    schema_version: u32,
+    defaults_on_import: bool,
+    secondary_indexes: Vec<SecondaryIndexDefinition>,
+    migrations: Option<Migrations>,
+}
+
+impl Default for KernelBuilder {
+    fn default() -> Self {
+        KernelBuilder::new()
+    }
+}
+
+impl KernelBuilder {
+    pub fn new() -> Self {
+        KernelBuilder {
+            models: LinkedHashMap::new(),
+            duplicated_models: vec![],
+            interfaces: HashSet::new(),
+            interface_definitions: LinkedHashMap::new(),
+            instantiable_models: vec![],
+            plugins: Plugins::new(),
+            schema_version: 0,
+            defaults_on_import: false,
+            secondary_indexes: vec![],
+            migrations: None,
+        }
+    }
+
+    pub fn add_model(mut self, model: Rc<Model>) -> Self {
+        let name = model.get_name().clone();
+        if self.models.insert(name.clone(), model).is_some() {
+            self.duplicated_models.push(name);
+        }
+        self
+    }
+
+    // the interfaces must be declared to be implemented by the models
+    pub fn add_interface(mut self, name: &str) -> Self {
+        self.interfaces.insert(name.to_string());
+        self
+    }
+
+    // the models implementing the interface must provide its fields
+    pub fn add_interface_definition(mut self, interface: Interface) -> Self {
+        let name = interface.get_name().clone();
+        self.interfaces.insert(name.clone());
+        self.interface_definitions.insert(name, Rc::new(interface));
+        self
+    }
+
+    pub fn mark_instantiable(mut self, model_name: &str) -> Self {
+        if !self.instantiable_models.iter().any(|name| name == model_name) {
+            self.instantiable_models.push(model_name.to_string());
+        }
+        self
+    }
+
+    pub fn set_schema_version(mut self, version: u32) -> Self {
+        self.schema_version = version;
+        self
+    }
+
+    // the documents of the older versions are migrated by the imports
+    pub fn set_migrations(mut self, migrations: Migrations) -> Self {
+        self.migrations = Some(migrations);
+        self
+    }
+
+    // the defaults are always set by `Hit::new`, `insert` and `copy_object`
+    pub fn set_defaults_on_import(mut self, defaults_on_import: bool) -> Self {
+        self.defaults_on_import = defaults_on_import;
+        self
+    }
+
+    // the index is read with `Hit::index_lookup` and `Hit::index_range`
+    pub fn add_secondary_index(
+        mut self,
+        name: &str,
+        model: &str,
+        field: &str,
+        kind: SecondaryIndexKind,
+    ) -> Self {
+        self.secondary_indexes.push(SecondaryIndexDefinition {
+            name: name.to_string(),
+            model: model.to_string(),
+            field: field.to_string(),
+            kind,
+        });
+        self
+    }
+
+    pub fn add_init_plugin(mut self, plugin: Rc<RefCell<dyn InitEntryPlugin>>) -> Self {
+        self.plugins.init_plugins.push(plugin);
+        self
+    }
+
+    pub fn add_after_import_plugin(mut self, plugin: Rc<RefCell<dyn AfterImportPlugin>>) -> Self {
+        self.plugins.after_import_plugins.push(plugin);
+        self
+    }
+
+    pub fn add_plugin(mut self, plugin: Rc<RefCell<dyn Plugin>>) -> Self {
+        self.plugins.plugins.push(plugin);
+        self
+    }
+
+    pub fn add_delete_plugin(mut self, plugin: Rc<RefCell<dyn DeletePlugin>>) -> Self {
+        self.plugins.delete_plugins.push(plugin);
+        self
+    }
+
+    pub fn add_reference_plugin(mut self, plugin: Rc<RefCell<dyn ReferencePlugin>>) -> Self {
+        self.plugins.reference_plugins.push(plugin);
+        self
+    }
+
+    pub fn add_transaction_plugin(mut self, plugin: Rc<RefCell<dyn TransactionPlugin>>) -> Self {
+        self.plugins.transaction_plugins.push(plugin);
+        self
+    }
+
+    // checks the names used by the models before calling `on_kernel_init` on their fields
+    fn check_names(&self) -> Result<(), HitError> {
+        if let Some(name) = self.duplicated_models.first() {
+            return Err(HitError::DuplicateModel(name.clone()));
+        }
+        for name in self.instantiable_models.iter() {
+            match self.models.get(name) {
+                Some(model) if model.is_abstract => {
+                    return Err(HitError::AbstractModel(name.clone()))
+                }
+                Some(_) => {}
+                None => return Err(HitError::ModelDoesNotExist(name.clone())),
+            }
+        }
+        for (name, model) in self.models.iter() {
+            if let Some(base) = &model.extends {
+                if !self.models.contains_key(base) {
+                    return Err(HitError::UnknownBaseModel(name.clone(), base.clone()));
+                }
+            }
+            for interface in model.interfaces.iter() {
+                if !self.interfaces.contains(interface) {
+                    return Err(HitError::UnknownInterface(name.clone(), interface.clone()));
+                }
+            }
+            for (field_name, field) in model.fields.iter() {
+                let authorized_models = field.borrow().describe().authorized_models;
+                for authorized_model in authorized_models.iter().flatten() {
+                    if !self.models.contains_key(authorized_model)
+                        && !self.interfaces.contains(authorized_model)
+                    {
+                        return Err(HitError::UnknownAuthorizedModel(
+                            name.clone(),
+                            field_name.clone(),
+                            authorized_model.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // creates the models extending another one, with the fields of their base model first.
+    // A field declared again must have the kind of the inherited one.
+    fn resolve_model(
+        &self,
+        name: &str,
+        resolved: &mut HashMap<String, Rc<Model>>,
+        visiting: &mut Vec<String>,
+    ) -> Result<Rc<Model>, HitError> {
+        if let Some(model) = resolved.get(name) {
+            return Ok(model.clone());
+        }
+        let model = self
+            .models
+            .get(name)
+            .ok_or_else(|| HitError::ModelDoesNotExist(name.to_string()))?;
+        let base_name = match &model.extends {
+            Some(base_name) => base_name,
+            None => {
+                resolved.insert(name.to_string(), model.clone());
+                return Ok(model.clone());
+            }
+        };
+        if visiting.iter().any(|visited| visited == name) {
+            return Err(HitError::CyclicInheritance(name.to_string()));
+        }
+        visiting.push(name.to_string());
+        let base = self.resolve_model(base_name, resolved, visiting)?;
+
+        let mut new_model = Model::new(name.to_string(), model.get_label().clone());
+        new_model.extends = Some(base_name.clone());
+        new_model.is_abstract = model.is_abstract;
+        new_model.ancestors = base.ancestors.clone();
+        new_model.ancestors.push(base_name.clone());
+        new_model.interfaces = base.interfaces.clone();
+        for interface in model.interfaces.iter() {
+            if !new_model.interfaces.contains(interface) {
+                new_model.interfaces.push(interface.clone());
+            }
+        }
+        for (field_name, field) in base.fields.iter() {
+            let field = match model.fields.get(field_name) {
+                Some(own_field) => {
+                    if own_field.borrow().get_kind() != field.borrow().get_kind() {
+                        return Err(HitError::InvalidFieldOverride(
+                            name.to_string(),
+                            field_name.clone(),
+                        ));
+                    }
+                    own_field.clone()
+                }
+                None => field.clone(),
+            };
+            new_model.fields.insert(field_name.clone(), field);
+        }
+        for (field_name, field) in model.fields.iter() {
+            if !new_model.fields.contains_key(field_name) {
+                new_model.fields.insert(field_name.clone(), field.clone());
+            }
+        }
+        // a field declared again keeps the default and the slot policy of the base model
+        // unless it has its own
+        for field_name in new_model.fields.keys().cloned().collect::<Vec<String>>() {
+            if let Some(default) = model
+                .get_default(&field_name)
+                .or_else(|| base.get_default(&field_name))
+            {
+                new_model.set_default(&field_name, default);
+            }
+            if let Some(policy) = model
+                .get_slot_policy(&field_name)
+                .or_else(|| base.get_slot_policy(&field_name))
+            {
+                new_model.set_slot_policy(&field_name, policy);
+            }
+        }
+
+        let new_model = Rc::new(new_model);
+        resolved.insert(name.to_string(), new_model.clone());
+        Ok(new_model)
+    }
+
+    fn check_interface_fields(&self, model: &Model) -> Result<(), HitError> {
+        for interface_name in model.interfaces.iter() {
+            let interface = match self.interface_definitions.get(interface_name) {
+                Some(interface) => interface,
+                None => continue,
+            };
+            for (field_name, field) in interface.fields.iter() {
+                let is_provided = match model.get_field(field_name) {
+                    Some(model_field) => {
+                        model_field.borrow().get_kind() == field.borrow().get_kind()
+                    }
+                    None => false,
+                };
+                if !is_provided {
+                    return Err(HitError::MissingInterfaceField(
+                        model.get_name().clone(),
+                        interface_name.clone(),
+                        field_name.clone(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_secondary_indexes(&self, models: &HashMap<String, Rc<Model>>) -> Result<(), HitError> {
+        for (position, definition) in self.secondary_indexes.iter().enumerate() {
+            let is_duplicate = self.secondary_indexes[..position]
+                .iter()
+                .any(|other| other.name == definition.name);
+            if is_duplicate {
+                return Err(HitError::InvalidSecondaryIndex(
+                    definition.name.clone(),
+                    "the index is declared twice".into(),
+                ));
+            }
+            definition.check(models)?;
+        }
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<DefaultKernel, HitError> {
+        self.check_names()?;
+        let mut resolved_models = HashMap::new();
+        let mut models = LinkedHashMap::new();
+        for name in self.models.keys() {
+            let model = self.resolve_model(name, &mut resolved_models, &mut vec![])?;
+            if !model.is_abstract {
+                self.check_interface_fields(&model)?;
+            }
+            models.insert(name.clone(), model);
+        }
+        self.check_secondary_indexes(&resolved_models)?;
+        kernel_init(resolved_models)?;
+        Ok(DefaultKernel {
+            models,
+            interfaces: self.interface_definitions,
+            instantiable_models: self.instantiable_models,
+            plugins: self.plugins,
+            schema_version: self.schema_version,
+            defaults_on_import: self.defaults_on_import,
+            secondary_indexes: self.secondary_indexes,
+            migrations: self.migrations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::field_types::{
+        FieldTypeBool, FieldTypeReference, FieldTypeString, FieldTypeStringVec,
+        FieldTypeSubobjectArray,
+    };
+    use crate::validators::{Validator, ValidatorContext};
+    use crate::{
+        modele, DeletePlugin, FieldKind, Hit, HitEntry, HitError, IndexEntryProperty, Interface,
+        Kernel, KernelBuilder, LinkedHashMap, ValidationError,
+    };
+
+    struct InitCounter {
+        count: Rc<RefCell<usize>>,
+    }
+
+    impl Validator<String> for InitCounter {
+        fn validate(
+            &self,
+            _value: &String,
+            _context: &ValidatorContext,
+        ) -> Result<Option<Vec<ValidationError>>, HitError> {
+            Ok(None)
+        }
+        fn on_kernel_init(&mut self, _field_name: &str, _model_name: &str) -> Result<(), HitError> {
+            *self.count.borrow_mut() += 1;
+            Ok(())
+        }
+    }
+
+    struct DeleteCounter {
+        count: usize,
+    }
+
+    impl DeletePlugin for DeleteCounter {
+        fn on_before_delete_entry(
+            &mut self,
+            _entry: &HitEntry,
+            _instance: &mut Hit,
+        ) -> Result<(), HitError> {
+            Ok(())
+        }
+        fn on_after_delete_entry(
+            &mut self,
+            _entry: &HitEntry,
+            _instance: &mut Hit,
+        ) -> Result<(), HitError> {
+            self.count += 1;
+            Ok(())
+        }
+    }
+
+    fn create_builder(init_count: Rc<RefCell<usize>>) -> KernelBuilder {
+        KernelBuilder::new()
+            .add_interface("fs/item")
+            .add_model(modele!("fs/root", "Root" =>
+                "items": FieldTypeSubobjectArray {
+                    authorized_models: vec!["fs/item".into()]
+                }
+            ))
+            .add_model(modele!("fs/folder", "Folder" =>
+                "name": FieldTypeString {
+                    validators: vec![Box::new(InitCounter { count: init_count })]
+                },
+                "link": FieldTypeReference {
+                    authorized_models: vec!["fs/folder".into()]
+                },
+                interfaces: "fs/item"
+            ))
+            .mark_instantiable("fs/folder")
+    }
+
+    #[test]
+    fn it_should_build_a_kernel() {
+        let init_count = Rc::new(RefCell::new(0));
+        let plugin = Rc::new(RefCell::new(DeleteCounter { count: 0 }));
+        let kernel = create_builder(init_count.clone())
+            .add_delete_plugin(plugin.clone())
+            .build()
+            .expect("Error");
+
+        assert_eq!(*init_count.borrow(), 1);
+        assert_eq!(kernel.get_models(), vec!["fs/root", "fs/folder"]);
+        let instantiable: Vec<&String> = kernel
+            .get_instantiable_models()
+            .iter()
+            .map(|model| model.get_name())
+            .collect();
+        assert_eq!(instantiable, vec!["fs/folder"]);
+
+        let mut hit = Hit::new("root", "fs/root", Rc::new(kernel)).expect("Error");
+        hit.insert(
+            "fs/folder",
+            "a",
+            LinkedHashMap::new(),
+            IndexEntryProperty {
+                id: "root".into(),
+                property: "items".into(),
+            },
+            None,
+        )
+        .expect("Error");
+        hit.remove_object("a").expect("Error");
+        assert_eq!(plugin.borrow().count, 1);
+    }
+
+    #[test]
+    fn it_should_check_the_names_of_the_models_and_interfaces() {
+        let count = Rc::new(RefCell::new(0));
+        let result = create_builder(count.clone())
+            .add_model(modele!("fs/file", "File" =>
+                "link": FieldTypeReference {
+                    authorized_models: vec!["fs/unknown".into()]
+                }
+            ))
+            .build();
+        assert!(matches!(
+            result,
+            Err(HitError::UnknownAuthorizedModel(model, field, name))
+                if model == "fs/file" && field == "link" && name == "fs/unknown"
+        ));
+
+        let result = create_builder(count.clone())
+            .add_model(modele!("fs/file", "File" => "name": FieldTypeString {}, interfaces: "fs/other"))
+            .build();
+        assert!(matches!(result, Err(HitError::UnknownInterface(_, name)) if name == "fs/other"));
+
+        let result = create_builder(count.clone())
+            .add_model(modele!("fs/root", "Root" => "name": FieldTypeString {}))
+            .build();
+        assert!(matches!(result, Err(HitError::DuplicateModel(name)) if name == "fs/root"));
+
+        let result = create_builder(count.clone())
+            .mark_instantiable("fs/file")
+            .build();
+        assert!(matches!(result, Err(HitError::ModelDoesNotExist(name)) if name == "fs/file"));
+        // the fields are not initialized when the kernel is invalid
+        assert_eq!(*count.borrow(), 0);
+    }
+
+    fn create_inheritance_builder() -> KernelBuilder {
+        KernelBuilder::new()
+            .add_model(modele!("fs/root", "Root" =>
+                "items": FieldTypeSubobjectArray {
+                    authorized_models: vec!["fs/node".into()]
+                }
+            ))
+            .add_model(modele!("fs/node", "Node" =>
+                "name": FieldTypeString {},
+                "hidden": FieldTypeBool {},
+                abstract: true
+            ))
+            .add_model(modele!("fs/folder", "Folder" =>
+                "items": FieldTypeSubobjectArray {
+                    authorized_models: vec!["fs/node".into()]
+                },
+                "name": FieldTypeString {
+                    required: true
+                },
+                extends: "fs/node"
+            ))
+            .add_model(modele!("fs/file", "File" =>
+                "tags": FieldTypeStringVec {},
+                extends: "fs/file_base"
+            ))
+            .add_model(modele!("fs/file_base", "File base" =>
+                "extension": FieldTypeString {},
+                extends: "fs/node",
+                abstract: true
+            ))
+            .mark_instantiable("fs/root")
+    }
+
+    fn get_field_names(kernel: &dyn Kernel, model: &str) -> Vec<String> {
+        kernel
+            .get_model(model)
+            .expect("Error")
+            .fields
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn it_should_inherit_the_fields_of_the_base_models() {
+        let kernel = create_inheritance_builder().build().expect("Error");
+
+        assert_eq!(get_field_names(&kernel, "fs/folder"), vec!["name", "hidden", "items"]);
+        assert_eq!(
+            get_field_names(&kernel, "fs/file"),
+            vec!["name", "hidden", "extension", "tags"]
+        );
+        let folder = kernel.get_model("fs/folder").expect("Error");
+        assert!(folder.get_field("name").unwrap().borrow().describe().required);
+        let file = kernel.get_model("fs/file").expect("Error");
+        assert_eq!(file.get_ancestors(), &vec!["fs/node", "fs/file_base"]);
+        assert!(file.is_a("fs/node"));
+        assert!(!folder.is_a("fs/file_base"));
+
+        let mut hit = Hit::new("root", "fs/root", Rc::new(kernel)).expect("Error");
+        let parent = IndexEntryProperty {
+            id: "root".into(),
+            property: "items".into(),
+        };
+        hit.insert("fs/file", "a", LinkedHashMap::new(), parent.clone(), None)
+            .expect("Error");
+        let result = hit.insert("fs/node", "b", LinkedHashMap::new(), parent, None);
+        assert!(matches!(result, Err(HitError::AbstractModel(name)) if name == "fs/node"));
+    }
+
+    #[test]
+    fn it_should_check_the_base_models() {
+        let result = create_inheritance_builder()
+            .add_model(modele!("fs/link", "Link" => "name": FieldTypeString {}, extends: "fs/other"))
+            .build();
+        assert!(matches!(
+            result,
+            Err(HitError::UnknownBaseModel(model, base)) if model == "fs/link" && base == "fs/other"
+        ));
+
+        let result = create_inheritance_builder()
+            .add_model(modele!("fs/a", "A" => "name": FieldTypeString {}, extends: "fs/b"))
+            .add_model(modele!("fs/b", "B" => "name": FieldTypeString {}, extends: "fs/a"))
+            .build();
+        assert!(matches!(result, Err(HitError::CyclicInheritance(_))));
+
+        let result = create_inheritance_builder()
+            .add_model(modele!("fs/link", "Link" => "hidden": FieldTypeString {}, extends: "fs/node"))
+            .build();
+        assert!(matches!(
+            result,
+            Err(HitError::InvalidFieldOverride(model, field)) if model == "fs/link" && field == "hidden"
+        ));
+
+        let result = create_inheritance_builder()
+            .mark_instantiable("fs/node")
+            .build();
+        assert!(matches!(result, Err(HitError::AbstractModel(name)) if name == "fs/node"));
+    }
+
+    #[test]
+    fn it_should_check_the_fields_of_the_interfaces() {
+        let mut interface = Interface::new("fs/item".into());
+        interface.fields.insert(
+            "name".into(),
+            Rc::new(RefCell::new(FieldTypeString {
+                name: "name".into(),
+                ..Default::default()
+            })),
+        );
+        let result = KernelBuilder::new()
+            .add_interface_definition(interface)
+            .add_model(modele!("fs/folder", "Folder" =>
+                "title": FieldTypeString {},
+                interfaces: "fs/item"
+            ))
+            .build();
+        assert!(matches!(
+            result,
+            Err(HitError::MissingInterfaceField(model, interface, field))
+                if model == "fs/folder" && interface == "fs/item" && field == "name"
+        ));
+
+        let mut interface = Interface::new("fs/item".into());
+        interface.fields.insert(
+            "name".into(),
+            Rc::new(RefCell::new(FieldTypeString {
+                name: "name".into(),
+                ..Default::default()
+            })),
+        );
+        // the field can be inherited
+        let kernel = KernelBuilder::new()
+            .add_interface_definition(interface)
+            .add_model(modele!("fs/node", "Node" => "name": FieldTypeString {}, abstract: true))
+            .add_model(modele!("fs/folder", "Folder" =>
+                "title": FieldTypeString {},
+                interfaces: "fs/item",
+                extends: "fs/node"
+            ))
+            .build()
+            .expect("Error");
+        let interface = kernel.get_interface("fs/item").expect("Error");
+        assert_eq!(
+            interface.get_field("name").unwrap().borrow().get_kind(),
+            FieldKind::String
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::rc::Rc;
+
+use linked_hash_map::LinkedHashMap;
+
+use crate::migrations::Migrations;
+use crate::secondary_index::SecondaryIndexDefinition;
+use crate::{HitError, Interface, Kernel, Model, Plugins};
+
+// The kernel created by `KernelBuilder`, the models are listed in the order they were added
+pub struct DefaultKernel {
+    pub(super) models: LinkedHashMap<String, Rc<Model>>,
+    pub(super) interfaces: LinkedHashMap<String, Rc<Interface>>,
+    pub(super) instantiable_models: Vec<String>,
+    pub(super) plugins: Plugins,
+    pub(super) schema_version: u32,
+    pub(super) defaults_on_import: bool,
+    pub(super) secondary_indexes: Vec<SecondaryIndexDefinition>,
+    pub(super) migrations: Option<Migrations>,
+}
+
+impl Kernel for DefaultKernel {
+    fn get_model(&self, name: &str) -> Result<Rc<Model>, HitError> {
+        match self.models.get(name) {
+            Some(model) => Ok(model.clone()),
+            None => Err(HitError::ModelDoesNotExist(name.to_string())),
+        }
+    }
+
+    fn get_instantiable_models(&self) -> Vec<&Model> {
+        self.instantiable_models
+            .iter()
+            .filter_map(|name| self.models.get(name))
+            .map(|model| model.as_ref())
+            .collect()
+    }
+
+    fn get_plugins(&self) -> Plugins {
+        self.plugins.clone()
+    }
+
+    fn get_models(&self) -> Vec<String> {
+        self.models.keys().cloned().collect()
+    }
+
+    fn get_schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    fn has_defaults_on_import(&self) -> bool {
+        self.defaults_on_import
+    }
+
+    fn get_interface(&self, name: &str) -> Option<Rc<Interface>> {
+        self.interfaces.get(name).cloned()
+    }
+
+    fn get_secondary_index_definitions(&self) -> &[SecondaryIndexDefinition] {
+        &self.secondary_indexes
+    }
+
+    fn get_migrations(&self) -> Option<&Migrations> {
+        self.migrations.as_ref()
+    }
+}
@@ -0,0 +1,40 @@
+mod builder;
+mod default_kernel;
+
+use crate::migrations::Migrations;
+use crate::model::{Interface, Model};
+use crate::plugins::Plugins;
+use crate::secondary_index::SecondaryIndexDefinition;
+use crate::HitError;
+use std::rc::Rc;
+
+pub use builder::KernelBuilder;
+pub use default_kernel::DefaultKernel;
+
+pub trait Kernel {
+    fn get_model(&self, name: &str) -> Result<Rc<Model>, HitError>;
+    fn get_instantiable_models(&self) -> Vec<&Model>;
+    fn get_plugins(&self) -> Plugins;
+    fn get_models(&self) -> Vec<String>;
+    // the version of the models, written in the exports, see `Migrations`
+    fn get_schema_version(&self) -> u32 {
+        0
+    }
+    // the imports set the defaults of the null fields
+    fn has_defaults_on_import(&self) -> bool {
+        false
+    }
+    // the interfaces that declare fields
+    fn get_interface(&self, _name: &str) -> Option<Rc<Interface>> {
+        None
+    }
+    // the indexes declared with `KernelBuilder::add_secondary_index`, each `Hit` keeps
+    // its own values of these indexes
+    fn get_secondary_index_definitions(&self) -> &[SecondaryIndexDefinition] {
+        &[]
+    }
+    // the imports migrate the older documents to the schema version with these migrations
+    fn get_migrations(&self) -> Option<&Migrations> {
+        None
+    }
+}
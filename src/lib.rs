@@ -2,6 +2,8 @@
 #[macro_use]
 extern crate mopa;
 
+mod binary;
+mod crdt;
 mod errors;
 pub mod events;
 mod hit_mod;
@@ -9,10 +11,17 @@ mod index;
 mod json;
 mod kernel;
 mod macros;
+mod migrations;
 mod model;
 mod object_data;
+mod operations;
+mod path;
 mod plugins;
 mod import;
+mod query;
+mod schema;
+mod secondary_index;
+mod traversal;
 #[cfg(test)]
 mod test_kernel;
 #[cfg(test)]
@@ -24,7 +33,7 @@ pub mod prelude;
 
 pub use index::IndexEntryProperty;
 
-pub use kernel::Kernel;
+pub use kernel::{DefaultKernel, Kernel, KernelBuilder};
 pub use model::*;
 
 pub use object_data::Id;
@@ -34,16 +43,40 @@ pub use object_data::Reference;
 
 pub use hit_mod::{Hit, HitEntry, HitKernel};
 
+pub use operations::{Operation, OperationRecord};
+
+pub use crdt::{CrdtChange, CrdtMessage, Replica, Timestamp};
+
 pub use events::FieldListener;
 pub use events::FieldListenerRef;
 
+pub use binary::export::export_binary;
+pub use binary::import::import_binary;
+pub use binary::BinaryImportError;
+
 pub use json::export::export;
+pub use json::export::export_to_writer;
 pub use json::import::import;
+pub use json::import::import_from_reader;
 pub use json::import::import_from_string;
+pub use json::import::import_with_operation_log;
+
+pub use migrations::{
+    DocumentMigration, MigrationError, MigrationStep, Migrations, ValueConverter,
+};
+pub use path::PathTarget;
+pub use query::{Comparison, Query, QueryError, QueryIndex};
+pub use secondary_index::{SecondaryIndexDefinition, SecondaryIndexKind, SecondaryIndexes};
+pub use traversal::{Ancestors, Descendants, TraversalEntry, TraversalOrder};
+pub use schema::{export_schema, load_kernel_from_json, SchemaError};
+#[cfg(feature = "toml")]
+pub use schema::load_kernel_from_toml;
+#[cfg(feature = "yaml")]
+pub use schema::load_kernel_from_yaml;
 
 pub use plugins::{
     AfterImportPlugin, DeletePlugin, InitEntryPlugin, ModelTypeIndexer, Plugin, Plugins,
-    ReferencePlugin,
+    ReferencePlugin, TransactionPlugin,
 };
 
 pub use errors::HitError;
@@ -7,9 +7,12 @@ macro_rules! modele {
             $(
                 $field_name:literal: $field:ident $({
                     $($key: ident: $value: expr ),*$(,)?
-                })?
+                })? $(= $default: expr)?
             ),*
             $(,interfaces: $($interfaces: literal),*)?
+            $(,extends: $extends: literal)?
+            $(,abstract: $is_abstract: literal)?
+            $(,slot_policies: $($slot_field: literal => $slot_policy: expr),*)?
             $(,)?
     ) => {
         {
@@ -24,8 +27,12 @@ macro_rules! modele {
                     $($key: $value,)*
                     ..Default::default()
                 }))
-            );)*)?
-            $($(mdl.interfaces.push(String::from($interfaces));),*)?
+            );)*
+            $(mdl.set_default($field_name, $default);)?)*
+            $($(mdl.interfaces.push(String::from($interfaces));)*)?
+            $(mdl.extends = Some(String::from($extends));)?
+            $(mdl.is_abstract = $is_abstract;)?
+            $($(mdl.set_slot_policy($slot_field, $slot_policy);)*)?
             std::rc::Rc::new(mdl)
         }
     }
@@ -0,0 +1,487 @@
+mod steps;
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::json::export::export;
+use crate::json::import::import;
+use crate::json::{get_document_version, JSONImportError};
+use crate::{Hit, HitKernel};
+
+pub use steps::{DocumentMigration, MigrationStep, ValueConverter};
+
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error("Invalid document: {0}")]
+    InvalidDocument(String),
+    #[error("The document has the version {0}, which is newer than the version {1}")]
+    NewerVersion(u32, u32),
+    #[error("No migration from the version {0}")]
+    MissingMigration(u32),
+    #[error("Field `{1}` of object `{0}` cannot be converted: {2}")]
+    InvalidValue(String, String, String),
+    #[error("Export failed: {0}")]
+    ExportError(String),
+    #[error("Import failed: {0:?}")]
+    ImportError(JSONImportError),
+    #[error("{0}")]
+    Custom(String),
+}
+
+// The steps that transform the documents of each version of the models into documents
+// of the next version
+#[derive(Default, Clone)]
+pub struct Migrations {
+    steps: BTreeMap<u32, Vec<MigrationStep>>,
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Migrations {
+            steps: BTreeMap::new(),
+        }
+    }
+
+    // the steps migrate the documents from `version` to `version + 1`
+    pub fn add_migration(mut self, version: u32, steps: Vec<MigrationStep>) -> Self {
+        self.steps.entry(version).or_default().extend(steps);
+        self
+    }
+
+    // there are steps for every version from `from_version` to `to_version`
+    pub fn can_migrate(&self, from_version: u32, to_version: u32) -> bool {
+        (from_version..to_version).all(|version| self.steps.contains_key(&version))
+    }
+
+    // Transforms a document produced by `export` into a document of the version `to_version`,
+    // which is written in the document
+    pub fn migrate(&self, document: &mut Value, to_version: u32) -> Result<(), MigrationError> {
+        self.migrate_document(document, to_version, None)
+    }
+
+    // Migrates a document to the version of the kernel. The steps also change the objects
+    // whose model inherits the changed model in the kernel.
+    pub fn migrate_to_kernel(
+        &self,
+        document: &mut Value,
+        kernel: &HitKernel,
+    ) -> Result<(), MigrationError> {
+        self.migrate_document(document, kernel.get_schema_version(), Some(kernel))
+    }
+
+    fn migrate_document(
+        &self,
+        document: &mut Value,
+        to_version: u32,
+        kernel: Option<&HitKernel>,
+    ) -> Result<(), MigrationError> {
+        let mut version = document
+            .as_object()
+            .ok_or_else(|| MigrationError::InvalidDocument("should be an object".into()))
+            .and_then(|document| {
+                get_document_version(document)
+                    .map_err(|_| MigrationError::InvalidDocument("invalid `version`".into()))
+            })?;
+        if version > to_version {
+            return Err(MigrationError::NewerVersion(version, to_version));
+        }
+        while version < to_version {
+            let steps = self
+                .steps
+                .get(&version)
+                .ok_or(MigrationError::MissingMigration(version))?;
+            for step in steps.iter() {
+                step.apply(document, kernel)?;
+            }
+            version += 1;
+            document["version"] = Value::from(version);
+        }
+        Ok(())
+    }
+
+    // Migrates a document to the version of the kernel, and imports it
+    pub fn import(&self, document: &Value, kernel: Rc<HitKernel>) -> Result<Hit, MigrationError> {
+        let mut document = document.clone();
+        self.migrate_to_kernel(&mut document, kernel.as_ref())?;
+        import(&document, kernel).map_err(MigrationError::ImportError)
+    }
+
+    // Creates a copy of a document that uses the models of `kernel`
+    pub fn migrate_hit(&self, hit: &Hit, kernel: Rc<HitKernel>) -> Result<Hit, MigrationError> {
+        let document = export(hit).map_err(MigrationError::ExportError)?;
+        self.import(&document, kernel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use serde_json::{json, Value};
+
+    use crate::field_types::{
+        FieldTypeFloat, FieldTypeInteger, FieldTypeString, FieldTypeSubobject,
+        FieldTypeSubobjectArray,
+    };
+    use crate::json::import::{import, import_from_reader};
+    use crate::json::JSONImportError;
+    use crate::migrations::{MigrationError, MigrationStep, Migrations};
+    use crate::{
+        export_binary, import_binary, modele, BinaryImportError, Hit, HitKernel,
+        IndexEntryProperty, KernelBuilder, ObjectValue, ObjectValues,
+    };
+
+    // version 0 : the files have a `title` and a float `size`
+    fn create_document() -> Value {
+        json!({
+            "id": "root",
+            "data": [{
+                "model": "fs/folder",
+                "id": "root",
+                "data": {
+                    "items": {"type": "subobject_array", "value": [{"id": "a"}]}
+                },
+                "parent": null
+            }, {
+                "model": "fs/document",
+                "id": "a",
+                "data": {"title": "notes", "size": 12.0, "author": "me"},
+                "parent": {"id": "root", "property": "items"}
+            }]
+        })
+    }
+
+    // version 2 : the files are renamed, their title is their name and the author is
+    // in a subobject
+    fn create_kernel() -> Rc<HitKernel> {
+        Rc::new(create_kernel_builder().build().expect("Error"))
+    }
+
+    fn create_kernel_builder() -> KernelBuilder {
+        KernelBuilder::new()
+            .set_schema_version(2)
+            .add_model(modele!("fs/folder", "Folder" =>
+                "entries": FieldTypeSubobjectArray {
+                    authorized_models: vec!["fs/file".into()]
+                }
+            ))
+            .add_model(modele!("fs/file", "File" =>
+                "name": FieldTypeString {},
+                "size": FieldTypeInteger {},
+                "metadata": FieldTypeSubobjectArray {
+                    authorized_models: vec!["fs/metadata".into()]
+                }
+            ))
+            .add_model(modele!("fs/metadata", "Metadata" =>
+                "author": FieldTypeString {}
+            ))
+            .mark_instantiable("fs/folder")
+    }
+
+    fn create_migrations() -> Migrations {
+        Migrations::new()
+            .add_migration(
+                0,
+                vec![
+                    MigrationStep::rename_model("fs/document", "fs/file"),
+                    MigrationStep::rename_field("fs/folder", "items", "entries"),
+                    MigrationStep::rename_field("fs/file", "title", "name"),
+                ],
+            )
+            .add_migration(
+                1,
+                vec![
+                    MigrationStep::change_type("fs/file", "size", |value| {
+                        value
+                            .as_f64()
+                            .map(|size| json!(size.round() as i64))
+                            .ok_or_else(|| "not a number".to_string())
+                    }),
+                    MigrationStep::move_field_to_subobject(
+                        "fs/file",
+                        "author",
+                        "metadata",
+                        "fs/metadata",
+                    ),
+                ],
+            )
+    }
+
+    #[test]
+    fn it_should_migrate_a_document() {
+        let kernel = create_kernel();
+        let result = import(&create_document(), kernel.clone());
+        assert!(matches!(
+            result,
+            Err(JSONImportError::InvalidSchemaVersion(0, 2))
+        ));
+
+        let hit = create_migrations()
+            .import(&create_document(), kernel)
+            .expect("Error");
+        assert_eq!(
+            hit.get_value("root", "entries"),
+            Some(ObjectValue::VecSubObjects(vec![crate::Reference {
+                id: "a".into()
+            }]))
+        );
+        assert_eq!(hit.get_model("a").unwrap().get_name(), "fs/file");
+        assert_eq!(
+            hit.get_value("a", "name"),
+            Some(ObjectValue::String("notes".into()))
+        );
+        assert_eq!(hit.get_value("a", "size"), Some(ObjectValue::I32(12)));
+        assert_eq!(
+            hit.get_value("a_metadata", "author"),
+            Some(ObjectValue::String("me".into()))
+        );
+        assert_eq!(hit.get_parent("a_metadata").unwrap().property, "metadata");
+    }
+
+    #[test]
+    fn it_should_migrate_a_hit_and_stop_on_errors() {
+        let migrations = create_migrations();
+        let mut document = create_document();
+        migrations.migrate(&mut document, 1).expect("Error");
+        assert_eq!(document["version"], json!(1));
+        assert_eq!(document["data"][1]["data"]["size"], json!(12.0));
+
+        // a live document of the intermediate version
+        let kernel = KernelBuilder::new()
+            .set_schema_version(1)
+            .add_model(modele!("fs/folder", "Folder" =>
+                "entries": FieldTypeSubobjectArray {
+                    authorized_models: vec!["fs/file".into()]
+                }
+            ))
+            .add_model(modele!("fs/file", "File" =>
+                "name": FieldTypeString {},
+                "author": FieldTypeString {}
+            ))
+            .build()
+            .expect("Error");
+        let mut document = create_document();
+        migrations.migrate(&mut document, 1).expect("Error");
+        document["data"][1]["data"]
+            .as_object_mut()
+            .unwrap()
+            .remove("size");
+        let hit = import(&document, Rc::new(kernel)).expect("Error");
+        let hit = migrations
+            .migrate_hit(&hit, create_kernel())
+            .expect("Error");
+        assert_eq!(hit.get_value("a", "size"), Some(ObjectValue::Null));
+        assert_eq!(
+            hit.get_value("a_metadata", "author"),
+            Some(ObjectValue::String("me".into()))
+        );
+
+        let mut document = create_document();
+        document["data"][1]["data"]["size"] = json!("big");
+        let result = migrations.migrate(&mut document, 2);
+        assert!(matches!(
+            result,
+            Err(MigrationError::InvalidValue(id, field, _)) if id == "a" && field == "size"
+        ));
+        let result = migrations.migrate(&mut create_document(), 3);
+        assert!(matches!(result, Err(MigrationError::MissingMigration(2))));
+        let mut document = create_document();
+        document["version"] = json!(3);
+        let result = migrations.migrate(&mut document, 2);
+        assert!(matches!(result, Err(MigrationError::NewerVersion(3, 2))));
+    }
+
+    fn assert_migrated(hit: &Hit) {
+        assert_eq!(hit.get_model("a").unwrap().get_name(), "fs/file");
+        assert_eq!(
+            hit.get_value("a", "name"),
+            Some(ObjectValue::String("notes".into()))
+        );
+        assert_eq!(hit.get_value("a", "size"), Some(ObjectValue::I32(12)));
+        assert_eq!(
+            hit.get_value("a_metadata", "author"),
+            Some(ObjectValue::String("me".into()))
+        );
+    }
+
+    #[test]
+    fn it_should_migrate_the_imported_documents_with_the_migrations_of_the_kernel() {
+        let kernel = Rc::new(
+            create_kernel_builder()
+                .set_migrations(create_migrations())
+                .build()
+                .expect("Error"),
+        );
+        assert_migrated(&import(&create_document(), kernel.clone()).expect("Error"));
+
+        let json = serde_json::to_string(&create_document()).unwrap();
+        let mut progress = vec![];
+        let hit = import_from_reader(json.as_bytes(), kernel.clone(), &mut |count| {
+            progress.push(count)
+        })
+        .expect("Error");
+        assert_migrated(&hit);
+        assert_eq!(progress, vec![2]);
+
+        // a binary export of the version 0
+        let old_kernel = KernelBuilder::new()
+            .add_model(modele!("fs/folder", "Folder" =>
+                "items": FieldTypeSubobjectArray {
+                    authorized_models: vec!["fs/document".into()]
+                }
+            ))
+            .add_model(modele!("fs/document", "Document" =>
+                "title": FieldTypeString {},
+                "size": FieldTypeFloat {},
+                "author": FieldTypeString {}
+            ))
+            .build()
+            .expect("Error");
+        let mut hit = Hit::new("root", "fs/folder", Rc::new(old_kernel)).expect("Error");
+        let mut values = ObjectValues::new();
+        values.insert("title".into(), ObjectValue::String("notes".into()));
+        values.insert("size".into(), ObjectValue::F32(12.0));
+        values.insert("author".into(), ObjectValue::String("me".into()));
+        hit.insert(
+            "fs/document",
+            "a",
+            values,
+            IndexEntryProperty {
+                id: "root".into(),
+                property: "items".into(),
+            },
+            None,
+        )
+        .expect("Error");
+        let bytes = export_binary(&hit).expect("Error");
+        assert_migrated(&import_binary(&bytes, kernel.clone()).expect("Error"));
+
+        // the newer documents and the versions without migrations are rejected
+        let mut document = create_document();
+        document["version"] = json!(3);
+        assert!(matches!(
+            import(&document, kernel),
+            Err(JSONImportError::InvalidSchemaVersion(3, 2))
+        ));
+        let kernel = Rc::new(
+            create_kernel_builder()
+                .set_migrations(Migrations::new().add_migration(1, vec![]))
+                .build()
+                .expect("Error"),
+        );
+        assert!(matches!(
+            import(&create_document(), kernel.clone()),
+            Err(JSONImportError::InvalidSchemaVersion(0, 2))
+        ));
+        assert_eq!(
+            import_binary(&bytes, kernel).err(),
+            Some(BinaryImportError::InvalidSchemaVersion(0, 2))
+        );
+    }
+
+    #[test]
+    fn it_should_migrate_the_inherited_fields_and_the_single_subobjects() {
+        // the files inherit the name and the size of the items, and have a single metadata
+        let kernel = KernelBuilder::new()
+            .set_schema_version(2)
+            .add_model(modele!("fs/folder", "Folder" =>
+                "items": FieldTypeSubobjectArray {
+                    authorized_models: vec!["fs/document".into()]
+                }
+            ))
+            .add_model(modele!("fs/item", "Item" =>
+                "name": FieldTypeString {},
+                "size": FieldTypeInteger {},
+                abstract: true
+            ))
+            .add_model(modele!("fs/document", "Document" =>
+                "metadata": FieldTypeSubobject {
+                    authorized_models: vec!["fs/metadata".into()]
+                },
+                extends: "fs/item"
+            ))
+            .add_model(modele!("fs/metadata", "Metadata" =>
+                "author": FieldTypeString {}
+            ))
+            .set_migrations(
+                Migrations::new()
+                    .add_migration(
+                        0,
+                        vec![MigrationStep::rename_field("fs/item", "title", "name")],
+                    )
+                    .add_migration(
+                        1,
+                        vec![
+                            MigrationStep::change_type("fs/item", "size", |value| {
+                                Ok(json!(value.as_f64().unwrap_or(0.0).round() as i64))
+                            }),
+                            MigrationStep::move_field_to_subobject(
+                                "fs/item",
+                                "author",
+                                "metadata",
+                                "fs/metadata",
+                            ),
+                        ],
+                    ),
+            )
+            .build()
+            .expect("Error");
+        let hit = import(&create_document(), Rc::new(kernel)).expect("Error");
+        assert_eq!(
+            hit.get_value("a", "name"),
+            Some(ObjectValue::String("notes".into()))
+        );
+        assert_eq!(hit.get_value("a", "size"), Some(ObjectValue::I32(12)));
+        assert_eq!(
+            hit.get_value("a", "metadata"),
+            Some(ObjectValue::SubObject(crate::Reference {
+                id: "a_metadata".into()
+            }))
+        );
+        assert_eq!(
+            hit.get_value("a_metadata", "author"),
+            Some(ObjectValue::String("me".into()))
+        );
+
+        // without a kernel, the kind of the field is read in the document
+        let migrations = Migrations::new().add_migration(
+            0,
+            vec![MigrationStep::move_field_to_subobject(
+                "fs/document",
+                "author",
+                "metadata",
+                "fs/metadata",
+            )],
+        );
+        let mut document = create_document();
+        document["data"][1]["data"]["metadata"] =
+            json!({"type": "subobject", "value": {"id": "other"}});
+        let result = migrations.migrate(&mut document, 1);
+        assert!(matches!(
+            result,
+            Err(MigrationError::InvalidValue(id, field, _)) if id == "a" && field == "metadata"
+        ));
+    }
+
+    #[test]
+    fn it_should_run_custom_steps() {
+        let migrations = Migrations::new().add_migration(
+            0,
+            vec![MigrationStep::custom(|document| {
+                let objects = document["data"]
+                    .as_array_mut()
+                    .ok_or_else(|| MigrationError::Custom("no data".into()))?;
+                objects.retain(|object| object["id"] != json!("a"));
+                document["data"][0]["data"] = json!({});
+                Ok(())
+            })],
+        );
+        let mut document = create_document();
+        migrations.migrate(&mut document, 1).expect("Error");
+        assert_eq!(document["data"].as_array().unwrap().len(), 1);
+        assert_eq!(document["version"], json!(1));
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use serde_json::{json, Map, Value};
+
+use crate::migrations::MigrationError;
+use crate::model::FieldKind;
+use crate::HitKernel;
+
+// Converts the exported value of a field, for instance `12.0` to `12`
+pub type ValueConverter = Rc<dyn Fn(&Value) -> Result<Value, String>>;
+// Receives the whole exported document, with its `id`, `version` and `data` properties
+pub type DocumentMigration = Rc<dyn Fn(&mut Value) -> Result<(), MigrationError>>;
+
+// A change of the models, applied to an exported document
+#[derive(Clone)]
+pub enum MigrationStep {
+    RenameField {
+        model: String,
+        from: String,
+        to: String,
+    },
+    // the converter is not called for the null values
+    ChangeType {
+        model: String,
+        field: String,
+        converter: ValueConverter,
+    },
+    RenameModel {
+        from: String,
+        to: String,
+    },
+    // moves the value of a field into an object of a subobject field. The object is the first
+    // one of the subobject model in the field, or a new one that has the id `{id}_{subobject_field}`
+    MoveFieldToSubobject {
+        model: String,
+        field: String,
+        subobject_field: String,
+        subobject_model: String,
+    },
+    Custom(DocumentMigration),
+}
+
+impl MigrationStep {
+    pub fn rename_field(model: &str, from: &str, to: &str) -> MigrationStep {
+        MigrationStep::RenameField {
+            model: model.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    pub fn change_type<F>(model: &str, field: &str, converter: F) -> MigrationStep
+    where
+        F: Fn(&Value) -> Result<Value, String> + 'static,
+    {
+        MigrationStep::ChangeType {
+            model: model.to_string(),
+            field: field.to_string(),
+            converter: Rc::new(converter),
+        }
+    }
+
+    pub fn rename_model(from: &str, to: &str) -> MigrationStep {
+        MigrationStep::RenameModel {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    pub fn move_field_to_subobject(
+        model: &str,
+        field: &str,
+        subobject_field: &str,
+        subobject_model: &str,
+    ) -> MigrationStep {
+        MigrationStep::MoveFieldToSubobject {
+            model: model.to_string(),
+            field: field.to_string(),
+            subobject_field: subobject_field.to_string(),
+            subobject_model: subobject_model.to_string(),
+        }
+    }
+
+    pub fn custom<F>(migration: F) -> MigrationStep
+    where
+        F: Fn(&mut Value) -> Result<(), MigrationError> + 'static,
+    {
+        MigrationStep::Custom(Rc::new(migration))
+    }
+
+    // The models of the kernel are matched with `is_a`, so that the steps of a base model
+    // or an interface also change the models that inherit the field
+    pub(super) fn apply(
+        &self,
+        document: &mut Value,
+        kernel: Option<&HitKernel>,
+    ) -> Result<(), MigrationError> {
+        let models = Models { kernel };
+        match self {
+            MigrationStep::RenameField { model, from, to } => {
+                rename_field(get_objects(document)?, &models, model, from, to)
+            }
+            MigrationStep::ChangeType {
+                model,
+                field,
+                converter,
+            } => change_type(get_objects(document)?, &models, model, field, converter),
+            MigrationStep::RenameModel { from, to } => {
+                for object in get_objects(document)?.iter_mut() {
+                    if get_model(object)? == from {
+                        object["model"] = Value::String(to.clone());
+                    }
+                }
+                Ok(())
+            }
+            MigrationStep::MoveFieldToSubobject {
+                model,
+                field,
+                subobject_field,
+                subobject_model,
+            } => move_field_to_subobject(
+                get_objects(document)?,
+                &models,
+                model,
+                field,
+                subobject_field,
+                subobject_model,
+            ),
+            MigrationStep::Custom(migration) => migration(document),
+        }
+    }
+}
+
+struct Models<'a> {
+    kernel: Option<&'a HitKernel>,
+}
+
+impl<'a> Models<'a> {
+    fn is_a(&self, object: &Value, model: &str) -> Result<bool, MigrationError> {
+        let name = get_model(object)?;
+        Ok(name == model
+            || self
+                .kernel
+                .and_then(|kernel| kernel.get_model(name).ok())
+                .is_some_and(|object_model| object_model.is_a(model)))
+    }
+
+    // the kind of the field when the model is in the kernel, an array otherwise
+    fn is_single_subobject(&self, object: &Value, field: &str) -> Result<bool, MigrationError> {
+        let name = get_model(object)?;
+        Ok(self
+            .kernel
+            .and_then(|kernel| kernel.get_model(name).ok())
+            .and_then(|model| {
+                model
+                    .get_field(field)
+                    .map(|field| field.borrow().get_kind())
+            })
+            == Some(FieldKind::Subobject))
+    }
+}
+
+fn get_objects(document: &mut Value) -> Result<&mut Vec<Value>, MigrationError> {
+    document
+        .get_mut("data")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| MigrationError::InvalidDocument("`data` should be an array".into()))
+}
+
+fn get_id(object: &Value) -> Result<&str, MigrationError> {
+    object
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| MigrationError::InvalidDocument("an object has no `id`".into()))
+}
+
+fn get_model(object: &Value) -> Result<&str, MigrationError> {
+    object
+        .get("model")
+        .and_then(Value::as_str)
+        .ok_or_else(|| MigrationError::InvalidDocument("an object has no `model`".into()))
+}
+
+fn get_data(object: &mut Value) -> Result<&mut Map<String, Value>, MigrationError> {
+    let id = get_id(object)?.to_string();
+    object
+        .get_mut("data")
+        .and_then(Value::as_object_mut)
+        .ok_or(MigrationError::InvalidDocument(format!(
+            "the object `{}` has no `data`",
+            id
+        )))
+}
+
+fn get_ids_of_model(
+    objects: &[Value],
+    models: &Models,
+    model: &str,
+) -> Result<HashSet<String>, MigrationError> {
+    let mut ids = HashSet::new();
+    for object in objects.iter() {
+        if models.is_a(object, model)? {
+            ids.insert(get_id(object)?.to_string());
+        }
+    }
+    Ok(ids)
+}
+
+fn rename_field(
+    objects: &mut [Value],
+    models: &Models,
+    model: &str,
+    from: &str,
+    to: &str,
+) -> Result<(), MigrationError> {
+    let ids = get_ids_of_model(objects, models, model)?;
+    for object in objects.iter_mut() {
+        if models.is_a(object, model)? {
+            let data = get_data(object)?;
+            if let Some(value) = data.remove(from) {
+                data.insert(to.to_string(), value);
+            }
+        }
+        // the subobjects keep the name of the property of their parent
+        if let Some(parent) = object.get_mut("parent").and_then(Value::as_object_mut) {
+            let is_renamed = matches!(parent.get("id").and_then(Value::as_str), Some(id) if ids.contains(id))
+                && parent.get("property").and_then(Value::as_str) == Some(from);
+            if is_renamed {
+                parent.insert("property".into(), Value::String(to.to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn change_type(
+    objects: &mut [Value],
+    models: &Models,
+    model: &str,
+    field: &str,
+    converter: &ValueConverter,
+) -> Result<(), MigrationError> {
+    for object in objects.iter_mut() {
+        if !models.is_a(object, model)? {
+            continue;
+        }
+        let id = get_id(object)?.to_string();
+        let data = get_data(object)?;
+        let value = match data.get(field) {
+            Some(Value::Null) | None => continue,
+            Some(value) => value,
+        };
+        let value = converter(value)
+            .map_err(|error| MigrationError::InvalidValue(id, field.to_string(), error))?;
+        data.insert(field.to_string(), value);
+    }
+    Ok(())
+}
+
+// the ids of a `subobject` or a `subobject_array` value
+fn get_subobject_ids(value: Option<&Value>) -> Vec<String> {
+    let references = match value.and_then(|value| value.get("value")) {
+        Some(Value::Array(references)) => references.iter().collect(),
+        Some(reference) => vec![reference],
+        None => vec![],
+    };
+    references
+        .into_iter()
+        .filter_map(|reference| reference.get("id").and_then(Value::as_str))
+        .map(String::from)
+        .collect()
+}
+
+fn is_single_subobject(value: Option<&Value>) -> bool {
+    value
+        .and_then(|value| value.get("type"))
+        .and_then(Value::as_str)
+        == Some("subobject")
+}
+
+fn move_field_to_subobject(
+    objects: &mut Vec<Value>,
+    models: &Models,
+    model: &str,
+    field: &str,
+    subobject_field: &str,
+    subobject_model: &str,
+) -> Result<(), MigrationError> {
+    let subobject_ids = get_ids_of_model(objects, models, subobject_model)?;
+    let mut used_ids = HashSet::new();
+    for object in objects.iter() {
+        used_ids.insert(get_id(object)?.to_string());
+    }
+    // the values to set in the existing subobjects
+    let mut moved_values = vec![];
+    let mut new_objects = vec![];
+
+    for object in objects.iter_mut() {
+        if !models.is_a(object, model)? {
+            continue;
+        }
+        let id = get_id(object)?.to_string();
+        let is_single = models.is_single_subobject(object, subobject_field)?;
+        let data = get_data(object)?;
+        let value = match data.remove(field) {
+            Some(Value::Null) | None => continue,
+            Some(value) => value,
+        };
+        let children = get_subobject_ids(data.get(subobject_field));
+        if let Some(child_id) = children.iter().find(|child| subobject_ids.contains(*child)) {
+            moved_values.push((child_id.clone(), value));
+            continue;
+        }
+        let is_single = is_single || is_single_subobject(data.get(subobject_field));
+        if is_single && !children.is_empty() {
+            return Err(MigrationError::InvalidValue(
+                id,
+                subobject_field.to_string(),
+                "the subobject field already has an object".into(),
+            ));
+        }
+
+        let mut child_id = format!("{}_{}", id, subobject_field);
+        let mut suffix = 1;
+        while used_ids.contains(&child_id) {
+            suffix += 1;
+            child_id = format!("{}_{}_{}", id, subobject_field, suffix);
+        }
+        used_ids.insert(child_id.clone());
+        let subobjects = if is_single {
+            json!({ "type": "subobject", "value": { "id": child_id } })
+        } else {
+            let mut references: Vec<Value> =
+                children.iter().map(|id| json!({ "id": id })).collect();
+            references.push(json!({ "id": child_id }));
+            json!({ "type": "subobject_array", "value": references })
+        };
+        data.insert(subobject_field.to_string(), subobjects);
+        new_objects.push(json!({
+            "model": subobject_model,
+            "id": child_id,
+            "data": { field: value },
+            "parent": { "id": id, "property": subobject_field }
+        }));
+    }
+
+    for (child_id, value) in moved_values.into_iter() {
+        for object in objects.iter_mut() {
+            if get_id(object)? == child_id {
+                get_data(object)?.insert(field.to_string(), value);
+                break;
+            }
+        }
+    }
+    objects.extend(new_objects);
+    Ok(())
+}
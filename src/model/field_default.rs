@@ -0,0 +1,42 @@
+use std::rc::Rc;
+
+use crate::{Hit, HitError, IndexEntryProperty, ObjectValue};
+
+// Receives the parent of the new object (None for the main object), and the document
+pub type DefaultInitializer =
+    Rc<dyn Fn(Option<&IndexEntryProperty>, &Hit) -> Result<ObjectValue, HitError>>;
+
+// The value of a field when an object is created without it
+#[derive(Clone)]
+pub enum FieldDefault {
+    Value(ObjectValue),
+    Initializer(DefaultInitializer),
+}
+
+impl FieldDefault {
+    pub fn initializer<F>(initializer: F) -> FieldDefault
+    where
+        F: Fn(Option<&IndexEntryProperty>, &Hit) -> Result<ObjectValue, HitError> + 'static,
+    {
+        FieldDefault::Initializer(Rc::new(initializer))
+    }
+
+    // only the constant defaults are written in the schema documents
+    pub fn get_constant(&self) -> Option<&ObjectValue> {
+        match self {
+            FieldDefault::Value(value) => Some(value),
+            FieldDefault::Initializer(_) => None,
+        }
+    }
+
+    pub fn get_value(
+        &self,
+        parent: Option<&IndexEntryProperty>,
+        hit: &Hit,
+    ) -> Result<ObjectValue, HitError> {
+        match self {
+            FieldDefault::Value(value) => Ok(value.clone()),
+            FieldDefault::Initializer(initializer) => initializer(parent, hit),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::model::{FieldDescription, FieldKind};
+use crate::model::{Model, ModelField};
+use crate::object_data::ObjectValue;
+use crate::{errors::ValidationError, model::validators::ValidatorContext, HitError};
+
+// The objects of `source_model` that reference this object through their `source_field`.
+// The value is read from the reference index : it is not stored, and cannot be set.
+#[derive(Default)]
+pub struct FieldTypeBacklinks {
+    pub name: String,
+    pub source_model: String,
+    pub source_field: String,
+    // writes the value in the JSON exports, the imports ignore it
+    pub exported: bool,
+}
+
+impl ModelField for FieldTypeBacklinks {
+    fn on_kernel_init(&mut self, _model_name: &str) -> Result<(), HitError> {
+        Ok(())
+    }
+    fn get_name(&self) -> String {
+        String::from(&self.name)
+    }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::Backlinks,
+            required: false,
+            authorized_models: Some(vec![self.source_model.clone()]),
+            validators: vec![],
+        }
+    }
+
+    fn accepts_for_set(&self, _value: &ObjectValue, _context: &ValidatorContext) -> bool {
+        false
+    }
+
+    fn accepts_model(&self, _model: &Model) -> bool {
+        false
+    }
+
+    fn validate(
+        &self,
+        _value: &ObjectValue,
+        _context: &ValidatorContext,
+    ) -> Result<Option<Vec<ValidationError>>, HitError> {
+        Ok(None)
+    }
+    fn is_vec_reference(&self) -> bool {
+        false
+    }
+    fn is_vec_subobject(&self) -> bool {
+        false
+    }
+}
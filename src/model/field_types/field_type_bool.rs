@@ -1,3 +1,5 @@
+use crate::model::field_types::describe_validators;
+use crate::model::{FieldDescription, FieldKind};
 use crate::{
     errors::ValidationError,
     model::field_types::{check_if_required, run_validators},
@@ -25,6 +27,14 @@ impl ModelField for FieldTypeBool {
     fn get_name(&self) -> String {
         return String::from(&self.name);
     }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::Bool,
+            required: self.required,
+            authorized_models: None,
+            validators: describe_validators(&self.validators),
+        }
+    }
     fn validate(
         &self,
         value: &ObjectValue,
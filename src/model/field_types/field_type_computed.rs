@@ -0,0 +1,159 @@
+use std::rc::Rc;
+
+use crate::model::{FieldDescription, FieldKind};
+use crate::model::{Model, ModelField};
+use crate::object_data::{ObjectValue, Reference};
+use crate::{errors::ValidationError, model::validators::ValidatorContext};
+use crate::{Hit, HitError};
+
+// Receives the id of the object, and the document
+pub type FieldComputation = Rc<dyn Fn(&str, &Hit) -> Result<ObjectValue, HitError>>;
+
+// The values that are read by a computation : the field is computed again when they change
+#[derive(Clone, Debug, PartialEq)]
+pub enum ComputedDependency {
+    // a field of the object
+    Field(String),
+    // the list of subobjects of a subobject (array) field, and a field of each subobject
+    Subobjects {
+        field: String,
+        property: Option<String>,
+    },
+    // the references of a reference (array) field, and a field of each referenced object
+    References {
+        field: String,
+        property: Option<String>,
+    },
+}
+
+impl ComputedDependency {
+    // the field of the object that contains the subobjects or the references
+    pub fn get_field(&self) -> &str {
+        match self {
+            ComputedDependency::Field(field) => field,
+            ComputedDependency::Subobjects { field, .. } => field,
+            ComputedDependency::References { field, .. } => field,
+        }
+    }
+}
+
+// A field whose value is derived from other values, it cannot be set
+pub struct FieldTypeComputed {
+    pub name: String,
+    pub dependencies: Vec<ComputedDependency>,
+    pub compute: FieldComputation,
+}
+
+impl FieldTypeComputed {
+    pub fn new<F>(name: &str, dependencies: Vec<ComputedDependency>, compute: F) -> Self
+    where
+        F: Fn(&str, &Hit) -> Result<ObjectValue, HitError> + 'static,
+    {
+        FieldTypeComputed {
+            name: name.to_string(),
+            dependencies,
+            compute: Rc::new(compute),
+        }
+    }
+
+    // the number of subobjects in `field`
+    pub fn count(name: &str, field: &str) -> Self {
+        let subobjects_field = field.to_string();
+        FieldTypeComputed::new(
+            name,
+            vec![ComputedDependency::Subobjects {
+                field: field.to_string(),
+                property: None,
+            }],
+            move |id, hit| {
+                let count = get_linked_ids(hit.get_value(id, &subobjects_field)).len();
+                Ok(ObjectValue::I32(count as i32))
+            },
+        )
+    }
+
+    // the sum of the numeric `property` of the subobjects in `field`, a float as soon as
+    // one of the values is a float
+    pub fn sum(name: &str, field: &str, property: &str) -> Self {
+        let subobjects_field = field.to_string();
+        let summed_property = property.to_string();
+        FieldTypeComputed::new(
+            name,
+            vec![ComputedDependency::Subobjects {
+                field: field.to_string(),
+                property: Some(property.to_string()),
+            }],
+            move |id, hit| {
+                let mut integer_sum: i32 = 0;
+                let mut float_sum: f32 = 0.0;
+                let mut is_float = false;
+                for child in get_linked_ids(hit.get_value(id, &subobjects_field)).iter() {
+                    match hit.get_value(&child.id, &summed_property) {
+                        Some(ObjectValue::I32(value)) => integer_sum += value,
+                        Some(ObjectValue::F32(value)) => {
+                            float_sum += value;
+                            is_float = true;
+                        }
+                        _ => {}
+                    }
+                }
+                if is_float {
+                    Ok(ObjectValue::F32(float_sum + integer_sum as f32))
+                } else {
+                    Ok(ObjectValue::I32(integer_sum))
+                }
+            },
+        )
+    }
+}
+
+// the objects contained or referenced by a value
+pub fn get_linked_ids(value: Option<ObjectValue>) -> Vec<Reference> {
+    match value {
+        Some(ObjectValue::SubObject(reference)) | Some(ObjectValue::Reference(reference)) => {
+            vec![reference]
+        }
+        Some(ObjectValue::VecSubObjects(references))
+        | Some(ObjectValue::VecReference(references)) => references,
+        _ => vec![],
+    }
+}
+
+impl ModelField for FieldTypeComputed {
+    fn on_kernel_init(&mut self, _model_name: &str) -> Result<(), HitError> {
+        Ok(())
+    }
+    fn get_name(&self) -> String {
+        String::from(&self.name)
+    }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::Computed,
+            required: false,
+            authorized_models: None,
+            validators: vec![],
+        }
+    }
+
+    fn accepts_for_set(&self, _value: &ObjectValue, _context: &ValidatorContext) -> bool {
+        false
+    }
+
+    fn accepts_model(&self, _model: &Model) -> bool {
+        false
+    }
+
+    fn validate(
+        &self,
+        _value: &ObjectValue,
+        _context: &ValidatorContext,
+    ) -> Result<Option<Vec<ValidationError>>, HitError> {
+        Ok(None)
+    }
+    fn is_vec_reference(&self) -> bool {
+        false
+    }
+    fn is_vec_subobject(&self) -> bool {
+        false
+    }
+}
@@ -1,3 +1,5 @@
+use crate::model::field_types::describe_validators;
+use crate::model::{FieldDescription, FieldKind};
 use crate::model::{Model, ModelField};
 use crate::object_data::ObjectValue;
 use crate::{
@@ -10,6 +12,7 @@ use crate::{
 };
 use chrono::{DateTime, Utc};
 
+#[derive(Default)]
 pub struct FieldTypeDate {
     pub required: bool,
     pub name: String,
@@ -26,6 +29,14 @@ impl ModelField for FieldTypeDate {
     fn get_name(&self) -> String {
         return String::from(&self.name);
     }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::Date,
+            required: self.required,
+            authorized_models: None,
+            validators: describe_validators(&self.validators),
+        }
+    }
 
     fn accepts_for_set(&self, value: &ObjectValue, _context: &ValidatorContext) -> bool {
         match value {
@@ -1,3 +1,5 @@
+use crate::model::field_types::describe_validators;
+use crate::model::{FieldDescription, FieldKind};
 use crate::model::validators::{ValidatorContext, Validators};
 use crate::model::{Model, ModelField};
 use crate::object_data::ObjectValue;
@@ -7,6 +9,7 @@ use crate::{
     model::field_types::{check_if_required, run_validators},
 };
 
+#[derive(Default)]
 pub struct FieldTypeFloat {
     pub required: bool,
     pub name: String,
@@ -23,6 +26,14 @@ impl ModelField for FieldTypeFloat {
     fn get_name(&self) -> String {
         return String::from(&self.name);
     }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::Float,
+            required: self.required,
+            authorized_models: None,
+            validators: describe_validators(&self.validators),
+        }
+    }
 
     fn accepts_for_set(&self, value: &ObjectValue, _context: &ValidatorContext) -> bool {
         match value {
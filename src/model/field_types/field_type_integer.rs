@@ -1,3 +1,5 @@
+use crate::model::field_types::describe_validators;
+use crate::model::{FieldDescription, FieldKind};
 use crate::model::{Model, ModelField};
 use crate::object_data::ObjectValue;
 use crate::{
@@ -9,6 +11,7 @@ use crate::{
     HitError,
 };
 
+#[derive(Default)]
 pub struct FieldTypeInteger {
     pub required: bool,
     pub name: String,
@@ -25,6 +28,14 @@ impl ModelField for FieldTypeInteger {
     fn get_name(&self) -> String {
         return String::from(&self.name);
     }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::Integer,
+            required: self.required,
+            authorized_models: None,
+            validators: describe_validators(&self.validators),
+        }
+    }
 
     fn accepts_for_set(&self, value: &ObjectValue, _context: &ValidatorContext) -> bool {
         match value {
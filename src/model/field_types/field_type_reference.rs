@@ -1,3 +1,5 @@
+use crate::model::field_types::describe_validators;
+use crate::model::{FieldDescription, FieldKind};
 use crate::model::{Model, ModelField};
 use crate::object_data::{ObjectValue, Reference};
 use crate::{
@@ -41,6 +43,14 @@ impl ModelField for FieldTypeReference {
     fn get_name(&self) -> String {
         return String::from(&self.name);
     }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::Reference,
+            required: self.required,
+            authorized_models: Some(self.authorized_models.clone()),
+            validators: describe_validators(&self.validators),
+        }
+    }
     fn validate(&self, value: &ObjectValue, context: &ValidatorContext) -> ReturnHitError {
         match value {
             ObjectValue::Null => check_if_required(self.required),
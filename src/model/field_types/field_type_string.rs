@@ -1,3 +1,5 @@
+use crate::model::field_types::describe_validators;
+use crate::model::{FieldDescription, FieldKind};
 use crate::{
     errors::ValidationError,
     model::field_types::{check_if_required, run_validators},
@@ -27,6 +29,14 @@ impl ModelField for FieldTypeString {
     fn get_name(&self) -> String {
         return String::from(&self.name);
     }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::String,
+            required: self.required,
+            authorized_models: None,
+            validators: describe_validators(&self.validators),
+        }
+    }
     fn accepts_for_set(&self, value: &ObjectValue, _context: &ValidatorContext) -> bool {
         match value {
             ObjectValue::Null => !self.required,
@@ -1,3 +1,5 @@
+use crate::model::field_types::describe_validators;
+use crate::model::{FieldDescription, FieldKind};
 use crate::{
     errors::ValidationError,
     model::field_types::{check_if_required, run_validators},
@@ -27,9 +29,18 @@ impl ModelField for FieldTypeStringVec {
     fn get_name(&self) -> String {
         return String::from(&self.name);
     }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::StringArray,
+            required: self.required,
+            authorized_models: None,
+            validators: describe_validators(&self.validators),
+        }
+    }
     fn accepts_for_set(&self, value: &ObjectValue, _context: &ValidatorContext) -> bool {
         match value {
-            ObjectValue::String(_) => true,
+            ObjectValue::Null => !self.required,
+            ObjectValue::VecString(_) => true,
             _ => false,
         }
     }
@@ -45,9 +56,11 @@ impl ModelField for FieldTypeStringVec {
     ) -> Result<Option<Vec<ValidationError>>, HitError> {
         match value {
             ObjectValue::Null => check_if_required(self.required),
-            ObjectValue::String(value) => {
+            ObjectValue::VecString(values) => {
                 let mut errors: Vec<ValidationError> = vec![];
-                run_validators(&self.validators, value, &mut errors, context)?;
+                for value in values.iter() {
+                    run_validators(&self.validators, value, &mut errors, context)?;
+                }
 
                 if errors.len() > 0 {
                     return Ok(Some(errors));
@@ -1,3 +1,5 @@
+use crate::model::field_types::describe_validators;
+use crate::model::{FieldDescription, FieldKind};
 use crate::{
     errors::ValidationError,
     model::field_types::{
@@ -11,6 +13,19 @@ use crate::model::validators::{ValidatorContext, Validators};
 use crate::model::{Model, ModelField};
 use crate::object_data::{ObjectValue, Reference};
 
+// What happens when an object is inserted or moved into a field that already contains one,
+// set on the model with `Model::set_slot_policy`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SlotPolicy {
+    // the insert or the move fails
+    #[default]
+    Error,
+    // the object of the field is removed
+    Replace,
+    // the object of the field is moved to another subobject field of the same object
+    MoveTo(String),
+}
+
 #[derive(Default)]
 pub struct FieldTypeSubobject {
     pub required: bool,
@@ -41,10 +56,18 @@ impl ModelField for FieldTypeSubobject {
     fn get_name(&self) -> String {
         return String::from(&self.name);
     }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::Subobject,
+            required: self.required,
+            authorized_models: Some(self.authorized_models.clone()),
+            validators: describe_validators(&self.validators),
+        }
+    }
     fn validate(&self, value: &ObjectValue, context: &ValidatorContext) -> ReturnHitError {
         match value {
             ObjectValue::Null => check_if_required(self.required),
-            ObjectValue::Reference(value) => {
+            ObjectValue::SubObject(value) => {
                 let mut errors: Vec<ValidationError> = vec![];
                 //verify validity of reference
                 let entry = check_reference_exists(value, context)?;
@@ -1,3 +1,5 @@
+use crate::model::field_types::describe_validators;
+use crate::model::{FieldDescription, FieldKind};
 use crate::{
     errors::ValidationError,
     model::field_types::{
@@ -53,6 +55,14 @@ impl ModelField for FieldTypeSubobjectArray {
     fn get_name(&self) -> String {
         return String::from(&self.name);
     }
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::SubobjectArray,
+            required: false,
+            authorized_models: Some(self.authorized_models.clone()),
+            validators: describe_validators(&self.validators),
+        }
+    }
     fn validate(&self, value: &ObjectValue, context: &ValidatorContext) -> ReturnHitError {
         match value {
             ObjectValue::Null => Ok(None),
@@ -1,4 +1,6 @@
+mod field_type_backlinks;
 mod field_type_bool;
+mod field_type_computed;
 mod field_type_date;
 mod field_type_float;
 mod field_type_integer;
@@ -16,9 +18,13 @@ use crate::{errors::ValidationError, hit_mod::HitEntry};
 use crate::{
     errors::ValidationErrorLevel,
     errors::VALIDATION_ERROR_REQUIRED,
-    model::validators::{ValidatorContext, Validators},
+    model::validators::{ValidatorContext, ValidatorDescription, Validators},
 };
+pub use field_type_backlinks::FieldTypeBacklinks;
 pub use field_type_bool::FieldTypeBool;
+pub use field_type_computed::{
+    get_linked_ids, ComputedDependency, FieldComputation, FieldTypeComputed,
+};
 pub use field_type_date::FieldTypeDate;
 pub use field_type_float::FieldTypeFloat;
 pub use field_type_integer::FieldTypeInteger;
@@ -26,7 +32,7 @@ pub use field_type_reference::FieldTypeReference;
 pub use field_type_reference_array::FieldTypeReferenceArray;
 pub use field_type_string::FieldTypeString;
 pub use field_type_string_vec::FieldTypeStringVec;
-pub use field_type_subobject::FieldTypeSubobject;
+pub use field_type_subobject::{FieldTypeSubobject, SlotPolicy};
 pub use field_type_subobject_array::FieldTypeSubobjectArray;
 
 fn check_if_required(required: bool) -> Result<Option<Vec<ValidationError>>, HitError> {
@@ -58,16 +64,20 @@ fn check_reference_exists<'a>(
 
 fn check_reference_is_authorized(authorized_models: &Vec<String>, model: &Model) -> bool {
     for authorized_model in authorized_models {
-        if model.get_name() == authorized_model {
-            return true;
-        }
-        if model.implements_interface(authorized_model) {
+        if model.is_a(authorized_model) {
             return true;
         }
     }
     return false;
 }
 
+fn describe_validators<T>(validators: &Validators<T>) -> Vec<ValidatorDescription> {
+    validators
+        .iter()
+        .map(|validator| validator.describe())
+        .collect()
+}
+
 pub fn run_validators<T>(
     validators: &Validators<T>,
     value: &T,
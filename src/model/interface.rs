@@ -0,0 +1,27 @@
+use linked_hash_map::LinkedHashMap;
+
+use crate::model::{Fields, ModelFieldRef};
+
+// The fields that the models implementing an interface must provide,
+// with the same names and kinds
+pub struct Interface {
+    name: String,
+    pub fields: Fields,
+}
+
+impl Interface {
+    pub fn new(name: String) -> Interface {
+        Interface {
+            name,
+            fields: LinkedHashMap::new(),
+        }
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<&ModelFieldRef> {
+        self.fields.get(name)
+    }
+}
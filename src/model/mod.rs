@@ -2,9 +2,13 @@
 #[cfg(not(tarpaulin_include))]
 pub mod field_types;
 
+mod field_default;
+mod interface;
 mod model;
 mod model_field;
 pub mod validators;
 pub use crate::hit_mod::helpers;
+pub use field_default::{DefaultInitializer, FieldDefault};
+pub use interface::Interface;
 pub use model::Model;
-pub use model_field::{Fields, ModelField, ModelFieldRef};
+pub use model_field::{FieldDescription, FieldKind, Fields, ModelField, ModelFieldRef};
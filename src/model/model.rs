@@ -1,12 +1,25 @@
+use std::collections::HashMap;
+
 use linked_hash_map::LinkedHashMap;
 
-use crate::model::{Fields, ModelFieldRef};
+use crate::model::field_types::SlotPolicy;
+use crate::model::{FieldDefault, Fields, ModelFieldRef};
 
 pub struct Model {
     name: String,
     label: String,
     pub fields: Fields,
     pub interfaces: Vec<String>,
+    // the base model, whose fields are inherited when the kernel is built
+    pub extends: Option<String>,
+    // an abstract model only declares fields for the models extending it
+    pub is_abstract: bool,
+    // the base models, from the most generic one, set by `KernelBuilder`
+    pub(crate) ancestors: Vec<String>,
+    // the values set when an object is created without these fields
+    defaults: HashMap<String, FieldDefault>,
+    // what happens to the object of an occupied single subobject field
+    slot_policies: HashMap<String, SlotPolicy>,
 }
 
 impl Model {
@@ -16,6 +29,11 @@ impl Model {
             label: label,
             fields: LinkedHashMap::new(),
             interfaces: vec![],
+            extends: None,
+            is_abstract: false,
+            ancestors: vec![],
+            defaults: HashMap::new(),
+            slot_policies: HashMap::new(),
         };
     }
 
@@ -34,6 +52,37 @@ impl Model {
         return self.interfaces.contains(interface);
     }
 
+    pub fn get_ancestors(&self) -> &Vec<String> {
+        &self.ancestors
+    }
+
+    pub fn extends_model(&self, name: &str) -> bool {
+        self.ancestors.iter().any(|ancestor| ancestor == name)
+    }
+
+    // true if the name is the model, one of its base models or one of its interfaces
+    pub fn is_a(&self, name: &str) -> bool {
+        self.name == name
+            || self.extends_model(name)
+            || self.interfaces.iter().any(|interface| interface == name)
+    }
+
+    pub fn set_default(&mut self, field: &str, default: FieldDefault) {
+        self.defaults.insert(field.to_string(), default);
+    }
+
+    pub fn get_default(&self, field: &str) -> Option<FieldDefault> {
+        self.defaults.get(field).cloned()
+    }
+
+    pub fn set_slot_policy(&mut self, field: &str, policy: SlotPolicy) {
+        self.slot_policies.insert(field.to_string(), policy);
+    }
+
+    pub fn get_slot_policy(&self, field: &str) -> Option<SlotPolicy> {
+        self.slot_policies.get(field).cloned()
+    }
+
     pub(in crate) fn get_fields(&self) -> &Fields {
         &self.fields
     }
@@ -1,14 +1,78 @@
 use crate::model::Model;
 use crate::object_data::ObjectValue;
 use crate::HitError;
-use crate::{errors::ValidationError, model::validators::ValidatorContext};
+use crate::{
+    errors::ValidationError,
+    model::validators::{ValidatorContext, ValidatorDescription},
+};
 use linked_hash_map::LinkedHashMap;
 use mopa;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+// The kind of the standard fields, the custom fields give the name of their kind
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldKind {
+    Bool,
+    Date,
+    Integer,
+    Float,
+    String,
+    StringArray,
+    Reference,
+    ReferenceArray,
+    Subobject,
+    SubobjectArray,
+    Computed,
+    Backlinks,
+    Custom(String),
+}
+
+impl FieldKind {
+    // the name used in the schema documents
+    pub fn get_name(&self) -> &str {
+        match self {
+            FieldKind::Bool => "bool",
+            FieldKind::Date => "date",
+            FieldKind::Integer => "integer",
+            FieldKind::Float => "float",
+            FieldKind::String => "string",
+            FieldKind::StringArray => "string_array",
+            FieldKind::Reference => "reference",
+            FieldKind::ReferenceArray => "reference_array",
+            FieldKind::Subobject => "subobject",
+            FieldKind::SubobjectArray => "subobject_array",
+            FieldKind::Computed => "computed",
+            FieldKind::Backlinks => "backlinks",
+            FieldKind::Custom(name) => name,
+        }
+    }
+}
+
+// The configuration of a field
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldDescription {
+    pub kind: FieldKind,
+    pub required: bool,
+    // only for the fields containing references or subobjects
+    pub authorized_models: Option<Vec<String>>,
+    pub validators: Vec<ValidatorDescription>,
+}
+
 pub trait ModelField: mopa::Any {
     fn get_name(&self) -> String;
+    // the fields defined outside of this crate are described as custom fields
+    fn describe(&self) -> FieldDescription {
+        FieldDescription {
+            kind: FieldKind::Custom(String::from("custom")),
+            required: false,
+            authorized_models: None,
+            validators: vec![],
+        }
+    }
+    fn get_kind(&self) -> FieldKind {
+        self.describe().kind
+    }
     fn validate(
         &self,
         value: &ObjectValue,
@@ -25,3 +89,47 @@ mopafy!(ModelField);
 pub type ModelFieldRef = Rc<RefCell<dyn ModelField>>;
 
 pub type Fields = LinkedHashMap<String, ModelFieldRef>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::validators::ValidatorContext;
+
+    struct FieldTypeColor {}
+
+    impl ModelField for FieldTypeColor {
+        fn get_name(&self) -> String {
+            String::from("color")
+        }
+        fn validate(
+            &self,
+            _value: &ObjectValue,
+            _context: &ValidatorContext,
+        ) -> Result<Option<Vec<ValidationError>>, HitError> {
+            Ok(None)
+        }
+        fn accepts_for_set(&self, value: &ObjectValue, _context: &ValidatorContext) -> bool {
+            matches!(value, ObjectValue::String(_))
+        }
+        fn accepts_model(&self, _model: &Model) -> bool {
+            false
+        }
+        fn is_vec_reference(&self) -> bool {
+            false
+        }
+        fn is_vec_subobject(&self) -> bool {
+            false
+        }
+        fn on_kernel_init(&mut self, _model_name: &str) -> Result<(), HitError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn it_should_describe_a_field_defined_outside_of_the_crate() {
+        let field = FieldTypeColor {};
+        assert_eq!(field.get_kind(), FieldKind::Custom(String::from("custom")));
+        assert_eq!(field.describe().kind.get_name(), "custom");
+        assert!(!field.describe().required);
+    }
+}
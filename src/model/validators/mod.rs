@@ -1,5 +1,6 @@
 use crate::{errors::ValidationError, Hit};
 use crate::{errors::ValidationErrorLevel, HitError};
+use serde_json::{Map, Value};
 use std::rc::Rc;
 
 pub type Validators<T> = Vec<Box<dyn Validator<T>>>;
@@ -12,6 +13,20 @@ pub trait Validator<T> {
     ) -> Result<Option<Vec<ValidationError>>, HitError>;
 
     fn on_kernel_init(&mut self, field_name: &str, model_name: &str) -> Result<(), HitError>;
+
+    fn describe(&self) -> ValidatorDescription {
+        ValidatorDescription {
+            name: String::from("custom"),
+            arguments: Map::new(),
+        }
+    }
+}
+
+// The name of a validator and its configuration
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidatorDescription {
+    pub name: String,
+    pub arguments: Map<String, Value>,
 }
 
 pub struct MaxLength {
@@ -46,4 +61,13 @@ impl Validator<String> for MaxLength {
     fn on_kernel_init(&mut self, _field_name: &str, _model_namee: &str) -> Result<(), HitError> {
         Ok(())
     }
+
+    fn describe(&self) -> ValidatorDescription {
+        let mut arguments = Map::new();
+        arguments.insert(String::from("length"), Value::from(self.length));
+        ValidatorDescription {
+            name: String::from("max_length"),
+            arguments,
+        }
+    }
 }
@@ -1,4 +1,5 @@
 use chrono::DateTime;
+use chrono::SecondsFormat;
 use chrono::NaiveDateTime;
 use chrono::Utc;
 use linked_hash_map::LinkedHashMap;
@@ -16,6 +17,9 @@ pub struct Reference {
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct DateTimeUtc {
     datetime: i64,
+    // the values serialized before the sub-second precision have no nanoseconds
+    #[serde(default)]
+    nanoseconds: u32,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
@@ -38,15 +42,40 @@ impl DateTimeUtc {
     pub fn new(datetime: DateTime<Utc>) -> DateTimeUtc {
         return DateTimeUtc {
             datetime: datetime.timestamp(),
+            nanoseconds: datetime.timestamp_subsec_nanos(),
         };
     }
+    pub fn from_timestamp(timestamp: i64) -> DateTimeUtc {
+        DateTimeUtc::from_timestamp_nanos(timestamp, 0)
+    }
+    pub fn from_timestamp_nanos(timestamp: i64, nanoseconds: u32) -> DateTimeUtc {
+        DateTimeUtc {
+            datetime: timestamp,
+            nanoseconds,
+        }
+    }
+    pub fn get_timestamp(&self) -> i64 {
+        self.datetime
+    }
+    pub fn get_nanoseconds(&self) -> u32 {
+        self.nanoseconds
+    }
     pub fn get_date(&self) -> DateTime<Utc> {
-        return DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(self.datetime, 0), Utc);
+        return DateTime::<Utc>::from_utc(
+            NaiveDateTime::from_timestamp(self.datetime, self.nanoseconds),
+            Utc,
+        );
     }
 
+    // RFC 2822 has no sub-second precision
     pub fn get_date_as_string(&self) -> String {
         return self.get_date().to_rfc2822();
     }
+
+    pub fn get_date_as_rfc3339(&self) -> String {
+        self.get_date()
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
 }
 
 impl Display for DateTimeUtc {
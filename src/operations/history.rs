@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+
+use crate::operations::Operation;
+
+// Undo and redo stacks. Each step is a list of operations that is undone as a whole.
+#[derive(Clone)]
+pub(crate) struct History {
+    max_depth: usize,
+    undo_stack: VecDeque<Vec<Operation>>,
+    redo_stack: Vec<Vec<Operation>>,
+    group: Vec<Operation>,
+    group_depth: usize,
+    // disabled while an undo or a redo is applied
+    paused: bool,
+}
+
+impl History {
+    pub fn new(max_depth: usize) -> Self {
+        History {
+            max_depth,
+            undo_stack: VecDeque::new(),
+            redo_stack: vec![],
+            group: vec![],
+            group_depth: 0,
+            paused: false,
+        }
+    }
+
+    pub fn record(&mut self, operations: Vec<Operation>) {
+        if self.paused || operations.is_empty() {
+            return;
+        }
+        self.redo_stack.clear();
+        if self.group_depth > 0 {
+            self.group.extend(operations);
+        } else {
+            self.push_undo(operations);
+        }
+    }
+
+    pub fn begin_group(&mut self) {
+        self.group_depth += 1;
+    }
+
+    pub fn end_group(&mut self) {
+        if self.group_depth == 0 {
+            return;
+        }
+        self.group_depth -= 1;
+        if self.group_depth == 0 {
+            self.close_group();
+        }
+    }
+
+    // closes the open groups, so that the operations recorded so far form one step
+    pub fn close_group(&mut self) {
+        self.group_depth = 0;
+        if !self.group.is_empty() {
+            let group = std::mem::take(&mut self.group);
+            self.push_undo(group);
+        }
+    }
+
+    pub fn push_undo(&mut self, step: Vec<Operation>) {
+        self.undo_stack.push_back(step);
+        while self.undo_stack.len() > self.max_depth {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Vec<Operation>> {
+        self.undo_stack.pop_back()
+    }
+
+    pub fn push_redo(&mut self, step: Vec<Operation>) {
+        self.redo_stack.push(step);
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Vec<Operation>> {
+        self.redo_stack.pop()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty() || !self.group.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.group.clear();
+        self.group_depth = 0;
+    }
+}
@@ -0,0 +1,8 @@
+mod history;
+mod operation;
+mod recorder;
+
+pub(crate) use history::History;
+pub use operation::{Operation, OperationRecord};
+pub(crate) use operation::{get_next_id, restore_entries_operations};
+pub(crate) use recorder::OperationRecorder;
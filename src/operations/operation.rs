@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+use crate::import::AddedEntry;
+use crate::index::IndexEntryProperty;
+use crate::object_data::{Id, ObjectValue, ObjectValues, Reference};
+
+// A mutation made through the Hit API, with enough information to be inverted
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Operation {
+    Set {
+        id: Id,
+        property: String,
+        value: ObjectValue,
+        old_value: ObjectValue,
+    },
+    Insert {
+        model: String,
+        id: Id,
+        values: ObjectValues,
+        parent: IndexEntryProperty,
+        before_id: Option<Id>,
+    },
+    // entries contains the removed object and its descendants, parents first
+    Remove {
+        id: Id,
+        parent: IndexEntryProperty,
+        before_id: Option<Id>,
+        entries: Vec<AddedEntry>,
+    },
+    Move {
+        id: Id,
+        target: IndexEntryProperty,
+        before_id: Option<Id>,
+        original_parent: IndexEntryProperty,
+        original_before_id: Option<Id>,
+    },
+    // entries contains the created copies, so that the copy can be replayed with the same ids
+    Copy {
+        id: Id,
+        new_id: Id,
+        target: IndexEntryProperty,
+        before_id: Option<Id>,
+        entries: Vec<AddedEntry>,
+    },
+    InsertReference {
+        id: Id,
+        target: IndexEntryProperty,
+        before_id: Option<Id>,
+    },
+    // before_id is the reference that followed the removed one
+    RemoveReference {
+        id: Id,
+        target: IndexEntryProperty,
+        before_id: Option<Id>,
+    },
+    MoveReference {
+        id: Id,
+        target: IndexEntryProperty,
+        before_id: Option<Id>,
+        original_before_id: Option<Id>,
+    },
+}
+
+// An operation as emitted to the subscribers, numbered in the order it was applied
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OperationRecord {
+    pub sequence: u64,
+    pub operation: Operation,
+}
+
+impl Operation {
+    // returns the operations that cancel this one
+    pub fn invert(&self) -> Vec<Operation> {
+        match self {
+            Operation::Set {
+                id,
+                property,
+                value,
+                old_value,
+            } => vec![Operation::Set {
+                id: id.clone(),
+                property: property.clone(),
+                value: old_value.clone(),
+                old_value: value.clone(),
+            }],
+            Operation::Insert {
+                id,
+                parent,
+                before_id,
+                ..
+            } => vec![Operation::Remove {
+                id: id.clone(),
+                parent: parent.clone(),
+                before_id: before_id.clone(),
+                entries: vec![],
+            }],
+            Operation::Remove {
+                before_id, entries, ..
+            } => restore_entries_operations(entries, before_id),
+            Operation::Move {
+                id,
+                target,
+                before_id,
+                original_parent,
+                original_before_id,
+            } => vec![Operation::Move {
+                id: id.clone(),
+                target: original_parent.clone(),
+                before_id: original_before_id.clone(),
+                original_parent: target.clone(),
+                original_before_id: before_id.clone(),
+            }],
+            Operation::Copy {
+                new_id,
+                target,
+                before_id,
+                ..
+            } => vec![Operation::Remove {
+                id: new_id.clone(),
+                parent: target.clone(),
+                before_id: before_id.clone(),
+                entries: vec![],
+            }],
+            Operation::InsertReference {
+                id,
+                target,
+                before_id,
+            } => vec![Operation::RemoveReference {
+                id: id.clone(),
+                target: target.clone(),
+                before_id: before_id.clone(),
+            }],
+            Operation::RemoveReference {
+                id,
+                target,
+                before_id,
+            } => vec![Operation::InsertReference {
+                id: id.clone(),
+                target: target.clone(),
+                before_id: before_id.clone(),
+            }],
+            Operation::MoveReference {
+                id,
+                target,
+                before_id,
+                original_before_id,
+            } => vec![Operation::MoveReference {
+                id: id.clone(),
+                target: target.clone(),
+                before_id: original_before_id.clone(),
+                original_before_id: before_id.clone(),
+            }],
+        }
+    }
+}
+
+// Recreates a subtree : the objects are inserted first (parents before children),
+// then the references are set, as they can point to any object of the subtree
+pub(crate) fn restore_entries_operations(
+    entries: &[AddedEntry],
+    before_id: &Option<Id>,
+) -> Vec<Operation> {
+    let mut operations = vec![];
+    for (position, entry) in entries.iter().enumerate() {
+        let mut values = ObjectValues::new();
+        for (property, value) in entry.data.iter() {
+            match value {
+                ObjectValue::Reference(_)
+                | ObjectValue::VecReference(_)
+                | ObjectValue::SubObject(_)
+                | ObjectValue::VecSubObjects(_) => {}
+                _ => {
+                    values.insert(property.clone(), value.clone());
+                }
+            }
+        }
+        operations.push(Operation::Insert {
+            model: entry.model.clone(),
+            id: entry.id.clone(),
+            values,
+            parent: entry.parent.clone().unwrap_or_default(),
+            before_id: if position == 0 {
+                before_id.clone()
+            } else {
+                None
+            },
+        });
+    }
+    for entry in entries.iter() {
+        for (property, value) in entry.data.iter() {
+            match value {
+                ObjectValue::Reference(reference) => operations.push(Operation::Set {
+                    id: entry.id.clone(),
+                    property: property.clone(),
+                    value: ObjectValue::Reference(reference.clone()),
+                    old_value: ObjectValue::Null,
+                }),
+                ObjectValue::VecReference(references) => {
+                    for reference in references.iter() {
+                        operations.push(Operation::InsertReference {
+                            id: reference.id.clone(),
+                            target: IndexEntryProperty {
+                                id: entry.id.clone(),
+                                property: property.clone(),
+                            },
+                            before_id: None,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    operations
+}
+
+// id of the element following `id` in a list of references or subobjects
+pub(crate) fn get_next_id(references: &[Reference], id: &str) -> Option<Id> {
+    let position = references.iter().position(|r| r.id == id)?;
+    references.get(position + 1).map(|r| r.id.clone())
+}
@@ -0,0 +1,70 @@
+use crate::events::{FieldListenerRef, Listeners};
+use crate::operations::{History, Operation, OperationRecord};
+use crate::HitError;
+
+const OPERATIONS_KEY: &str = "operations";
+
+#[derive(Clone)]
+pub(crate) struct OperationRecorder {
+    // operations emitted while this is positive are part of a composite operation
+    // (ex: the inserts of a copy) and are not recorded on their own
+    suppressed: usize,
+    sequence: u64,
+    listeners: Listeners<OperationRecord>,
+    pub history: Option<History>,
+}
+
+impl OperationRecorder {
+    pub fn new() -> Self {
+        OperationRecorder {
+            suppressed: 0,
+            sequence: 0,
+            listeners: Listeners::new(),
+            history: None,
+        }
+    }
+
+    pub fn is_suppressed(&self) -> bool {
+        self.suppressed > 0
+    }
+
+    pub fn suppress(&mut self) {
+        self.suppressed += 1;
+    }
+
+    pub fn resume(&mut self) {
+        self.suppressed -= 1;
+    }
+
+    pub fn get_sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn set_sequence(&mut self, sequence: u64) {
+        self.sequence = sequence;
+    }
+
+    pub fn subscribe(&mut self, listener: FieldListenerRef<OperationRecord>) {
+        self.listeners.insert(OPERATIONS_KEY, listener);
+    }
+
+    pub fn unsubscribe(&mut self, listener_id: &str) -> Result<(), HitError> {
+        self.listeners.remove(OPERATIONS_KEY, listener_id)
+    }
+
+    pub fn record(&mut self, operations: Vec<Operation>) {
+        for operation in operations.iter() {
+            self.sequence += 1;
+            self.listeners.dispatch_value(
+                OPERATIONS_KEY,
+                &OperationRecord {
+                    sequence: self.sequence,
+                    operation: operation.clone(),
+                },
+            );
+        }
+        if let Some(history) = &mut self.history {
+            history.record(operations);
+        }
+    }
+}
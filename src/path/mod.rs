@@ -0,0 +1,307 @@
+use crate::field_types::get_linked_ids;
+use crate::model::FieldKind;
+use crate::object_data::{Id, ObjectValue};
+use crate::{Hit, HitError, IndexEntryProperty};
+
+// What a path designates in a document
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathTarget {
+    Object(Id),
+    Field(IndexEntryProperty),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Selector {
+    // `[2]` : the position of the subobject, starting at 0
+    Position(usize),
+    // `[name="a.txt"]` : the first subobject whose field has the value
+    Value(String, ObjectValue),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum PathSegment {
+    Property(String, Option<Selector>),
+    // `#id` : a subobject of the previous field, by id
+    Id(Id),
+}
+
+fn is_name_character(character: char) -> bool {
+    !matches!(character, '/' | '[' | ']' | '#' | '=' | '"')
+}
+
+struct PathParser<'a> {
+    path: &'a str,
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl<'a> PathParser<'a> {
+    fn error(&self, reason: &str) -> HitError {
+        HitError::InvalidPath(
+            self.path.to_string(),
+            format!("{} at position {}", reason, self.position),
+        )
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn expect(&mut self, character: char) -> Result<(), HitError> {
+        if self.peek() != Some(character) {
+            return Err(self.error(&format!("expected `{}`", character)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn read_name(&mut self) -> Result<String, HitError> {
+        let start = self.position;
+        while self.peek().is_some_and(is_name_character) {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(self.error("expected a name"));
+        }
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+
+    fn read_string(&mut self) -> Result<String, HitError> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.position += 1;
+                    return Ok(value);
+                }
+                Some('\\') if self.position + 1 < self.chars.len() => {
+                    value.push(self.chars[self.position + 1]);
+                    self.position += 2;
+                }
+                Some(character) => {
+                    value.push(character);
+                    self.position += 1;
+                }
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    // a string, a number or a boolean
+    fn read_value(&mut self) -> Result<ObjectValue, HitError> {
+        if self.peek() == Some('"') {
+            return Ok(ObjectValue::String(self.read_string()?));
+        }
+        let text = self.read_name()?;
+        if let Ok(value) = text.parse::<i32>() {
+            return Ok(ObjectValue::I32(value));
+        }
+        if let Ok(value) = text.parse::<f32>() {
+            return Ok(ObjectValue::F32(value));
+        }
+        match text.as_str() {
+            "true" => Ok(ObjectValue::Bool(true)),
+            "false" => Ok(ObjectValue::Bool(false)),
+            _ => Err(self.error("expected a string, a number or a boolean")),
+        }
+    }
+
+    fn read_selector(&mut self) -> Result<Selector, HitError> {
+        self.expect('[')?;
+        let name = self.read_name()?;
+        let selector = if self.peek() == Some('=') {
+            self.position += 1;
+            Selector::Value(name, self.read_value()?)
+        } else {
+            let position = name
+                .parse::<usize>()
+                .map_err(|_| self.error("expected a position or a value"))?;
+            Selector::Position(position)
+        };
+        self.expect(']')?;
+        Ok(selector)
+    }
+
+    fn read_segment(&mut self) -> Result<PathSegment, HitError> {
+        self.expect('/')?;
+        if self.peek() == Some('#') {
+            self.position += 1;
+            return Ok(PathSegment::Id(self.read_name()?));
+        }
+        let property = self.read_name()?;
+        let selector = match self.peek() {
+            Some('[') => Some(self.read_selector()?),
+            _ => None,
+        };
+        Ok(PathSegment::Property(property, selector))
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<PathSegment>, HitError> {
+    let mut parser = PathParser {
+        path,
+        chars: path.chars().collect(),
+        position: 0,
+    };
+    if path == "/" {
+        return Ok(vec![]);
+    }
+    let mut segments = vec![parser.read_segment()?];
+    while parser.peek().is_some() {
+        segments.push(parser.read_segment()?);
+    }
+    Ok(segments)
+}
+
+fn is_equal(value: &ObjectValue, expected: &ObjectValue) -> bool {
+    match (value, expected) {
+        (ObjectValue::F32(value), ObjectValue::I32(expected)) => *value == *expected as f32,
+        (ObjectValue::Reference(reference), ObjectValue::String(id)) => reference.id == *id,
+        _ => value == expected,
+    }
+}
+
+fn select_subobject(hit: &Hit, field: &IndexEntryProperty, selector: &Selector) -> Option<Id> {
+    let children = get_linked_ids(hit.get_value(&field.id, &field.property));
+    let child = match selector {
+        Selector::Position(position) => children.get(*position),
+        Selector::Value(property, expected) => children.iter().find(|child| {
+            hit.get_value(&child.id, property)
+                .is_some_and(|value| is_equal(&value, expected))
+        }),
+    };
+    child.map(|child| child.id.clone())
+}
+
+// Reads a path such as `/sub_items[2]/children[name="a.txt"]/size` or `/sub_items/#id3`
+pub(crate) fn resolve_path(hit: &Hit, path: &str) -> Result<PathTarget, HitError> {
+    let not_found = || HitError::PathNotFound(path.to_string());
+    let mut target = PathTarget::Object(hit.get_main_object_id().clone());
+    for segment in parse_path(path)?.into_iter() {
+        target = match (target, segment) {
+            (PathTarget::Object(id), PathSegment::Property(property, selector)) => {
+                let model = hit.get_model(&id).ok_or_else(not_found)?;
+                let kind = model
+                    .get_field(&property)
+                    .ok_or_else(not_found)?
+                    .borrow()
+                    .get_kind();
+                let field = IndexEntryProperty { id, property };
+                match (selector, kind) {
+                    (Some(selector), FieldKind::SubobjectArray) => PathTarget::Object(
+                        select_subobject(hit, &field, &selector).ok_or_else(not_found)?,
+                    ),
+                    (Some(_), _) => {
+                        return Err(HitError::InvalidPath(
+                            path.to_string(),
+                            format!("`{}` is not a subobject array", field.property),
+                        ))
+                    }
+                    // a single subobject field designates its object
+                    (None, FieldKind::Subobject) => {
+                        match hit.get_value(&field.id, &field.property) {
+                            Some(ObjectValue::SubObject(child)) => PathTarget::Object(child.id),
+                            _ => return Err(not_found()),
+                        }
+                    }
+                    (None, _) => PathTarget::Field(field),
+                }
+            }
+            (PathTarget::Field(field), PathSegment::Id(id)) => match hit.get_parent(&id) {
+                Some(parent) if parent == field => PathTarget::Object(id),
+                _ => return Err(not_found()),
+            },
+            (PathTarget::Object(_), PathSegment::Id(_)) => {
+                return Err(HitError::InvalidPath(
+                    path.to_string(),
+                    "an id must follow a subobject field".into(),
+                ))
+            }
+            (PathTarget::Field(field), PathSegment::Property(..)) => {
+                return Err(HitError::InvalidPath(
+                    path.to_string(),
+                    format!("`{}` does not contain objects", field.property),
+                ))
+            }
+        };
+    }
+    Ok(target)
+}
+
+// the path of an object, with the positions of the subobjects in their array
+pub(crate) fn get_object_path(hit: &Hit, id: &str) -> Result<String, HitError> {
+    if !hit.contains_key(id) {
+        return Err(HitError::IDNotFound(id.to_string(), "path_of".into()));
+    }
+    let mut segments = vec![];
+    let mut current = id.to_string();
+    while let Some(parent) = hit.get_parent(&current) {
+        segments.push(match hit.get_parent_index(&current) {
+            Some(position) => format!("{}[{}]", parent.property, position),
+            None => parent.property.clone(),
+        });
+        current = parent.id;
+    }
+    segments.reverse();
+    Ok(format!("/{}", segments.join("/")))
+}
+
+pub(crate) fn get_field_path(hit: &Hit, id: &str, property: &str) -> Result<String, HitError> {
+    let object_path = get_object_path(hit, id)?;
+    Ok(format!(
+        "{}/{}",
+        object_path.trim_end_matches('/'),
+        property
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_path, PathSegment, Selector};
+    use crate::{HitError, ObjectValue};
+
+    #[test]
+    fn it_should_parse_a_path() {
+        assert_eq!(parse_path("/"), Ok(vec![]));
+        assert_eq!(
+            parse_path(r#"/sub_items[2]/children[name="a\"/b"]/size"#),
+            Ok(vec![
+                PathSegment::Property("sub_items".into(), Some(Selector::Position(2))),
+                PathSegment::Property(
+                    "children".into(),
+                    Some(Selector::Value(
+                        "name".into(),
+                        ObjectValue::String("a\"/b".into())
+                    ))
+                ),
+                PathSegment::Property("size".into(), None),
+            ])
+        );
+        assert_eq!(
+            parse_path("/sub_items/#id3/children[size=-2]"),
+            Ok(vec![
+                PathSegment::Property("sub_items".into(), None),
+                PathSegment::Id("id3".into()),
+                PathSegment::Property(
+                    "children".into(),
+                    Some(Selector::Value("size".into(), ObjectValue::I32(-2)))
+                ),
+            ])
+        );
+        assert_eq!(
+            parse_path("sub_items"),
+            Err(HitError::InvalidPath(
+                "sub_items".into(),
+                "expected `/` at position 0".into()
+            ))
+        );
+        assert_eq!(
+            parse_path("/sub_items[a]"),
+            Err(HitError::InvalidPath(
+                "/sub_items[a]".into(),
+                "expected a position or a value at position 12".into()
+            ))
+        );
+    }
+}
@@ -2,7 +2,9 @@ mod model_type_indexer;
 mod plugin;
 
 pub use model_type_indexer::ModelTypeIndexer;
-pub use plugin::{AfterImportPlugin, DeletePlugin, InitEntryPlugin, Plugin, ReferencePlugin};
+pub use plugin::{
+    AfterImportPlugin, DeletePlugin, InitEntryPlugin, Plugin, ReferencePlugin, TransactionPlugin,
+};
 use std::cell::RefCell;
 use std::clone::Clone;
 use std::rc::Rc;
@@ -14,6 +16,7 @@ pub struct Plugins {
     pub plugins: Vec<Rc<RefCell<dyn Plugin>>>,
     pub delete_plugins: Vec<Rc<RefCell<dyn DeletePlugin>>>,
     pub reference_plugins: Vec<Rc<RefCell<dyn ReferencePlugin>>>,
+    pub transaction_plugins: Vec<Rc<RefCell<dyn TransactionPlugin>>>,
 }
 
 impl Plugins {
@@ -24,6 +27,7 @@ impl Plugins {
             plugins: vec![],
             delete_plugins: vec![],
             reference_plugins: vec![],
+            transaction_plugins: vec![],
         }
     }
 }
@@ -3,6 +3,8 @@ use crate::object_data::ObjectValues;
 use crate::plugins::DeletePlugin;
 use crate::plugins::InitEntryPlugin;
 use crate::plugins::Plugin;
+use crate::plugins::TransactionPlugin;
+use crate::utils::UndoLog;
 use crate::HitError;
 use crate::Id;
 use crate::{hit_mod::HitEntry, ObjectValue};
@@ -10,14 +12,22 @@ use crate::{index::IndexEntryProperty, Hit};
 use std::collections::HashMap;
 use std::rc::Rc;
 
+// the position of a removed id, to insert it back on rollback
+enum ModelTypeChange {
+    Added(String, Id),
+    Removed(String, Id, usize),
+}
+
 pub struct ModelTypeIndexer {
     index: HashMap<String, Vec<String>>,
+    undo_log: UndoLog<ModelTypeChange>,
 }
 
 impl ModelTypeIndexer {
     pub fn new() -> Self {
         ModelTypeIndexer {
             index: HashMap::new(),
+            undo_log: UndoLog::new(),
         }
     }
 
@@ -31,6 +41,24 @@ impl ModelTypeIndexer {
             .entry(model.get_name().to_string())
             .or_insert(vec![]);
         vector.push(id.to_string());
+        self.undo_log
+            .record(ModelTypeChange::Added(model.get_name().to_string(), id.to_string()));
+    }
+
+    fn undo(&mut self, change: ModelTypeChange) {
+        match change {
+            ModelTypeChange::Added(model_type, id) => {
+                if let Some(vector) = self.index.get_mut(&model_type) {
+                    if let Some(position) = vector.iter().rposition(|i| *i == id) {
+                        vector.remove(position);
+                    }
+                }
+            }
+            ModelTypeChange::Removed(model_type, id, position) => {
+                let vector = self.index.entry(model_type).or_default();
+                vector.insert(position.min(vector.len()), id);
+            }
+        }
     }
 }
 
@@ -46,6 +74,22 @@ impl InitEntryPlugin for ModelTypeIndexer {
     }
 }
 
+impl TransactionPlugin for ModelTypeIndexer {
+    fn on_begin_transaction(&mut self) {
+        self.undo_log.begin();
+    }
+
+    fn on_commit_transaction(&mut self) {
+        self.undo_log.commit();
+    }
+
+    fn on_rollback_transaction(&mut self) {
+        for change in self.undo_log.rollback() {
+            self.undo(change);
+        }
+    }
+}
+
 impl DeletePlugin for ModelTypeIndexer {
     fn on_before_delete_entry(
         &mut self,
@@ -63,10 +107,16 @@ impl DeletePlugin for ModelTypeIndexer {
         let model = entry.get_model();
         let model_type = model.get_name();
         let id = entry.get_id();
-        match self.index.get_mut(model_type) {
-            Some(vector) => vector.retain(|i| i != &id),
-            None => {}
-        };
+        if let Some(vector) = self.index.get_mut(model_type) {
+            if let Some(position) = vector.iter().position(|i| *i == id) {
+                vector.remove(position);
+                self.undo_log.record(ModelTypeChange::Removed(
+                    model_type.to_string(),
+                    id,
+                    position,
+                ));
+            }
+        }
         Ok(())
     }
 }
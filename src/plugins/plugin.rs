@@ -40,6 +40,15 @@ pub trait DeletePlugin {
         instance: &mut Hit,
     ) -> Result<(), HitError>;
 }
+// Lets a plugin save and restore the state it owns (indexes...) when a transaction
+// is rolled back. Transactions can be nested, so every begin call is matched by
+// exactly one commit or rollback call.
+pub trait TransactionPlugin {
+    fn on_begin_transaction(&mut self);
+    fn on_commit_transaction(&mut self);
+    fn on_rollback_transaction(&mut self);
+}
+
 pub trait ReferencePlugin {
     fn on_before_add_reference(
         &mut self,
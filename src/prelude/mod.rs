@@ -3,3 +3,4 @@ mod validators;
 pub use validators::unique_in_parent::UniqueInParentPlugin;
 pub use validators::unique_in_parent::UniqueInParentValidator;
 pub use validators::unique_in_parent::UniqueInParentValueIndex;
+pub use validators::StringEnumValidator;
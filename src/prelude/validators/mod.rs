@@ -1,2 +1,4 @@
 mod string_enum;
 pub mod unique_in_parent;
+
+pub use string_enum::StringEnumValidator;
@@ -1,10 +1,17 @@
-use crate::model::validators::{Validator, ValidatorContext};
+use crate::model::validators::{Validator, ValidatorContext, ValidatorDescription};
+use serde_json::{Map, Value};
 use crate::{HitError, ValidationError};
 
 pub struct StringEnumValidator {
     values: Vec<String>,
 }
 
+impl StringEnumValidator {
+    pub fn new(values: Vec<String>) -> Box<StringEnumValidator> {
+        Box::new(StringEnumValidator { values })
+    }
+}
+
 impl Validator<String> for StringEnumValidator {
     fn validate(
         &self,
@@ -25,4 +32,13 @@ impl Validator<String> for StringEnumValidator {
     fn on_kernel_init(&mut self, _field_name: &str, _model_namee: &str) -> Result<(), HitError> {
         Ok(())
     }
+
+    fn describe(&self) -> ValidatorDescription {
+        let mut arguments = Map::new();
+        arguments.insert(String::from("values"), Value::from(self.values.clone()));
+        ValidatorDescription {
+            name: String::from("enum"),
+            arguments,
+        }
+    }
 }
@@ -2,7 +2,7 @@ use std::{collections::HashSet, rc::Rc};
 
 use linked_hash_map::LinkedHashMap;
 
-use crate::{Hit, IndexEntryProperty, ObjectValue, ValidationError};
+use crate::{Hit, HitError, IndexEntryProperty, ObjectValue, ValidationError};
 
 use self::unique_in_parent_kernel::create_test_unique_in_parent_kernel;
 
@@ -233,3 +233,45 @@ fn it_should_remove_unique_errors_when_moving_an_entry() {
     assert!(hit.get_validation_errors("id3", "name").is_none());
     assert!(hit.get_validation_errors("id2", "name").is_none());
 }
+
+#[test]
+fn it_should_restore_the_value_index_when_a_transaction_fails() {
+    let kernel = Rc::new(create_test_unique_in_parent_kernel().expect("Error"));
+    let mut hit = Hit::new("id", "testunique/project", kernel.clone()).expect("Error");
+    let mut name = LinkedHashMap::new();
+    name.insert("name".into(), ObjectValue::String("identical".into()));
+    hit.insert(
+        "testunique/folder",
+        "id2",
+        name,
+        IndexEntryProperty {
+            id: "id".into(),
+            property: "folders".into(),
+        },
+        None,
+    )
+    .expect("Ok");
+
+    let result: Result<(), HitError> = hit.transaction(|tx| {
+        let mut name = LinkedHashMap::new();
+        name.insert("name".into(), ObjectValue::String("identical".into()));
+        tx.insert(
+            "testunique/folder",
+            "id3",
+            name,
+            IndexEntryProperty {
+                id: "id".into(),
+                property: "folders".into(),
+            },
+            None,
+        )?;
+        Err(HitError::ValidationError())
+    });
+    assert!(result.is_err());
+    assert_eq!(hit.get_validation_errors("id2", "name"), None);
+
+    // id3 is not in the value index anymore
+    hit.set("id2", "name", ObjectValue::String("identical".into()))
+        .expect("Ok");
+    assert_eq!(hit.get_validation_errors("id2", "name"), None);
+}
@@ -38,6 +38,9 @@ impl Kernel for TestUniqueKernel {
             .init_plugins
             .push(self.unique_in_parent_plugin.clone());
         plugins.plugins.push(self.unique_in_parent_plugin.clone());
+        plugins
+            .transaction_plugins
+            .push(self.unique_in_parent_plugin.clone());
 
         plugins
     }
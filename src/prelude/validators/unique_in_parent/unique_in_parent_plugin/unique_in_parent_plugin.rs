@@ -1,7 +1,7 @@
 use std::{cell::RefCell, collections::HashSet, rc::Rc};
 
 use crate::{DeletePlugin, Hit, Id, IndexEntryProperty, Model, ObjectValue, ObjectValues, Plugin};
-use crate::{HitError, InitEntryPlugin};
+use crate::{HitError, InitEntryPlugin, TransactionPlugin};
 
 use super::unique_in_parent_value_index::UniqueInParentValueIndex;
 
@@ -94,6 +94,20 @@ impl InitEntryPlugin for UniqueInParentPlugin {
     }
 }
 
+impl TransactionPlugin for UniqueInParentPlugin {
+    fn on_begin_transaction(&mut self) {
+        self.index.borrow_mut().begin_transaction();
+    }
+
+    fn on_commit_transaction(&mut self) {
+        self.index.borrow_mut().commit_transaction();
+    }
+
+    fn on_rollback_transaction(&mut self) {
+        self.index.borrow_mut().rollback_transaction();
+    }
+}
+
 impl DeletePlugin for UniqueInParentPlugin {
     fn on_before_delete_entry(
         &mut self,
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::utils::UndoLog;
+
 #[derive(Clone, Debug)]
 pub struct UniqueInParentValueIndexValue {
     pub id: String,
@@ -7,25 +9,34 @@ pub struct UniqueInParentValueIndexValue {
 }
 
 #[derive(Debug)]
-pub struct UniqueInParentValueIndex(HashMap<String, Vec<UniqueInParentValueIndexValue>>);
+pub struct UniqueInParentValueIndex {
+    values: HashMap<String, Vec<UniqueInParentValueIndexValue>>,
+    // the previous results of the keys changed during the transactions
+    undo_log: UndoLog<(String, Option<Vec<UniqueInParentValueIndexValue>>)>,
+}
 
 impl UniqueInParentValueIndex {
     pub fn new() -> Self {
-        UniqueInParentValueIndex(HashMap::new())
+        UniqueInParentValueIndex {
+            values: HashMap::new(),
+            undo_log: UndoLog::new(),
+        }
     }
 
     fn get_key(property_name: &str, parent_id: &str, parent_property_name: &str) -> String {
         format!("{}{}{}", property_name, parent_id, parent_property_name)
     }
     fn get_results(&self, key: &str) -> Option<&Vec<UniqueInParentValueIndexValue>> {
-        self.0.get(key)
+        self.values.get(key)
     }
     fn get_results_and_remove_id(
         &mut self,
         key: &str,
         target_id: &str,
     ) -> &mut Vec<UniqueInParentValueIndexValue> {
-        let results = self.0.entry(key.to_string()).or_insert_with(|| vec![]);
+        let previous = self.values.get(key).cloned();
+        self.undo_log.record((key.to_string(), previous));
+        let results = self.values.entry(key.to_string()).or_insert_with(|| vec![]);
         results.retain(|value| value.id != target_id);
         results
     }
@@ -63,4 +74,21 @@ impl UniqueInParentValueIndex {
         let key = Self::get_key(property_name, parent_id, parent_property_name);
         self.get_results(&key)
     }
+
+    pub(super) fn begin_transaction(&mut self) {
+        self.undo_log.begin();
+    }
+
+    pub(super) fn commit_transaction(&mut self) {
+        self.undo_log.commit();
+    }
+
+    pub(super) fn rollback_transaction(&mut self) {
+        for (key, previous) in self.undo_log.rollback() {
+            match previous {
+                Some(results) => self.values.insert(key, results),
+                None => self.values.remove(&key),
+            };
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use std::{cell::RefCell, clone::Clone, rc::Rc};
 
-use crate::model::validators::{Validator, ValidatorContext};
+use crate::model::validators::{Validator, ValidatorContext, ValidatorDescription};
+use serde_json::Map;
 use crate::{HitError, ValidationError};
 
 use super::unique_in_parent_plugin::{
@@ -79,4 +80,11 @@ impl Validator<String> for UniqueInParentValidator {
             .get_or_insert(model_name.to_string());
         Ok(())
     }
+
+    fn describe(&self) -> ValidatorDescription {
+        ValidatorDescription {
+            name: String::from("unique_in_parent"),
+            arguments: Map::new(),
+        }
+    }
 }
@@ -0,0 +1,236 @@
+use std::cmp::Ordering;
+
+use crate::field_types::get_linked_ids;
+use crate::object_data::{Id, ObjectValue};
+use crate::query::{Comparison, Query, QueryIndex};
+use crate::{Hit, TraversalOrder};
+
+fn get_number(value: &ObjectValue) -> Option<f32> {
+    match value {
+        ObjectValue::I32(value) => Some(*value as f32),
+        ObjectValue::F32(value) => Some(*value),
+        _ => None,
+    }
+}
+
+// the references and the subobjects are compared to their id
+fn is_equal(value: &ObjectValue, expected: &ObjectValue) -> bool {
+    match (value, expected) {
+        (ObjectValue::Reference(reference), ObjectValue::String(id))
+        | (ObjectValue::SubObject(reference), ObjectValue::String(id)) => reference.id == *id,
+        _ => match (get_number(value), get_number(expected)) {
+            (Some(value), Some(expected)) => value == expected,
+            _ => value == expected,
+        },
+    }
+}
+
+fn compare(value: &ObjectValue, expected: &ObjectValue) -> Option<Ordering> {
+    match (value, expected) {
+        (ObjectValue::String(value), ObjectValue::String(expected)) => Some(value.cmp(expected)),
+        _ => get_number(value)?.partial_cmp(&get_number(expected)?),
+    }
+}
+
+fn contains(value: &ObjectValue, expected: &ObjectValue) -> bool {
+    let expected = match expected {
+        ObjectValue::String(expected) => expected,
+        _ => return false,
+    };
+    match value {
+        ObjectValue::String(value) => value.contains(expected.as_str()),
+        ObjectValue::VecString(values) => values.contains(expected),
+        ObjectValue::VecReference(references) | ObjectValue::VecSubObjects(references) => {
+            references.iter().any(|reference| reference.id == *expected)
+        }
+        _ => false,
+    }
+}
+
+fn matches_string<F>(value: &ObjectValue, expected: &ObjectValue, predicate: F) -> bool
+where
+    F: Fn(&str, &str) -> bool,
+{
+    match (value, expected) {
+        (ObjectValue::String(value), ObjectValue::String(expected)) => predicate(value, expected),
+        _ => false,
+    }
+}
+
+fn matches_comparison(
+    value: &ObjectValue,
+    comparison: &Comparison,
+    expected: &ObjectValue,
+) -> bool {
+    match comparison {
+        Comparison::Equal => is_equal(value, expected),
+        Comparison::NotEqual => !is_equal(value, expected),
+        Comparison::Less => compare(value, expected) == Some(Ordering::Less),
+        Comparison::LessOrEqual => matches!(
+            compare(value, expected),
+            Some(Ordering::Less) | Some(Ordering::Equal)
+        ),
+        Comparison::Greater => compare(value, expected) == Some(Ordering::Greater),
+        Comparison::GreaterOrEqual => matches!(
+            compare(value, expected),
+            Some(Ordering::Greater) | Some(Ordering::Equal)
+        ),
+        Comparison::Contains => contains(value, expected),
+        Comparison::StartsWith => matches_string(value, expected, |value, expected| {
+            value.starts_with(expected)
+        }),
+        Comparison::EndsWith => {
+            matches_string(value, expected, |value, expected| value.ends_with(expected))
+        }
+    }
+}
+
+fn get_ancestors(hit: &Hit, id: &str) -> Vec<Id> {
+    hit.ancestors(id).map(|ancestor| ancestor.get_id()).collect()
+}
+
+fn is_referenced_by(hit: &Hit, id: &str, source: &str, field: &Option<String>) -> bool {
+    hit.get_references(id)
+        .unwrap_or_default()
+        .iter()
+        .any(|reference| {
+            reference.id == source
+                && field
+                    .as_ref()
+                    .is_none_or(|field| reference.property == *field)
+        })
+}
+
+// whether the object `id` satisfies the query
+pub(crate) fn matches(hit: &Hit, id: &str, query: &Query) -> bool {
+    let model = match hit.get_model(id) {
+        Some(model) => model,
+        None => return false,
+    };
+    match query {
+        Query::And(queries) => queries.iter().all(|query| matches(hit, id, query)),
+        Query::Or(queries) => queries.iter().any(|query| matches(hit, id, query)),
+        Query::Not(query) => !matches(hit, id, query),
+        Query::Model(name) => model.is_a(name),
+        Query::Id(expected) => id == expected,
+        Query::Field {
+            field,
+            comparison,
+            value,
+        } => {
+            // a field that the model does not have is null
+            let field_value = hit.get_value(id, field).unwrap_or(ObjectValue::Null);
+            matches_comparison(&field_value, comparison, value)
+        }
+        Query::Parent {
+            id: parent_id,
+            property,
+        } => match hit.get_parent(id) {
+            Some(parent) => {
+                parent.id == *parent_id
+                    && property
+                        .as_ref()
+                        .is_none_or(|property| parent.property == *property)
+            }
+            None => false,
+        },
+        Query::Ancestor(ancestor) => get_ancestors(hit, id).contains(ancestor),
+        Query::Descendant(descendant) => get_ancestors(hit, descendant).iter().any(|a| a == id),
+        Query::References {
+            id: referenced,
+            field,
+        } => is_referenced_by(hit, referenced, id, field),
+        Query::ReferencedBy { id: source, field } => is_referenced_by(hit, id, source, field),
+    }
+}
+
+fn get_model_candidates(hit: &Hit, name: &str, index: &dyn QueryIndex) -> Vec<Id> {
+    // the main object is created before the plugins can index it
+    let mut candidates = vec![hit.get_main_object_id().clone()];
+    for model_name in hit.kernel.get_models().iter() {
+        let is_candidate = match hit.kernel.get_model(model_name) {
+            Ok(model) => model.is_a(name),
+            Err(_) => false,
+        };
+        if is_candidate {
+            candidates.extend(index.get_ids_of_model(model_name).unwrap_or_default());
+        }
+    }
+    candidates
+}
+
+fn get_children(hit: &Hit, id: &str, property: &Option<String>) -> Vec<Id> {
+    let properties: Vec<String> = match (property, hit.get_model(id)) {
+        (Some(property), _) => vec![property.clone()],
+        (None, Some(model)) => model.get_fields().keys().cloned().collect(),
+        (None, None) => vec![],
+    };
+    properties
+        .iter()
+        .filter_map(|property| match hit.get_value(id, property) {
+            value @ Some(ObjectValue::SubObject(_))
+            | value @ Some(ObjectValue::VecSubObjects(_)) => Some(get_linked_ids(value)),
+            _ => None,
+        })
+        .flatten()
+        .map(|reference| reference.id)
+        .collect()
+}
+
+fn get_referenced_ids(hit: &Hit, id: &str, field: &Option<String>) -> Vec<Id> {
+    let fields: Vec<String> = match (field, hit.get_model(id)) {
+        (Some(field), _) => vec![field.clone()],
+        (None, Some(model)) => model.get_fields().keys().cloned().collect(),
+        (None, None) => vec![],
+    };
+    fields
+        .iter()
+        .filter_map(|field| match hit.get_value(id, field) {
+            value @ Some(ObjectValue::Reference(_))
+            | value @ Some(ObjectValue::VecReference(_)) => Some(get_linked_ids(value)),
+            _ => None,
+        })
+        .flatten()
+        .map(|reference| reference.id)
+        .collect()
+}
+
+// A list of objects that contains all the objects matching the query, read from the
+// relations of the objects and from the index. None when the whole document has to be read.
+pub(crate) fn get_candidates(
+    hit: &Hit,
+    query: &Query,
+    index: Option<&dyn QueryIndex>,
+) -> Option<Vec<Id>> {
+    match query {
+        Query::And(queries) => queries
+            .iter()
+            .filter_map(|query| get_candidates(hit, query, index))
+            .min_by_key(|candidates| candidates.len()),
+        Query::Or(queries) => {
+            let mut candidates = vec![];
+            for query in queries.iter() {
+                candidates.extend(get_candidates(hit, query, index)?);
+            }
+            Some(candidates)
+        }
+        Query::Not(_) | Query::Field { .. } => None,
+        Query::Model(name) => index.map(|index| get_model_candidates(hit, name, index)),
+        Query::Id(id) => Some(vec![id.clone()]),
+        Query::Parent { id, property } => Some(get_children(hit, id, property)),
+        Query::Ancestor(ancestor) => Some(
+            hit.descendants(ancestor, TraversalOrder::DepthFirst)
+                .map(|descendant| descendant.get_id())
+                .collect(),
+        ),
+        Query::Descendant(descendant) => Some(get_ancestors(hit, descendant)),
+        Query::References { id, .. } => Some(
+            hit.get_references(id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|reference| reference.id)
+                .collect(),
+        ),
+        Query::ReferencedBy { id, field } => Some(get_referenced_ids(hit, id, field)),
+    }
+}
@@ -0,0 +1,127 @@
+mod evaluate;
+mod parser;
+
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::object_data::{Id, ObjectValue};
+use crate::plugins::ModelTypeIndexer;
+
+pub(crate) use evaluate::{get_candidates, matches};
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum QueryError {
+    #[error("Unexpected character `{1}` at position {0}")]
+    UnexpectedCharacter(usize, char),
+    #[error("Unterminated string at position {0}")]
+    UnterminatedString(usize),
+    #[error("Invalid number `{1}` at position {0}")]
+    InvalidNumber(usize, String),
+    #[error("Expected {1} at position {0}")]
+    Expected(usize, String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    // a substring, an item of a string array, or the id of a reference or subobject array
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+// A condition on the objects of a document
+#[derive(Clone, Debug, PartialEq)]
+pub enum Query {
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+    // the model, one of its base models or one of its interfaces
+    Model(String),
+    Id(Id),
+    Field {
+        field: String,
+        comparison: Comparison,
+        value: ObjectValue,
+    },
+    // the object is a subobject of `id`, in `property` if it is set
+    Parent {
+        id: Id,
+        property: Option<String>,
+    },
+    // the object is contained, directly or not, in `id`
+    Ancestor(Id),
+    // the object contains, directly or not, `id`
+    Descendant(Id),
+    // the object references `id`, through `field` if it is set
+    References {
+        id: Id,
+        field: Option<String>,
+    },
+    // `id` references the object, through `field` if it is set
+    ReferencedBy {
+        id: Id,
+        field: Option<String>,
+    },
+}
+
+impl Query {
+    // Reads a query such as `model = "fs/folder" and name starts_with "src"`
+    pub fn parse(query: &str) -> Result<Query, QueryError> {
+        parser::parse(query)
+    }
+
+    pub fn field(field: &str, comparison: Comparison, value: ObjectValue) -> Query {
+        Query::Field {
+            field: field.to_string(),
+            comparison,
+            value,
+        }
+    }
+
+    pub fn and(self, query: Query) -> Query {
+        match self {
+            Query::And(mut queries) => {
+                queries.push(query);
+                Query::And(queries)
+            }
+            _ => Query::And(vec![self, query]),
+        }
+    }
+
+    pub fn or(self, query: Query) -> Query {
+        match self {
+            Query::Or(mut queries) => {
+                queries.push(query);
+                Query::Or(queries)
+            }
+            _ => Query::Or(vec![self, query]),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Query::parse(query)
+    }
+}
+
+// Lists the objects of a model, so that the queries on models do not read the whole document
+pub trait QueryIndex {
+    // None when the index knows no object of the model
+    fn get_ids_of_model(&self, model: &str) -> Option<Vec<Id>>;
+}
+
+impl QueryIndex for ModelTypeIndexer {
+    fn get_ids_of_model(&self, model: &str) -> Option<Vec<Id>> {
+        self.get(model).cloned()
+    }
+}
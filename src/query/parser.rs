@@ -0,0 +1,390 @@
+use crate::object_data::ObjectValue;
+use crate::query::{Comparison, Query, QueryError};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    String(String),
+    Number(ObjectValue),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 9] = ["!=", "<=", ">=", "=", "<", ">", "(", ")", ","];
+
+fn is_word_character(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+fn read_string(chars: &[char], start: usize) -> Result<(String, usize), QueryError> {
+    let mut value = String::new();
+    let mut position = start + 1;
+    while position < chars.len() {
+        match chars[position] {
+            '"' => return Ok((value, position + 1)),
+            '\\' if position + 1 < chars.len() => {
+                value.push(chars[position + 1]);
+                position += 2;
+            }
+            character => {
+                value.push(character);
+                position += 1;
+            }
+        }
+    }
+    Err(QueryError::UnterminatedString(start))
+}
+
+fn read_number(chars: &[char], start: usize) -> Result<(ObjectValue, usize), QueryError> {
+    let mut end = start + 1;
+    while end < chars.len() && (chars[end].is_ascii_digit() || chars[end] == '.') {
+        end += 1;
+    }
+    let text: String = chars[start..end].iter().collect();
+    let value = if text.contains('.') {
+        text.parse::<f32>().map(ObjectValue::F32).ok()
+    } else {
+        text.parse::<i32>().map(ObjectValue::I32).ok()
+    };
+    match value {
+        Some(value) => Ok((value, end)),
+        None => Err(QueryError::InvalidNumber(start, text)),
+    }
+}
+
+// the tokens, with their position in the query
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = vec![];
+    let mut position = 0;
+    'tokens: while position < chars.len() {
+        let character = chars[position];
+        if character.is_whitespace() {
+            position += 1;
+            continue;
+        }
+        if character == '"' {
+            let (value, end) = read_string(&chars, position)?;
+            tokens.push((position, Token::String(value)));
+            position = end;
+            continue;
+        }
+        let is_negative_number =
+            character == '-' && position + 1 < chars.len() && chars[position + 1].is_ascii_digit();
+        if character.is_ascii_digit() || is_negative_number {
+            let (value, end) = read_number(&chars, position)?;
+            tokens.push((position, Token::Number(value)));
+            position = end;
+            continue;
+        }
+        if is_word_character(character) {
+            let mut end = position;
+            while end < chars.len() && is_word_character(chars[end]) {
+                end += 1;
+            }
+            tokens.push((position, Token::Word(chars[position..end].iter().collect())));
+            position = end;
+            continue;
+        }
+        for symbol in SYMBOLS.iter() {
+            let length = symbol.chars().count();
+            let candidate: String = chars[position..chars.len().min(position + length)]
+                .iter()
+                .collect();
+            if candidate == *symbol {
+                tokens.push((position, Token::Symbol(symbol)));
+                position += length;
+                continue 'tokens;
+            }
+        }
+        return Err(QueryError::UnexpectedCharacter(position, character));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    // the length of the query, reported when the query ends too early
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, expected: &str) -> QueryError {
+        let position = match self.tokens.get(self.position) {
+            Some((position, _)) => *position,
+            None => self.end,
+        };
+        QueryError::Expected(position, expected.to_string())
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        self.peek() == Some(&Token::Word(word.to_string()))
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), QueryError> {
+        match self.peek() {
+            Some(Token::Symbol(found)) if *found == symbol => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => Err(self.error(&format!("`{}`", symbol))),
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<String, QueryError> {
+        match self.peek().cloned() {
+            Some(Token::String(value)) => {
+                self.position += 1;
+                Ok(value)
+            }
+            _ => Err(self.error("a string")),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut queries = vec![self.parse_and()?];
+        while self.is_word("or") {
+            self.position += 1;
+            queries.push(self.parse_and()?);
+        }
+        match queries.len() {
+            1 => Ok(queries.remove(0)),
+            _ => Ok(Query::Or(queries)),
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut queries = vec![self.parse_unary()?];
+        while self.is_word("and") {
+            self.position += 1;
+            queries.push(self.parse_unary()?);
+        }
+        match queries.len() {
+            1 => Ok(queries.remove(0)),
+            _ => Ok(Query::And(queries)),
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, QueryError> {
+        if self.is_word("not") {
+            self.position += 1;
+            return Ok(Query::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.peek() == Some(&Token::Symbol("(")) {
+            self.position += 1;
+            let query = self.parse_or()?;
+            self.expect_symbol(")")?;
+            return Ok(query);
+        }
+        let word = match self.peek().cloned() {
+            Some(Token::Word(word)) => word,
+            _ => return Err(self.error("a field, a relation or `(`")),
+        };
+        self.position += 1;
+        match word.as_str() {
+            "model" => self.parse_identity(Query::Model),
+            "id" => self.parse_identity(Query::Id),
+            "parent" => {
+                let (id, property) = self.parse_relation()?;
+                Ok(Query::Parent { id, property })
+            }
+            "ancestor" => Ok(Query::Ancestor(self.parse_relation_id()?)),
+            "descendant" => Ok(Query::Descendant(self.parse_relation_id()?)),
+            "references" => {
+                let (id, field) = self.parse_relation()?;
+                Ok(Query::References { id, field })
+            }
+            "referenced_by" => {
+                let (id, field) = self.parse_relation()?;
+                Ok(Query::ReferencedBy { id, field })
+            }
+            _ => {
+                let comparison = self.parse_comparison()?;
+                let value = self.parse_value()?;
+                Ok(Query::Field {
+                    field: word,
+                    comparison,
+                    value,
+                })
+            }
+        }
+    }
+
+    // `model` and `id` only accept `=` and `!=`
+    fn parse_identity<F>(&mut self, to_query: F) -> Result<Query, QueryError>
+    where
+        F: Fn(String) -> Query,
+    {
+        let is_equal = match self.peek() {
+            Some(Token::Symbol("=")) => true,
+            Some(Token::Symbol("!=")) => false,
+            _ => return Err(self.error("`=` or `!=`")),
+        };
+        self.position += 1;
+        let query = to_query(self.expect_string()?);
+        match is_equal {
+            true => Ok(query),
+            false => Ok(Query::Not(Box::new(query))),
+        }
+    }
+
+    // `("id")` or `("id", "property")`
+    fn parse_relation(&mut self) -> Result<(String, Option<String>), QueryError> {
+        self.expect_symbol("(")?;
+        let id = self.expect_string()?;
+        let property = match self.peek() {
+            Some(Token::Symbol(",")) => {
+                self.position += 1;
+                Some(self.expect_string()?)
+            }
+            _ => None,
+        };
+        self.expect_symbol(")")?;
+        Ok((id, property))
+    }
+
+    fn parse_relation_id(&mut self) -> Result<String, QueryError> {
+        self.expect_symbol("(")?;
+        let id = self.expect_string()?;
+        self.expect_symbol(")")?;
+        Ok(id)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Comparison, QueryError> {
+        let comparison = match self.peek() {
+            Some(Token::Symbol("=")) => Comparison::Equal,
+            Some(Token::Symbol("!=")) => Comparison::NotEqual,
+            Some(Token::Symbol("<")) => Comparison::Less,
+            Some(Token::Symbol("<=")) => Comparison::LessOrEqual,
+            Some(Token::Symbol(">")) => Comparison::Greater,
+            Some(Token::Symbol(">=")) => Comparison::GreaterOrEqual,
+            Some(Token::Word(word)) if word == "contains" => Comparison::Contains,
+            Some(Token::Word(word)) if word == "starts_with" => Comparison::StartsWith,
+            Some(Token::Word(word)) if word == "ends_with" => Comparison::EndsWith,
+            _ => return Err(self.error("a comparison")),
+        };
+        self.position += 1;
+        Ok(comparison)
+    }
+
+    fn parse_value(&mut self) -> Result<ObjectValue, QueryError> {
+        let value = match self.peek().cloned() {
+            Some(Token::String(value)) => ObjectValue::String(value),
+            Some(Token::Number(value)) => value,
+            Some(Token::Word(word)) if word == "true" => ObjectValue::Bool(true),
+            Some(Token::Word(word)) if word == "false" => ObjectValue::Bool(false),
+            Some(Token::Word(word)) if word == "null" => ObjectValue::Null,
+            _ => return Err(self.error("a value")),
+        };
+        self.position += 1;
+        Ok(value)
+    }
+}
+
+pub(super) fn parse(query: &str) -> Result<Query, QueryError> {
+    let mut parser = Parser {
+        tokens: tokenize(query)?,
+        position: 0,
+        end: query.chars().count(),
+    };
+    let result = parser.parse_or()?;
+    match parser.next() {
+        None => Ok(result),
+        Some(_) => {
+            parser.position -= 1;
+            Err(parser.error("`and`, `or` or the end of the query"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::object_data::ObjectValue;
+    use crate::query::{Comparison, Query, QueryError};
+
+    #[test]
+    fn it_should_parse_a_query() {
+        let query = Query::parse(
+            r#"model = "fs/folder" and name starts_with "src" and ancestor("root")
+                or not (size >= -2.5 or hidden = true) and parent("id", "items")"#,
+        )
+        .expect("Error");
+        assert_eq!(
+            query,
+            Query::Or(vec![
+                Query::And(vec![
+                    Query::Model("fs/folder".into()),
+                    Query::field(
+                        "name",
+                        Comparison::StartsWith,
+                        ObjectValue::String("src".into())
+                    ),
+                    Query::Ancestor("root".into()),
+                ]),
+                Query::And(vec![
+                    Query::Not(Box::new(Query::Or(vec![
+                        Query::field("size", Comparison::GreaterOrEqual, ObjectValue::F32(-2.5)),
+                        Query::field("hidden", Comparison::Equal, ObjectValue::Bool(true)),
+                    ]))),
+                    Query::Parent {
+                        id: "id".into(),
+                        property: Some("items".into())
+                    },
+                ]),
+            ])
+        );
+        assert_eq!(
+            Query::parse(r#"id != "a\"b" and references("c")"#),
+            Ok(
+                Query::Not(Box::new(Query::Id("a\"b".into()))).and(Query::References {
+                    id: "c".into(),
+                    field: None
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn it_should_report_the_position_of_syntax_errors() {
+        assert_eq!(
+            Query::parse(r#"name = "a" and"#),
+            Err(QueryError::Expected(
+                14,
+                "a field, a relation or `(`".into()
+            ))
+        );
+        assert_eq!(
+            Query::parse(r#"model < "a""#),
+            Err(QueryError::Expected(6, "`=` or `!=`".into()))
+        );
+        assert_eq!(
+            Query::parse(r#"name = "a" "b""#),
+            Err(QueryError::Expected(
+                11,
+                "`and`, `or` or the end of the query".into()
+            ))
+        );
+        assert_eq!(
+            Query::parse(r#"name = "a"#),
+            Err(QueryError::UnterminatedString(7))
+        );
+        assert_eq!(
+            Query::parse("size = 1.2.3"),
+            Err(QueryError::InvalidNumber(7, "1.2.3".into()))
+        );
+        assert_eq!(
+            Query::parse("name ~ 1"),
+            Err(QueryError::UnexpectedCharacter(5, '~'))
+        );
+    }
+}
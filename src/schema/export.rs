@@ -0,0 +1,195 @@
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use linked_hash_map::LinkedHashMap;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    FieldDefault, FieldDescription, Fields, HitError, HitKernel, Model, ModelFieldRef, ObjectValue,
+};
+
+#[derive(Serialize)]
+struct SchemaDocument {
+    #[serde(skip_serializing_if = "is_zero")]
+    version: u32,
+    interfaces: InterfacesSchema,
+    models: LinkedHashMap<String, ModelSchema>,
+}
+
+// the interfaces are only described by an object when some of them declare fields
+#[derive(Serialize)]
+#[serde(untagged)]
+enum InterfacesSchema {
+    Names(Vec<String>),
+    Definitions(LinkedHashMap<String, InterfaceSchema>),
+}
+
+#[derive(Serialize)]
+struct InterfaceSchema {
+    #[serde(skip_serializing_if = "LinkedHashMap::is_empty")]
+    fields: LinkedHashMap<String, FieldSchema>,
+}
+
+#[derive(Serialize)]
+struct ModelSchema {
+    label: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    interfaces: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extends: Option<String>,
+    #[serde(rename = "abstract", skip_serializing_if = "is_false")]
+    is_abstract: bool,
+    // only written when it is not the default, which is false for the abstract models
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instantiable: Option<bool>,
+    fields: LinkedHashMap<String, FieldSchema>,
+}
+
+#[derive(Serialize)]
+struct FieldSchema {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(skip_serializing_if = "is_false")]
+    required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authorized_models: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    validators: Vec<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<Value>,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+// the initializers cannot be written in a schema
+fn default_to_json(default: Option<FieldDefault>) -> Option<Value> {
+    match default?.get_constant()? {
+        ObjectValue::Bool(value) => Some(Value::Bool(*value)),
+        ObjectValue::I32(value) => Some(Value::from(*value)),
+        ObjectValue::F32(value) => Some(Value::from(*value)),
+        ObjectValue::String(value) => Some(Value::String(value.clone())),
+        ObjectValue::VecString(values) => Some(Value::from(values.clone())),
+        _ => None,
+    }
+}
+
+fn field_to_schema(field: &ModelFieldRef, default: Option<FieldDefault>) -> FieldSchema {
+    let field = field.borrow();
+    let description: FieldDescription = field.describe();
+    FieldSchema {
+        default: default_to_json(default),
+        kind: description.kind.get_name().to_string(),
+        required: description.required,
+        authorized_models: description.authorized_models,
+        validators: description
+            .validators
+            .into_iter()
+            .map(|validator| {
+                let mut value = Map::new();
+                value.insert(String::from("type"), Value::String(validator.name));
+                value.extend(validator.arguments);
+                value
+            })
+            .collect(),
+    }
+}
+
+// the interfaces have no defaults
+fn fields_to_schema(fields: &Fields, model: Option<&Model>) -> LinkedHashMap<String, FieldSchema> {
+    let mut schema = LinkedHashMap::new();
+    for (name, field) in fields.iter() {
+        let default = model.and_then(|model| model.get_default(name));
+        schema.insert(name.clone(), field_to_schema(field, default));
+    }
+    schema
+}
+
+// the inherited fields and interfaces are only written in the base model
+fn model_to_schema(
+    kernel: &HitKernel,
+    model: &Model,
+    instantiable: bool,
+) -> Result<ModelSchema, HitError> {
+    let mut fields = model.fields.clone();
+    let mut interfaces = model.interfaces.clone();
+    if let Some(base_name) = &model.extends {
+        let base = kernel.get_model(base_name)?;
+        for (name, field) in base.fields.iter() {
+            if matches!(fields.get(name), Some(own_field) if Rc::ptr_eq(own_field, field)) {
+                fields.remove(name);
+            }
+        }
+        interfaces.retain(|interface| !base.interfaces.contains(interface));
+    }
+    Ok(ModelSchema {
+        label: model.get_label().clone(),
+        interfaces,
+        extends: model.extends.clone(),
+        is_abstract: model.is_abstract,
+        instantiable: match instantiable != model.is_abstract {
+            true => None,
+            false => Some(instantiable),
+        },
+        fields: fields_to_schema(&fields, Some(model)),
+    })
+}
+
+// Describes the models of a kernel in the format read by `load_kernel_from_json`.
+// The interfaces are the ones implemented by the models, and the authorized models
+// that are not models.
+pub fn export_schema(kernel: &HitKernel) -> Result<String, HitError> {
+    let instantiable_models: Vec<String> = kernel
+        .get_instantiable_models()
+        .iter()
+        .map(|model| model.get_name().clone())
+        .collect();
+    let model_names = kernel.get_models();
+
+    let mut interfaces = BTreeSet::new();
+    let mut models = LinkedHashMap::new();
+    for name in model_names.iter() {
+        let model = kernel.get_model(name)?;
+        let schema = model_to_schema(kernel, &model, instantiable_models.contains(name))?;
+        interfaces.extend(model.interfaces.iter().cloned());
+        for field in schema.fields.values() {
+            for authorized_model in field.authorized_models.iter().flatten() {
+                if !model_names.contains(authorized_model) {
+                    interfaces.insert(authorized_model.clone());
+                }
+            }
+        }
+        models.insert(name.clone(), schema);
+    }
+
+    let has_definitions = interfaces
+        .iter()
+        .any(|name| kernel.get_interface(name).is_some());
+    let interfaces = match has_definitions {
+        false => InterfacesSchema::Names(interfaces.into_iter().collect()),
+        true => InterfacesSchema::Definitions(
+            interfaces
+                .into_iter()
+                .map(|name| {
+                    let fields = match kernel.get_interface(&name) {
+                        Some(interface) => fields_to_schema(&interface.fields, None),
+                        None => LinkedHashMap::new(),
+                    };
+                    (name, InterfaceSchema { fields })
+                })
+                .collect(),
+        ),
+    };
+    let document = SchemaDocument {
+        version: kernel.get_schema_version(),
+        interfaces,
+        models,
+    };
+    serde_json::to_string_pretty(&document).map_err(|_| HitError::InvalidDataType())
+}
@@ -0,0 +1,574 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use linked_hash_map::LinkedHashMap;
+use serde::Deserialize;
+
+use crate::field_types::*;
+use crate::prelude::{
+    StringEnumValidator, UniqueInParentPlugin, UniqueInParentValidator, UniqueInParentValueIndex,
+};
+use crate::schema::SchemaError;
+use crate::validators::{MaxLength, Validators};
+use crate::{
+    DefaultKernel, FieldDefault, HitKernel, Interface, KernelBuilder, Model, ModelFieldRef,
+    ObjectValue,
+};
+
+// The schema documents are read into this value whatever their format, so that the
+// errors have the same paths. Unlike serde_json::Value, it keeps the order of the
+// fields, which is the order of the fields of the models.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Value {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(LinkedHashMap<String, Value>),
+}
+
+type JSONObject = LinkedHashMap<String, Value>;
+
+impl Value {
+    fn as_object(&self) -> Option<&JSONObject> {
+        match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+    fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Integer(value) if *value >= 0 => Some(*value as u64),
+            _ => None,
+        }
+    }
+}
+
+pub fn load_kernel_from_json(schema: &str) -> Result<Rc<HitKernel>, SchemaError> {
+    let schema: Value = serde_json::from_str(schema)
+        .map_err(|error| SchemaError::InvalidSyntax(error.to_string()))?;
+    load_kernel(&schema)
+}
+
+#[cfg(feature = "yaml")]
+pub fn load_kernel_from_yaml(schema: &str) -> Result<Rc<HitKernel>, SchemaError> {
+    let schema: Value = serde_yaml::from_str(schema)
+        .map_err(|error| SchemaError::InvalidSyntax(error.to_string()))?;
+    load_kernel(&schema)
+}
+
+#[cfg(feature = "toml")]
+pub fn load_kernel_from_toml(schema: &str) -> Result<Rc<HitKernel>, SchemaError> {
+    let schema: Value =
+        toml::from_str(schema).map_err(|error| SchemaError::InvalidSyntax(error.to_string()))?;
+    load_kernel(&schema)
+}
+
+fn load_kernel(schema: &Value) -> Result<Rc<HitKernel>, SchemaError> {
+    let mut loader = Loader {
+        model_names: HashSet::new(),
+        interfaces: HashSet::new(),
+        unique_in_parent: None,
+    };
+    let kernel = loader.load(schema)?;
+    Ok(Rc::new(kernel))
+}
+
+fn property_path(path: &str, property: &str) -> String {
+    match path {
+        "" => property.to_string(),
+        _ => format!("{}.{}", path, property),
+    }
+}
+
+fn key_path(path: &str, key: &str) -> String {
+    format!("{}[\"{}\"]", path, key)
+}
+
+fn index_path(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
+
+fn as_object<'a>(value: &'a Value, path: &str) -> Result<&'a JSONObject, SchemaError> {
+    value
+        .as_object()
+        .ok_or_else(|| SchemaError::InvalidType(path.to_string(), "an object".into()))
+}
+
+fn as_string<'a>(value: &'a Value, path: &str) -> Result<&'a str, SchemaError> {
+    value
+        .as_str()
+        .ok_or_else(|| SchemaError::InvalidType(path.to_string(), "a string".into()))
+}
+
+fn as_bool(value: &Value, path: &str) -> Result<bool, SchemaError> {
+    value
+        .as_bool()
+        .ok_or_else(|| SchemaError::InvalidType(path.to_string(), "a boolean".into()))
+}
+
+fn as_strings(value: &Value, path: &str) -> Result<Vec<String>, SchemaError> {
+    let values = value
+        .as_array()
+        .ok_or_else(|| SchemaError::InvalidType(path.to_string(), "an array of strings".into()))?;
+    let mut strings = vec![];
+    for (index, value) in values.iter().enumerate() {
+        strings.push(as_string(value, &index_path(path, index))?.to_string());
+    }
+    Ok(strings)
+}
+
+fn get_property<'a>(
+    object: &'a JSONObject,
+    path: &str,
+    property: &str,
+) -> Result<&'a Value, SchemaError> {
+    object
+        .get(property)
+        .ok_or_else(|| SchemaError::MissingProperty(path.to_string(), property.to_string()))
+}
+
+fn check_properties(object: &JSONObject, path: &str, allowed: &[&str]) -> Result<(), SchemaError> {
+    for key in object.keys() {
+        if !allowed.contains(&key.as_str()) {
+            return Err(SchemaError::UnknownProperty(property_path(path, key)));
+        }
+    }
+    Ok(())
+}
+
+// the default of the field, if it declares one
+fn load_default<F>(
+    field: &JSONObject,
+    path: &str,
+    expected: &str,
+    to_value: F,
+) -> Result<Option<FieldDefault>, SchemaError>
+where
+    F: Fn(&Value) -> Option<ObjectValue>,
+{
+    match field.get("default") {
+        Some(value) => match to_value(value) {
+            Some(value) => Ok(Some(FieldDefault::Value(value))),
+            None => Err(SchemaError::InvalidType(
+                property_path(path, "default"),
+                expected.to_string(),
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
+type UniqueInParent = (
+    Rc<RefCell<UniqueInParentPlugin>>,
+    Rc<RefCell<UniqueInParentValueIndex>>,
+);
+
+struct Loader {
+    model_names: HashSet<String>,
+    interfaces: HashSet<String>,
+    // created by the first unique_in_parent validator, and shared by the others
+    unique_in_parent: Option<UniqueInParent>,
+}
+
+impl Loader {
+    fn load(&mut self, schema: &Value) -> Result<DefaultKernel, SchemaError> {
+        let schema = as_object(schema, "")?;
+        check_properties(schema, "", &["version", "interfaces", "models"])?;
+        // the interfaces are a list of names, or an object with the fields of each interface
+        let interfaces = match schema.get("interfaces") {
+            Some(Value::Object(interfaces)) => Some(interfaces),
+            Some(interfaces) => {
+                self.interfaces = as_strings(interfaces, "interfaces")?.into_iter().collect();
+                None
+            }
+            None => None,
+        };
+        if let Some(interfaces) = interfaces {
+            self.interfaces = interfaces.keys().cloned().collect();
+        }
+        let models = as_object(get_property(schema, "", "models")?, "models")?;
+        self.model_names = models.keys().cloned().collect();
+
+        let mut builder = KernelBuilder::new();
+        if let Some(version) = schema.get("version") {
+            let version = version
+                .as_u64()
+                .filter(|version| *version <= u32::MAX as u64)
+                .ok_or_else(|| SchemaError::InvalidType("version".into(), "an integer".into()))?;
+            builder = builder.set_schema_version(version as u32);
+        }
+        match interfaces {
+            Some(interfaces) => {
+                for (name, interface) in interfaces.iter() {
+                    let path = key_path("interfaces", name);
+                    let interface = self.load_interface(name, interface, &path)?;
+                    builder = builder.add_interface_definition(interface);
+                }
+            }
+            None => {
+                for interface in self.interfaces.iter() {
+                    builder = builder.add_interface(interface);
+                }
+            }
+        }
+        for (name, model) in models.iter() {
+            let path = key_path("models", name);
+            let (model, instantiable) = self.load_model(name, model, &path)?;
+            builder = builder.add_model(Rc::new(model));
+            if instantiable {
+                builder = builder.mark_instantiable(name);
+            }
+        }
+
+        if let Some((plugin, _)) = &self.unique_in_parent {
+            builder = builder
+                .add_delete_plugin(plugin.clone())
+                .add_init_plugin(plugin.clone())
+                .add_plugin(plugin.clone())
+                .add_transaction_plugin(plugin.clone());
+        }
+        builder.build().map_err(SchemaError::HitError)
+    }
+
+    fn load_interface(
+        &mut self,
+        name: &str,
+        interface: &Value,
+        path: &str,
+    ) -> Result<Interface, SchemaError> {
+        let interface = as_object(interface, path)?;
+        check_properties(interface, path, &["fields"])?;
+        let mut new_interface = Interface::new(name.to_string());
+        if let Some(fields) = interface.get("fields") {
+            let fields_path = property_path(path, "fields");
+            for (field_name, field) in as_object(fields, &fields_path)?.iter() {
+                // the models implementing the interface declare their own defaults
+                let (field, _default) =
+                    self.load_field(field_name, field, &key_path(&fields_path, field_name))?;
+                new_interface.fields.insert(field_name.clone(), field);
+            }
+        }
+        Ok(new_interface)
+    }
+
+    fn load_model(
+        &mut self,
+        name: &str,
+        model: &Value,
+        path: &str,
+    ) -> Result<(Model, bool), SchemaError> {
+        let model = as_object(model, path)?;
+        check_properties(
+            model,
+            path,
+            &[
+                "label",
+                "interfaces",
+                "extends",
+                "abstract",
+                "instantiable",
+                "fields",
+            ],
+        )?;
+        let label = match model.get("label") {
+            Some(label) => as_string(label, &property_path(path, "label"))?,
+            None => name,
+        };
+        let mut new_model = Model::new(name.to_string(), label.to_string());
+
+        if let Some(interfaces) = model.get("interfaces") {
+            let interfaces_path = property_path(path, "interfaces");
+            for (index, interface) in as_strings(interfaces, &interfaces_path)?
+                .into_iter()
+                .enumerate()
+            {
+                if !self.interfaces.contains(&interface) {
+                    return Err(SchemaError::UnknownInterface(
+                        index_path(&interfaces_path, index),
+                        interface,
+                    ));
+                }
+                new_model.interfaces.push(interface);
+            }
+        }
+
+        if let Some(base) = model.get("extends") {
+            let base_path = property_path(path, "extends");
+            let base = as_string(base, &base_path)?;
+            if !self.model_names.contains(base) {
+                return Err(SchemaError::UnknownModel(base_path, base.to_string()));
+            }
+            new_model.extends = Some(base.to_string());
+        }
+        if let Some(is_abstract) = model.get("abstract") {
+            new_model.is_abstract = as_bool(is_abstract, &property_path(path, "abstract"))?;
+        }
+
+        let instantiable = match model.get("instantiable") {
+            Some(instantiable) => as_bool(instantiable, &property_path(path, "instantiable"))?,
+            None => !new_model.is_abstract,
+        };
+
+        let fields_path = property_path(path, "fields");
+        let fields = match model.get("fields") {
+            Some(fields) => as_object(fields, &fields_path)?,
+            // a model extending another one can inherit all its fields
+            None if new_model.extends.is_some() => return Ok((new_model, instantiable)),
+            None => {
+                return Err(SchemaError::MissingProperty(
+                    path.to_string(),
+                    "fields".into(),
+                ))
+            }
+        };
+        for (field_name, field) in fields.iter() {
+            let (field, default) =
+                self.load_field(field_name, field, &key_path(&fields_path, field_name))?;
+            new_model.fields.insert(field_name.clone(), field);
+            if let Some(default) = default {
+                new_model.set_default(field_name, default);
+            }
+        }
+        Ok((new_model, instantiable))
+    }
+
+    // the field, and its default if it declares one
+    fn load_field(
+        &mut self,
+        name: &str,
+        field: &Value,
+        path: &str,
+    ) -> Result<(ModelFieldRef, Option<FieldDefault>), SchemaError> {
+        let field = as_object(field, path)?;
+        let type_path = property_path(path, "type");
+        let field_type = as_string(get_property(field, path, "type")?, &type_path)?;
+        let name = name.to_string();
+        let required = match field.get("required") {
+            Some(required) => as_bool(required, &property_path(path, "required"))?,
+            None => false,
+        };
+        let mut default = None;
+        let field: ModelFieldRef = match field_type {
+            "bool" => {
+                check_properties(field, path, &["type", "required", "default"])?;
+                default = load_default(field, path, "a boolean", |value| {
+                    value.as_bool().map(ObjectValue::Bool)
+                })?;
+                Rc::new(RefCell::new(FieldTypeBool {
+                    name,
+                    required,
+                    validators: vec![],
+                }))
+            }
+            "date" => {
+                check_properties(field, path, &["type", "required"])?;
+                Rc::new(RefCell::new(FieldTypeDate {
+                    name,
+                    required,
+                    validators: vec![],
+                }))
+            }
+            "integer" => {
+                check_properties(field, path, &["type", "required", "default"])?;
+                default = load_default(field, path, "an integer", |value| match value {
+                    Value::Integer(value) => i32::try_from(*value).ok().map(ObjectValue::I32),
+                    _ => None,
+                })?;
+                Rc::new(RefCell::new(FieldTypeInteger {
+                    name,
+                    required,
+                    validators: vec![],
+                }))
+            }
+            "float" => {
+                check_properties(field, path, &["type", "required", "default"])?;
+                default = load_default(field, path, "a number", |value| match value {
+                    Value::Integer(value) => Some(ObjectValue::F32(*value as f32)),
+                    Value::Float(value) => Some(ObjectValue::F32(*value as f32)),
+                    _ => None,
+                })?;
+                Rc::new(RefCell::new(FieldTypeFloat {
+                    name,
+                    required,
+                    validators: vec![],
+                }))
+            }
+            "string" => {
+                check_properties(field, path, &["type", "required", "validators", "default"])?;
+                default = load_default(field, path, "a string", |value| {
+                    value
+                        .as_str()
+                        .map(|value| ObjectValue::String(value.to_string()))
+                })?;
+                Rc::new(RefCell::new(FieldTypeString {
+                    validators: self.load_string_validators(&name, field, path)?,
+                    name,
+                    required,
+                    _enum: None,
+                }))
+            }
+            "string_array" => {
+                check_properties(field, path, &["type", "required", "validators", "default"])?;
+                default = load_default(field, path, "an array of strings", |value| {
+                    as_strings(value, "").ok().map(ObjectValue::VecString)
+                })?;
+                Rc::new(RefCell::new(FieldTypeStringVec {
+                    validators: self.load_string_validators(&name, field, path)?,
+                    name,
+                    required,
+                    _enum: None,
+                }))
+            }
+            "reference" => {
+                check_properties(field, path, &["type", "required", "authorized_models"])?;
+                Rc::new(RefCell::new(FieldTypeReference {
+                    name,
+                    required,
+                    validators: vec![],
+                    authorized_models: self.load_authorized_models(field, path)?,
+                }))
+            }
+            "reference_array" => {
+                check_properties(field, path, &["type", "authorized_models"])?;
+                Rc::new(RefCell::new(FieldTypeReferenceArray {
+                    name,
+                    validators: vec![],
+                    authorized_models: self.load_authorized_models(field, path)?,
+                }))
+            }
+            "subobject" => {
+                check_properties(field, path, &["type", "required", "authorized_models"])?;
+                Rc::new(RefCell::new(FieldTypeSubobject {
+                    name,
+                    required,
+                    validators: vec![],
+                    authorized_models: self.load_authorized_models(field, path)?,
+                }))
+            }
+            "subobject_array" => {
+                check_properties(field, path, &["type", "authorized_models"])?;
+                Rc::new(RefCell::new(FieldTypeSubobjectArray {
+                    name,
+                    validators: vec![],
+                    authorized_models: self.load_authorized_models(field, path)?,
+                }))
+            }
+            _ => {
+                return Err(SchemaError::UnknownFieldType(
+                    type_path,
+                    field_type.to_string(),
+                ))
+            }
+        };
+        Ok((field, default))
+    }
+
+    // the authorized models can be models or interfaces
+    fn load_authorized_models(
+        &self,
+        field: &JSONObject,
+        path: &str,
+    ) -> Result<Vec<String>, SchemaError> {
+        let value = get_property(field, path, "authorized_models")?;
+        let path = property_path(path, "authorized_models");
+        let authorized_models = as_strings(value, &path)?;
+        for (index, name) in authorized_models.iter().enumerate() {
+            if !self.model_names.contains(name) && !self.interfaces.contains(name) {
+                return Err(SchemaError::UnknownModel(
+                    index_path(&path, index),
+                    name.clone(),
+                ));
+            }
+        }
+        Ok(authorized_models)
+    }
+
+    fn load_string_validators(
+        &mut self,
+        field_name: &str,
+        field: &JSONObject,
+        path: &str,
+    ) -> Result<Validators<String>, SchemaError> {
+        let path = property_path(path, "validators");
+        let validators = match field.get("validators") {
+            Some(validators) => validators
+                .as_array()
+                .ok_or_else(|| SchemaError::InvalidType(path.clone(), "an array".into()))?,
+            None => return Ok(vec![]),
+        };
+        let mut new_validators: Validators<String> = vec![];
+        for (index, validator) in validators.iter().enumerate() {
+            let path = index_path(&path, index);
+            let validator = as_object(validator, &path)?;
+            let type_path = property_path(&path, "type");
+            let validator_type = as_string(get_property(validator, &path, "type")?, &type_path)?;
+            match validator_type {
+                "max_length" => {
+                    check_properties(validator, &path, &["type", "length"])?;
+                    let length_path = property_path(&path, "length");
+                    let length = get_property(validator, &path, "length")?
+                        .as_u64()
+                        .filter(|length| *length <= u8::MAX as u64)
+                        .ok_or_else(|| {
+                            SchemaError::InvalidType(length_path, "an integer from 0 to 255".into())
+                        })?;
+                    new_validators.push(Box::new(MaxLength {
+                        length: length as u8,
+                    }));
+                }
+                "enum" => {
+                    check_properties(validator, &path, &["type", "values"])?;
+                    let values_path = property_path(&path, "values");
+                    let values =
+                        as_strings(get_property(validator, &path, "values")?, &values_path)?;
+                    new_validators.push(StringEnumValidator::new(values));
+                }
+                "unique_in_parent" => {
+                    check_properties(validator, &path, &["type"])?;
+                    let (plugin, value_index) = self.unique_in_parent.get_or_insert_with(|| {
+                        let value_index = Rc::new(RefCell::new(UniqueInParentValueIndex::new()));
+                        let plugin =
+                            Rc::new(RefCell::new(UniqueInParentPlugin::new(value_index.clone())));
+                        (plugin, value_index)
+                    });
+                    new_validators.push(UniqueInParentValidator::new(
+                        field_name.to_string(),
+                        plugin.clone(),
+                        value_index.clone(),
+                    ));
+                }
+                _ => {
+                    return Err(SchemaError::UnknownValidator(
+                        type_path,
+                        validator_type.to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(new_validators)
+    }
+}
@@ -0,0 +1,448 @@
+mod export;
+mod loader;
+
+use thiserror::Error;
+
+use crate::HitError;
+
+pub use export::export_schema;
+pub use loader::load_kernel_from_json;
+#[cfg(feature = "toml")]
+pub use loader::load_kernel_from_toml;
+#[cfg(feature = "yaml")]
+pub use loader::load_kernel_from_yaml;
+
+// The first member of the variants is the path of the invalid part of the schema
+#[derive(Error, Debug, PartialEq)]
+pub enum SchemaError {
+    #[error("Invalid schema document: {0}")]
+    InvalidSyntax(String),
+    #[error("`{0}` should be {1}")]
+    InvalidType(String, String),
+    #[error("`{0}` is missing the property `{1}`")]
+    MissingProperty(String, String),
+    #[error("Unknown property `{0}`")]
+    UnknownProperty(String),
+    #[error("`{0}`: unknown field type `{1}`")]
+    UnknownFieldType(String, String),
+    #[error("`{0}`: unknown validator `{1}`")]
+    UnknownValidator(String, String),
+    #[error("`{0}`: unknown model or interface `{1}`")]
+    UnknownModel(String, String),
+    #[error("`{0}`: unknown interface `{1}`")]
+    UnknownInterface(String, String),
+    #[error("Kernel initialization failed: {0}")]
+    HitError(HitError),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::field_types::{FieldTypeString, FieldTypeSubobjectArray};
+    use crate::schema::{export_schema, load_kernel_from_json, SchemaError};
+    use crate::test_kernel::create_test_kernel;
+    use crate::{FieldKind, Hit, HitError, IndexEntryProperty, LinkedHashMap, ObjectValue};
+    use serde_json::json;
+
+    // the fields are not sorted, to check that their order is kept
+    const SCHEMA: &str = r#"{
+    "interfaces": ["fs/item"],
+    "models": {
+        "fs/filesystem": {
+            "label": "Filesystem",
+            "instantiable": false,
+            "fields": {
+                "name": {"type": "string", "required": true},
+                "items": {"type": "subobject_array", "authorized_models": ["fs/item"]}
+            }
+        },
+        "fs/folder": {
+            "label": "Folder",
+            "interfaces": ["fs/item"],
+            "fields": {
+                "name": {
+                    "type": "string",
+                    "required": true,
+                    "validators": [
+                        {"type": "max_length", "length": 10},
+                        {"type": "unique_in_parent"}
+                    ]
+                },
+                "items": {"type": "subobject_array", "authorized_models": ["fs/item"]},
+                "link": {"type": "reference", "authorized_models": ["fs/folder"]},
+                "color": {
+                    "type": "string",
+                    "validators": [{"type": "enum", "values": ["red", "blue"]}]
+                },
+                "tags": {"type": "string_array"},
+                "size": {"type": "integer"},
+                "hidden": {"type": "bool"},
+                "created": {"type": "date"},
+                "ratio": {"type": "float"}
+            }
+        }
+    }
+}"#;
+
+    fn create_schema() -> serde_json::Value {
+        serde_json::from_str(SCHEMA).unwrap()
+    }
+
+    fn items(id: &str) -> IndexEntryProperty {
+        IndexEntryProperty {
+            id: id.into(),
+            property: "items".into(),
+        }
+    }
+
+    fn load_error(schema: serde_json::Value) -> SchemaError {
+        match load_kernel_from_json(&schema.to_string()) {
+            Ok(_) => panic!("The schema should be invalid"),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn it_should_load_the_models() {
+        let kernel = load_kernel_from_json(SCHEMA).expect("Error");
+        assert_eq!(kernel.get_models(), vec!["fs/filesystem", "fs/folder"]);
+        let instantiable: Vec<&String> = kernel
+            .get_instantiable_models()
+            .iter()
+            .map(|model| model.get_name())
+            .collect();
+        assert_eq!(instantiable, vec!["fs/folder"]);
+
+        let folder = kernel.get_model("fs/folder").expect("Error");
+        assert_eq!(folder.get_label(), "Folder");
+        assert!(folder.implements_interface(&"fs/item".to_string()));
+        let fields: Vec<&String> = folder.fields.keys().collect();
+        assert_eq!(
+            fields,
+            vec!["name", "items", "link", "color", "tags", "size", "hidden", "created", "ratio"]
+        );
+        let name = folder.get_field("name").expect("Error").borrow();
+        let name = name.downcast_ref::<FieldTypeString>().expect("Error");
+        assert!(name.required);
+        assert_eq!(name.validators.len(), 2);
+        let items = folder.get_field("items").expect("Error").borrow();
+        let items = items
+            .downcast_ref::<FieldTypeSubobjectArray>()
+            .expect("Error");
+        assert_eq!(items.authorized_models, vec!["fs/item"]);
+    }
+
+    #[test]
+    fn it_should_create_a_working_kernel() {
+        let kernel = load_kernel_from_json(SCHEMA).expect("Error");
+        let mut hit = Hit::new("root", "fs/filesystem", kernel).expect("Error");
+        for id in ["a", "b"].iter() {
+            let mut values = LinkedHashMap::new();
+            values.insert("name".to_string(), ObjectValue::String("src".into()));
+            hit.insert("fs/folder", id, values, items("root"), None)
+                .expect("Error");
+        }
+        hit.set("a", "size", ObjectValue::I32(3)).expect("Error");
+        hit.set("a", "color", ObjectValue::String("green".into()))
+            .expect("Error");
+        hit.set("b", "name", ObjectValue::String("a_long_name".into()))
+            .expect("Error");
+        hit.set("b", "name", ObjectValue::String("src".into()))
+            .expect("Error");
+
+        assert!(hit.get_validation_errors("a", "color").is_some());
+        // unique in parent
+        assert!(hit.get_validation_errors("b", "name").is_some());
+        hit.set("b", "name", ObjectValue::String("a_long_name".into()))
+            .expect("Error");
+        let errors = hit.get_validation_errors("b", "name").expect("Error");
+        assert_eq!(errors[0].key, "MAX_LENGTH");
+    }
+
+    #[test]
+    fn it_should_report_the_path_of_the_errors() {
+        let mut schema = create_schema();
+        schema["models"]["fs/folder"]["fields"]["size"]["type"] = json!("number");
+        assert_eq!(
+            load_error(schema),
+            SchemaError::UnknownFieldType(
+                "models[\"fs/folder\"].fields[\"size\"].type".into(),
+                "number".into()
+            )
+        );
+
+        let mut schema = create_schema();
+        schema["models"]["fs/folder"]["fields"]["link"]["authorized_models"] =
+            json!(["fs/folder", "fs/file"]);
+        assert_eq!(
+            load_error(schema),
+            SchemaError::UnknownModel(
+                "models[\"fs/folder\"].fields[\"link\"].authorized_models[1]".into(),
+                "fs/file".into()
+            )
+        );
+
+        let mut schema = create_schema();
+        schema["models"]["fs/folder"]["fields"]["name"]["validators"][0]["length"] = json!(1000);
+        assert_eq!(
+            load_error(schema),
+            SchemaError::InvalidType(
+                "models[\"fs/folder\"].fields[\"name\"].validators[0].length".into(),
+                "an integer from 0 to 255".into()
+            )
+        );
+
+        let mut schema = create_schema();
+        schema["models"]["fs/folder"]["fields"]["size"]["authorized_models"] = json!([]);
+        assert_eq!(
+            load_error(schema),
+            SchemaError::UnknownProperty(
+                "models[\"fs/folder\"].fields[\"size\"].authorized_models".into()
+            )
+        );
+
+        let mut schema = create_schema();
+        schema["models"]["fs/folder"]["interfaces"] = json!(["fs/other"]);
+        assert_eq!(
+            load_error(schema),
+            SchemaError::UnknownInterface(
+                "models[\"fs/folder\"].interfaces[0]".into(),
+                "fs/other".into()
+            )
+        );
+
+        let mut schema = create_schema();
+        schema["models"]["fs/folder"]["fields"]["items"] = json!({"type": "subobject_array"});
+        assert_eq!(
+            load_error(schema),
+            SchemaError::MissingProperty(
+                "models[\"fs/folder\"].fields[\"items\"]".into(),
+                "authorized_models".into()
+            )
+        );
+
+        assert!(matches!(
+            load_kernel_from_json("{\"models\": "),
+            Err(SchemaError::InvalidSyntax(_))
+        ));
+    }
+
+    #[test]
+    fn it_should_describe_the_fields() {
+        let kernel = load_kernel_from_json(SCHEMA).expect("Error");
+        let folder = kernel.get_model("fs/folder").expect("Error");
+        let name = folder.get_field("name").expect("Error").borrow().describe();
+        assert_eq!(name.kind, FieldKind::String);
+        assert!(name.required);
+        assert_eq!(name.authorized_models, None);
+        assert_eq!(name.validators[0].name, "max_length");
+        assert_eq!(name.validators[0].arguments["length"], json!(10));
+        let link = folder.get_field("link").expect("Error").borrow().describe();
+        assert_eq!(link.kind, FieldKind::Reference);
+        assert_eq!(link.authorized_models, Some(vec!["fs/folder".to_string()]));
+        assert_eq!(
+            folder.get_field("tags").expect("Error").borrow().get_kind(),
+            FieldKind::StringArray
+        );
+    }
+
+    #[test]
+    fn it_should_export_the_schema_that_was_loaded() {
+        let kernel = load_kernel_from_json(SCHEMA).expect("Error");
+        let exported = export_schema(kernel.as_ref()).expect("Error");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&exported).unwrap(),
+            create_schema()
+        );
+        // the order of the models and fields is kept
+        let kernel = load_kernel_from_json(&exported).expect("Error");
+        assert_eq!(export_schema(kernel.as_ref()).expect("Error"), exported);
+    }
+
+    #[test]
+    fn it_should_export_the_schema_of_a_kernel() {
+        let exported = export_schema(&create_test_kernel()).expect("Error");
+        let kernel = load_kernel_from_json(&exported).expect("Error");
+        let model = kernel.get_model("test/test").expect("Error");
+        let fields: Vec<&String> = model.fields.keys().collect();
+        assert_eq!(fields, vec!["name", "sub_items", "age", "reference", "references"]);
+    }
+
+    #[test]
+    fn it_should_load_and_export_the_base_models_and_the_interfaces() {
+        let schema = r#"{
+            "version": 3,
+            "interfaces": {
+                "fs/item": {
+                    "fields": {"name": {"type": "string", "required": true}}
+                }
+            },
+            "models": {
+                "fs/root": {
+                    "label": "Root",
+                    "fields": {
+                        "items": {"type": "subobject_array", "authorized_models": ["fs/item"]}
+                    }
+                },
+                "fs/node": {
+                    "label": "Node",
+                    "interfaces": ["fs/item"],
+                    "abstract": true,
+                    "fields": {
+                        "name": {"type": "string", "required": true},
+                        "hidden": {"type": "bool"}
+                    }
+                },
+                "fs/folder": {
+                    "label": "Folder",
+                    "extends": "fs/node",
+                    "fields": {
+                        "items": {"type": "subobject_array", "authorized_models": ["fs/item"]}
+                    }
+                },
+                "fs/file": {"label": "File", "extends": "fs/node", "fields": {}}
+            }
+        }"#;
+        let kernel = load_kernel_from_json(schema).expect("Error");
+        let folder = kernel.get_model("fs/folder").expect("Error");
+        let fields: Vec<&String> = folder.fields.keys().collect();
+        assert_eq!(fields, vec!["name", "hidden", "items"]);
+        assert!(folder.implements_interface(&"fs/item".to_string()));
+        assert_eq!(kernel.get_schema_version(), 3);
+        let instantiable: Vec<&String> = kernel
+            .get_instantiable_models()
+            .iter()
+            .map(|model| model.get_name())
+            .collect();
+        assert_eq!(instantiable, vec!["fs/root", "fs/folder", "fs/file"]);
+
+        let exported = export_schema(kernel.as_ref()).expect("Error");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&exported).unwrap(),
+            serde_json::from_str::<serde_json::Value>(schema).unwrap()
+        );
+
+        let error = load_kernel_from_json(
+            &json!({
+                "interfaces": {"fs/item": {"fields": {"size": {"type": "integer"}}}},
+                "models": {"fs/file": {"interfaces": ["fs/item"], "fields": {}}}
+            })
+            .to_string(),
+        );
+        assert!(matches!(
+            error,
+            Err(SchemaError::HitError(HitError::MissingInterfaceField(..)))
+        ));
+        // the fields can all be inherited
+        let kernel = load_kernel_from_json(
+            &json!({"models": {
+                "fs/base": {"abstract": true, "fields": {"name": {"type": "string"}}},
+                "fs/file": {"extends": "fs/base"}
+            }})
+            .to_string(),
+        )
+        .expect("Error");
+        let file = kernel.get_model("fs/file").expect("Error");
+        assert!(file.get_field("name").is_some());
+        let error = load_kernel_from_json(
+            &json!({"models": {"fs/file": {"extends": "fs/base"}}}).to_string(),
+        );
+        assert_eq!(
+            error.err(),
+            Some(SchemaError::UnknownModel(
+                "models[\"fs/file\"].extends".into(),
+                "fs/base".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn it_should_load_and_export_the_defaults() {
+        let schema = r#"{
+            "interfaces": [],
+            "models": {
+                "test/test": {
+                    "label": "Test",
+                    "fields": {
+                        "name": {"type": "string", "required": true, "default": "untitled"},
+                        "size": {"type": "integer", "default": 3},
+                        "ratio": {"type": "float", "default": 0.5},
+                        "hidden": {"type": "bool", "default": false},
+                        "tags": {"type": "string_array", "default": ["a"]}
+                    }
+                }
+            }
+        }"#;
+        let kernel = load_kernel_from_json(schema).expect("Error");
+        let hit = Hit::new("id", "test/test", kernel.clone()).expect("Error");
+        assert_eq!(
+            hit.get_value("id", "name"),
+            Some(ObjectValue::String("untitled".into()))
+        );
+        assert_eq!(hit.get_value("id", "size"), Some(ObjectValue::I32(3)));
+        assert_eq!(hit.get_value("id", "ratio"), Some(ObjectValue::F32(0.5)));
+        assert_eq!(hit.get_value("id", "hidden"), Some(ObjectValue::Bool(false)));
+        assert_eq!(
+            hit.get_value("id", "tags"),
+            Some(ObjectValue::VecString(vec!["a".into()]))
+        );
+        let exported = export_schema(kernel.as_ref()).expect("Error");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&exported).unwrap(),
+            serde_json::from_str::<serde_json::Value>(schema).unwrap()
+        );
+
+        let error = load_kernel_from_json(
+            &json!({"models": {"test/test": {"fields": {"size": {"type": "integer", "default": 1.5}}}}})
+                .to_string(),
+        );
+        assert_eq!(
+            error.err(),
+            Some(SchemaError::InvalidType(
+                "models[\"test/test\"].fields[\"size\"].default".into(),
+                "an integer".into()
+            ))
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn it_should_load_a_yaml_schema() {
+        let schema = "
+models:
+  test/test:
+    label: Test
+    fields:
+      name:
+        type: string
+        required: true
+      sub_items:
+        type: subobject_array
+        authorized_models: [test/test]
+";
+        let kernel = crate::schema::load_kernel_from_yaml(schema).expect("Error");
+        let model = kernel.get_model("test/test").expect("Error");
+        let fields: Vec<&String> = model.fields.keys().collect();
+        assert_eq!(fields, vec!["name", "sub_items"]);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn it_should_load_a_toml_schema() {
+        let schema = r#"
+[models."test/test"]
+label = "Test"
+
+[models."test/test".fields.name]
+type = "string"
+required = true
+
+[models."test/test".fields.sub_items]
+type = "subobject_array"
+authorized_models = ["test/test"]
+"#;
+        let kernel = crate::schema::load_kernel_from_toml(schema).expect("Error");
+        let model = kernel.get_model("test/test").expect("Error");
+        let fields: Vec<&String> = model.fields.keys().collect();
+        assert_eq!(fields, vec!["name", "sub_items"]);
+    }
+}
@@ -0,0 +1,384 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Bound;
+use std::rc::Rc;
+
+use crate::model::{FieldKind, Model};
+use crate::object_data::{Id, ObjectValue, ObjectValues};
+use crate::utils::UndoLog;
+use crate::{HitError, IndexEntryProperty};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SecondaryIndexKind {
+    // the objects whose field has a value
+    Exact,
+    // the objects whose field is between two values, on integer, float and date fields
+    Range,
+    // the objects whose field starts with a string, on string fields
+    Prefix,
+}
+
+impl SecondaryIndexKind {
+    fn accepts(&self, kind: &FieldKind) -> bool {
+        match self {
+            SecondaryIndexKind::Exact => matches!(
+                kind,
+                FieldKind::Bool
+                    | FieldKind::Date
+                    | FieldKind::Integer
+                    | FieldKind::Float
+                    | FieldKind::String
+            ),
+            SecondaryIndexKind::Range => {
+                matches!(
+                    kind,
+                    FieldKind::Integer | FieldKind::Float | FieldKind::Date
+                )
+            }
+            SecondaryIndexKind::Prefix => *kind == FieldKind::String,
+        }
+    }
+}
+
+// An index of the values of a field, for the objects of a model and of the models
+// extending it or implementing it
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecondaryIndexDefinition {
+    pub name: String,
+    pub model: String,
+    pub field: String,
+    pub kind: SecondaryIndexKind,
+}
+
+impl SecondaryIndexDefinition {
+    pub(crate) fn check(&self, models: &HashMap<String, Rc<Model>>) -> Result<(), HitError> {
+        let error = |reason: String| HitError::InvalidSecondaryIndex(self.name.clone(), reason);
+        let indexed_models: Vec<&Rc<Model>> = models
+            .values()
+            .filter(|model| model.is_a(&self.model))
+            .collect();
+        if indexed_models.is_empty() {
+            return Err(error(format!("unknown model `{}`", self.model)));
+        }
+        for model in indexed_models.iter() {
+            let kind = match model.get_field(&self.field) {
+                Some(field) => field.borrow().get_kind(),
+                None => {
+                    return Err(error(format!(
+                        "model `{}` has no field `{}`",
+                        model.get_name(),
+                        self.field
+                    )))
+                }
+            };
+            if !self.kind.accepts(&kind) {
+                return Err(error(format!(
+                    "a {:?} index cannot be used on the {} field `{}`",
+                    self.kind,
+                    kind.get_name(),
+                    self.field
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+// the values are compared by type first, the integers and the floats are both numbers
+#[derive(Clone, Debug)]
+enum IndexKey {
+    Bool(bool),
+    Number(f64),
+    Date(i64, u32),
+    String(String),
+}
+
+impl IndexKey {
+    fn from_value(value: &ObjectValue) -> Option<IndexKey> {
+        match value {
+            ObjectValue::Bool(value) => Some(IndexKey::Bool(*value)),
+            ObjectValue::I32(value) => Some(IndexKey::Number(*value as f64)),
+            ObjectValue::F32(value) => Some(IndexKey::Number(*value as f64)),
+            ObjectValue::Date(date) => {
+                Some(IndexKey::Date(date.get_timestamp(), date.get_nanoseconds()))
+            }
+            ObjectValue::String(value) => Some(IndexKey::String(value.clone())),
+            _ => None,
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            IndexKey::Bool(_) => 0,
+            IndexKey::Number(_) => 1,
+            IndexKey::Date(..) => 2,
+            IndexKey::String(_) => 3,
+        }
+    }
+}
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (IndexKey::Bool(a), IndexKey::Bool(b)) => a.cmp(b),
+            (IndexKey::Number(a), IndexKey::Number(b)) => a.total_cmp(b),
+            (IndexKey::Date(a, a_nanoseconds), IndexKey::Date(b, b_nanoseconds)) => {
+                (a, a_nanoseconds).cmp(&(b, b_nanoseconds))
+            }
+            (IndexKey::String(a), IndexKey::String(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexKey {}
+
+type IndexValues = BTreeMap<IndexKey, BTreeSet<Id>>;
+
+// A change made to the values of an index during a transaction, undone on rollback
+#[derive(Clone, Debug)]
+enum SecondaryIndexChange {
+    Added(String, IndexKey, Id),
+    Removed(String, IndexKey, Id),
+}
+
+// The values of the secondary indexes declared in the kernel, see
+// `KernelBuilder::add_secondary_index`. Each `Hit` keeps its own values, built from the
+// definitions of the kernel. Only the scalar values are indexed, the null values are not.
+#[derive(Clone, Debug)]
+pub struct SecondaryIndexes {
+    definitions: Vec<SecondaryIndexDefinition>,
+    values: HashMap<String, IndexValues>,
+    undo_log: UndoLog<SecondaryIndexChange>,
+}
+
+impl SecondaryIndexes {
+    pub fn new(definitions: Vec<SecondaryIndexDefinition>) -> Self {
+        SecondaryIndexes {
+            definitions,
+            values: HashMap::new(),
+            undo_log: UndoLog::new(),
+        }
+    }
+
+    pub fn get_definitions(&self) -> &Vec<SecondaryIndexDefinition> {
+        &self.definitions
+    }
+
+    fn get_definition(&self, name: &str) -> Result<&SecondaryIndexDefinition, HitError> {
+        self.definitions
+            .iter()
+            .find(|definition| definition.name == name)
+            .ok_or_else(|| HitError::SecondaryIndexNotFound(name.to_string()))
+    }
+
+    fn get_values(&self, name: &str) -> Option<&IndexValues> {
+        self.values.get(name)
+    }
+
+    // the ids of the objects whose field has the value, or starts with it for a prefix index
+    pub fn lookup(&self, name: &str, value: &ObjectValue) -> Result<Vec<Id>, HitError> {
+        let definition = self.get_definition(name)?;
+        let (values, key) = match (self.get_values(name), IndexKey::from_value(value)) {
+            (Some(values), Some(key)) => (values, key),
+            _ => return Ok(vec![]),
+        };
+        let ids = match (definition.kind, &key) {
+            (SecondaryIndexKind::Prefix, IndexKey::String(prefix)) => values
+                .range(&key..)
+                .take_while(|(key, _)| match key {
+                    IndexKey::String(value) => value.starts_with(prefix.as_str()),
+                    _ => false,
+                })
+                .flat_map(|(_, ids)| ids.iter().cloned())
+                .collect(),
+            _ => values
+                .get(&key)
+                .map(|ids| ids.iter().cloned().collect())
+                .unwrap_or_default(),
+        };
+        Ok(ids)
+    }
+
+    // the ids of the objects whose field is between the bounds, in the order of the values
+    pub fn range(
+        &self,
+        name: &str,
+        min: Bound<&ObjectValue>,
+        max: Bound<&ObjectValue>,
+    ) -> Result<Vec<Id>, HitError> {
+        let definition = self.get_definition(name)?;
+        if definition.kind != SecondaryIndexKind::Range {
+            return Err(HitError::InvalidSecondaryIndex(
+                name.to_string(),
+                "only the range indexes can be read by range".into(),
+            ));
+        }
+        let to_key = |bound: Bound<&ObjectValue>| -> Result<Bound<IndexKey>, HitError> {
+            let key = |value: &ObjectValue| {
+                IndexKey::from_value(value).ok_or_else(|| {
+                    HitError::InvalidSecondaryIndex(
+                        name.to_string(),
+                        "the bounds must be numbers or dates".into(),
+                    )
+                })
+            };
+            Ok(match bound {
+                Bound::Included(value) => Bound::Included(key(value)?),
+                Bound::Excluded(value) => Bound::Excluded(key(value)?),
+                Bound::Unbounded => Bound::Unbounded,
+            })
+        };
+        let (min, max) = (to_key(min)?, to_key(max)?);
+        let values = match self.get_values(name) {
+            Some(values) => values,
+            None => return Ok(vec![]),
+        };
+        // `BTreeMap::range` panics on the empty ranges
+        let is_empty = match (&min, &max) {
+            (Bound::Included(min), Bound::Included(max)) => min > max,
+            (Bound::Included(min), Bound::Excluded(max))
+            | (Bound::Excluded(min), Bound::Included(max))
+            | (Bound::Excluded(min), Bound::Excluded(max)) => min >= max,
+            _ => false,
+        };
+        if is_empty {
+            return Ok(vec![]);
+        }
+        Ok(values
+            .range((min, max))
+            .flat_map(|(_, ids)| ids.iter().cloned())
+            .collect())
+    }
+
+    fn add_value(&mut self, name: &str, id: &str, value: &ObjectValue) {
+        if let Some(key) = IndexKey::from_value(value) {
+            let is_added = self
+                .values
+                .entry(name.to_string())
+                .or_default()
+                .entry(key.clone())
+                .or_default()
+                .insert(id.to_string());
+            if is_added {
+                self.undo_log.record(SecondaryIndexChange::Added(
+                    name.to_string(),
+                    key,
+                    id.to_string(),
+                ));
+            }
+        }
+    }
+
+    fn remove_value(&mut self, name: &str, id: &str, value: &ObjectValue) {
+        let (values, key) = match (self.values.get_mut(name), IndexKey::from_value(value)) {
+            (Some(values), Some(key)) => (values, key),
+            _ => return,
+        };
+        let is_removed = match values.get_mut(&key) {
+            Some(ids) => {
+                let is_removed = ids.remove(id);
+                if ids.is_empty() {
+                    values.remove(&key);
+                }
+                is_removed
+            }
+            None => false,
+        };
+        if is_removed {
+            self.undo_log.record(SecondaryIndexChange::Removed(
+                name.to_string(),
+                key,
+                id.to_string(),
+            ));
+        }
+    }
+
+    // the names and the fields of the indexes of a model
+    fn get_model_indexes(&self, model: &Model) -> Vec<(String, String)> {
+        self.definitions
+            .iter()
+            .filter(|definition| model.is_a(&definition.model))
+            .map(|definition| (definition.name.clone(), definition.field.clone()))
+            .collect()
+    }
+
+    pub(crate) fn add_entry(&mut self, model: &Model, id: &str, data: &ObjectValues) {
+        for (name, field) in self.get_model_indexes(model).iter() {
+            if let Some(value) = data.get(field) {
+                self.add_value(name, id, value);
+            }
+        }
+    }
+
+    pub(crate) fn remove_entry(&mut self, model: &Model, id: &str, data: &ObjectValues) {
+        for (name, field) in self.get_model_indexes(model).iter() {
+            if let Some(value) = data.get(field) {
+                self.remove_value(name, id, value);
+            }
+        }
+    }
+
+    pub(crate) fn set_value(
+        &mut self,
+        model: &Model,
+        property: &IndexEntryProperty,
+        value: &ObjectValue,
+        old_value: &Option<ObjectValue>,
+    ) {
+        for (name, field) in self.get_model_indexes(model).iter() {
+            if *field != property.property {
+                continue;
+            }
+            if let Some(old_value) = old_value {
+                self.remove_value(name, &property.id, old_value);
+            }
+            self.add_value(name, &property.id, value);
+        }
+    }
+
+    pub(crate) fn begin_transaction(&mut self) {
+        self.undo_log.begin();
+    }
+
+    pub(crate) fn commit_transaction(&mut self) {
+        self.undo_log.commit();
+    }
+
+    pub(crate) fn rollback_transaction(&mut self) {
+        for change in self.undo_log.rollback() {
+            match change {
+                SecondaryIndexChange::Added(name, key, id) => {
+                    if let Some(values) = self.values.get_mut(&name) {
+                        if let Some(ids) = values.get_mut(&key) {
+                            ids.remove(&id);
+                            if ids.is_empty() {
+                                values.remove(&key);
+                            }
+                        }
+                    }
+                }
+                SecondaryIndexChange::Removed(name, key, id) => {
+                    self.values
+                        .entry(name)
+                        .or_default()
+                        .entry(key)
+                        .or_default()
+                        .insert(id);
+                }
+            }
+        }
+    }
+}
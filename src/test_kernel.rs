@@ -1,35 +1,42 @@
+use crate::events::FieldListener;
 use crate::kernel::Kernel;
+use crate::model::field_types::{
+    FieldTypeBool, FieldTypeDate, FieldTypeInteger, FieldTypeStringVec, FieldTypeSubobject,
+};
 use crate::model::field_types::FieldTypeReferenceArray;
 use crate::model::field_types::FieldTypeString;
 use crate::model::field_types::FieldTypeSubobjectArray;
 use crate::model::Model;
 use crate::plugins::Plugins;
+use crate::import::{import_from_patches_and_entries, AddedEntry};
+use crate::index::IndexEntryProperty;
+use crate::object_data::{DateTimeUtc, ObjectValue, ObjectValues, Reference};
 use crate::HitError;
+use crate::{modele, Hit, HitKernel, KernelBuilder, LinkedHashMap};
 use crate::{field_types::FieldTypeFloat, model::field_types::FieldTypeReference};
 use std::cell::RefCell;
 use std::rc::Rc;
 
 pub struct TestKernel {
-    model: Rc<Model>,
+    models: Vec<Rc<Model>>,
 }
 
 impl Kernel for TestKernel {
     fn get_model(&self, name: &str) -> Result<Rc<Model>, HitError> {
-        if name == "test/test" {
-            return Ok(self.model.clone());
-        } else {
-            return Err(HitError::ModelDoesNotExist(String::from(name)));
+        match self.models.iter().find(|model| model.get_name() == name) {
+            Some(model) => Ok(model.clone()),
+            None => Err(HitError::ModelDoesNotExist(String::from(name))),
         }
     }
 
     fn get_instantiable_models(&self) -> Vec<&Model> {
-        return vec![&self.model];
+        return self.models.iter().map(|model| model.as_ref()).collect();
     }
     fn get_plugins(&self) -> Plugins {
         Plugins::new()
     }
     fn get_models(&self) -> Vec<String> {
-        vec!["test/test".to_string()]
+        self.models.iter().map(|model| model.get_name().clone()).collect()
     }
 }
 
@@ -78,6 +85,245 @@ pub fn create_test_kernel() -> TestKernel {
         })),
     );
     return TestKernel {
-        model: Rc::new(model),
+        models: vec![Rc::new(model)],
     };
 }
+
+// adds a model with a field for each type of value
+pub fn create_test_kernel_with_all_types() -> TestKernel {
+    let mut kernel = create_test_kernel();
+    let mut model = Model::new(String::from("test/values"), String::from("Values"));
+    model.fields.insert(
+        String::from("bool"),
+        Rc::new(RefCell::new(FieldTypeBool {
+            name: String::from("bool"),
+            required: false,
+            validators: vec![],
+        })),
+    );
+    model.fields.insert(
+        String::from("date"),
+        Rc::new(RefCell::new(FieldTypeDate {
+            name: String::from("date"),
+            required: false,
+            validators: vec![],
+        })),
+    );
+    model.fields.insert(
+        String::from("integer"),
+        Rc::new(RefCell::new(FieldTypeInteger {
+            name: String::from("integer"),
+            required: false,
+            validators: vec![],
+        })),
+    );
+    model.fields.insert(
+        String::from("float"),
+        Rc::new(RefCell::new(FieldTypeFloat {
+            name: String::from("float"),
+            required: false,
+            validators: vec![],
+        })),
+    );
+    model.fields.insert(
+        String::from("string"),
+        Rc::new(RefCell::new(FieldTypeString {
+            name: String::from("string"),
+            required: false,
+            validators: vec![],
+            _enum: None,
+        })),
+    );
+    model.fields.insert(
+        String::from("strings"),
+        Rc::new(RefCell::new(FieldTypeStringVec {
+            name: String::from("strings"),
+            required: false,
+            validators: vec![],
+            _enum: None,
+        })),
+    );
+    model.fields.insert(
+        String::from("reference"),
+        Rc::new(RefCell::new(FieldTypeReference {
+            name: String::from("reference"),
+            required: false,
+            validators: vec![],
+            authorized_models: vec!["test/test".into()],
+        })),
+    );
+    model.fields.insert(
+        String::from("sub_item"),
+        Rc::new(RefCell::new(FieldTypeSubobject {
+            name: String::from("sub_item"),
+            required: false,
+            validators: vec![],
+            authorized_models: vec!["test/test".into()],
+        })),
+    );
+    model.fields.insert(
+        String::from("sub_values"),
+        Rc::new(RefCell::new(FieldTypeSubobjectArray {
+            name: String::from("sub_values"),
+            validators: vec![],
+            authorized_models: vec!["test/values".into()],
+        })),
+    );
+    kernel.models.push(Rc::new(model));
+    kernel
+}
+
+fn entry(id: &str, model: &str, parent: Option<(&str, &str)>, data: ObjectValues) -> AddedEntry {
+    AddedEntry {
+        id: id.into(),
+        data,
+        parent: parent.map(|(id, property)| IndexEntryProperty {
+            id: id.into(),
+            property: property.into(),
+        }),
+        model: model.into(),
+    }
+}
+
+fn reference(id: &str) -> Reference {
+    Reference { id: id.into() }
+}
+
+// a document using every type of value, with subobjects and references
+pub fn create_hit_with_all_types() -> Hit {
+    let mut values = ObjectValues::new();
+    values.insert("bool".into(), ObjectValue::Bool(true));
+    values.insert("date".into(), ObjectValue::Date(DateTimeUtc::from_timestamp_nanos(-5, 123_456_789)));
+    values.insert("integer".into(), ObjectValue::I32(i32::MIN));
+    values.insert("float".into(), ObjectValue::F32(-1.5));
+    values.insert("string".into(), ObjectValue::String("énorme".into()));
+    values.insert(
+        "strings".into(),
+        ObjectValue::VecString(vec!["a".into(), "".into()]),
+    );
+    values.insert("reference".into(), ObjectValue::Reference(reference("id3")));
+    values.insert("sub_item".into(), ObjectValue::SubObject(reference("id2")));
+    let mut id2 = ObjectValues::new();
+    // the values are not in the order of the model
+    id2.insert("reference".into(), ObjectValue::Null);
+    id2.insert(
+        "sub_items".into(),
+        ObjectValue::VecSubObjects(vec![reference("id4"), reference("id3")]),
+    );
+    id2.insert(
+        "references".into(),
+        ObjectValue::VecReference(vec![reference("id4"), reference("id3")]),
+    );
+    id2.insert("name".into(), ObjectValue::String("id2".into()));
+    let mut id3 = ObjectValues::new();
+    id3.insert("age".into(), ObjectValue::F32(12.0));
+
+    let mut entries = LinkedHashMap::new();
+    for entry in [
+        entry("id", "test/values", None, values),
+        entry("id2", "test/test", Some(("id", "sub_item")), id2),
+        entry("id4", "test/test", Some(("id2", "sub_items")), ObjectValues::new()),
+        entry("id3", "test/test", Some(("id2", "sub_items")), id3),
+    ] {
+        entries.insert(entry.id.clone(), entry);
+    }
+    import_from_patches_and_entries(
+        vec![],
+        "id",
+        Rc::new(create_test_kernel_with_all_types()),
+        &mut entries,
+    )
+    .expect("Error")
+}
+
+// the subobjects of an object of `create_test_kernel`
+pub fn sub_items(id: &str) -> IndexEntryProperty {
+    IndexEntryProperty {
+        id: id.into(),
+        property: "sub_items".into(),
+    }
+}
+
+// keeps the values it was notified of
+pub struct TestListener {
+    pub values: Vec<ObjectValue>,
+}
+
+impl FieldListener<ObjectValue> for TestListener {
+    fn on_update(&mut self, value: &ObjectValue) {
+        self.values.push(value.clone());
+    }
+
+    fn get_unique_id(&self) -> &str {
+        "listener"
+    }
+}
+
+// fs/folder and fs/file implement fs/item : a folder has items and a single readme,
+// a file has a required name, a size, a link and tags
+pub fn create_fs_kernel_builder() -> KernelBuilder {
+    KernelBuilder::new()
+        .add_interface("fs/item")
+        .add_model(modele!("fs/folder", "Folder" =>
+            "name": FieldTypeString {},
+            "items": FieldTypeSubobjectArray {
+                authorized_models: vec!["fs/item".into()]
+            },
+            "readme": FieldTypeSubobject {
+                authorized_models: vec!["fs/file".into()]
+            },
+            interfaces: "fs/item"
+        ))
+        .add_model(modele!("fs/file", "File" =>
+            "name": FieldTypeString {
+                required: true
+            },
+            "size": FieldTypeInteger {},
+            "link": FieldTypeReference {
+                authorized_models: vec!["fs/item".into()]
+            },
+            "tags": FieldTypeReferenceArray {
+                authorized_models: vec!["fs/item".into()]
+            },
+            interfaces: "fs/item"
+        ))
+        .mark_instantiable("fs/folder")
+}
+
+pub fn insert_fs_object(hit: &mut Hit, model: &str, id: &str, name: &str, parent: (&str, &str)) {
+    let mut values = LinkedHashMap::new();
+    values.insert("name".to_string(), ObjectValue::String(name.into()));
+    let target = IndexEntryProperty {
+        id: parent.0.into(),
+        property: parent.1.into(),
+    };
+    hit.insert(model, id, values, target, None).expect("Error");
+}
+
+// root
+// ├── items : src (items : main.rs, lib.rs), docs (items : guide.md)
+// └── readme : README
+// guide.md links to lib.rs and has main.rs as a tag, it has no size
+pub fn create_fs_hit(kernel: Rc<HitKernel>) -> Hit {
+    let mut values = LinkedHashMap::new();
+    values.insert("name".to_string(), ObjectValue::String("root".into()));
+    let mut hit = Hit::new_with_values("root", kernel, values, "fs/folder").expect("Error");
+    // the single subobject field is filled first, the objects follow the order of the fields
+    insert_fs_object(&mut hit, "fs/file", "readme", "README", ("root", "readme"));
+    insert_fs_object(&mut hit, "fs/folder", "src", "src", ("root", "items"));
+    insert_fs_object(&mut hit, "fs/file", "main", "main.rs", ("src", "items"));
+    insert_fs_object(&mut hit, "fs/file", "lib", "lib.rs", ("src", "items"));
+    insert_fs_object(&mut hit, "fs/folder", "docs", "docs", ("root", "items"));
+    insert_fs_object(&mut hit, "fs/file", "guide", "guide.md", ("docs", "items"));
+    for (id, size) in [("readme", 20), ("main", 120), ("lib", 40)].iter() {
+        hit.set(id, "size", ObjectValue::I32(*size)).expect("Error");
+    }
+    hit.set("guide", "link", ObjectValue::Reference(reference("lib")))
+        .expect("Error");
+    let tags = IndexEntryProperty {
+        id: "guide".into(),
+        property: "tags".into(),
+    };
+    hit.insert_reference("main", tags, None).expect("Error");
+    hit
+}
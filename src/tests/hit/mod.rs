@@ -1,4 +1,5 @@
-mod test_copy_object;
+mod test_backlinks;
+pub(super) mod test_copy_object;
 mod test_create;
 mod test_get_references;
 mod test_insert_reference;
@@ -8,7 +9,23 @@ mod test_move_reference;
 mod test_remove_nested;
 mod test_remove_object;
 mod test_remove_reference;
+mod test_computed;
+mod test_defaults;
+mod test_path;
+mod test_query;
+mod test_secondary_index;
+mod test_set_values;
+mod test_subobject_slot;
+mod test_traversal;
 
 mod test_clone_hit;
 
-mod test_import_hit;
\ No newline at end of file
+mod test_import_hit;
+
+mod test_transaction;
+
+pub(super) mod test_history;
+
+mod test_operation_feed;
+
+mod test_apply_operations;
@@ -0,0 +1,165 @@
+use linked_hash_map::LinkedHashMap;
+
+use crate::test_kernel::sub_items;
+use crate::tests::hit::test_copy_object::create_hit_with_subobjects;
+use crate::tests::hit::test_history::dump;
+use crate::{
+    export, import_with_operation_log, FieldListener, Hit, HitError, IndexEntryProperty,
+    ObjectValue, Operation, OperationRecord,
+};
+use std::{cell::RefCell, rc::Rc};
+
+struct LogListener {
+    id: String,
+    records: Vec<OperationRecord>,
+}
+
+impl FieldListener<OperationRecord> for LogListener {
+    fn on_update(&mut self, value: &OperationRecord) {
+        self.records.push(value.clone());
+    }
+    fn get_unique_id(&self) -> &str {
+        &self.id
+    }
+}
+
+fn make_changes(hit: &mut Hit) {
+    hit.set("id2", "name", ObjectValue::String("a".into()))
+        .expect("Error");
+    hit.insert(
+        "test/test",
+        "id6",
+        LinkedHashMap::new(),
+        sub_items("id"),
+        Some("id2".into()),
+    )
+    .expect("Error");
+    hit.move_object("id5", sub_items("id6"), None)
+        .expect("Error");
+    hit.copy_object("id3".into(), sub_items("id6"), Some("id5".into()))
+        .expect("Error");
+    hit.insert_reference(
+        "id6",
+        IndexEntryProperty {
+            id: "id4".into(),
+            property: "references".into(),
+        },
+        Some("id2".into()),
+    )
+    .expect("Error");
+    // id5 was moved out of id3 and references it
+    hit.set("id5", "reference", ObjectValue::Null).expect("Error");
+    hit.remove_reference(
+        "id3",
+        IndexEntryProperty {
+            id: "id5".into(),
+            property: "references".into(),
+        },
+    )
+    .expect("Error");
+    hit.remove_object("id3").expect("Error");
+}
+
+// the changes made to a document, and the log of these changes
+fn create_log() -> (Hit, Vec<OperationRecord>) {
+    let mut hit = create_hit_with_subobjects();
+    let listener = Rc::new(RefCell::new(LogListener {
+        id: "log".into(),
+        records: vec![],
+    }));
+    hit.subscribe_operations(listener.clone());
+    make_changes(&mut hit);
+    let records = listener.borrow().records.clone();
+    (hit, records)
+}
+
+#[test]
+fn it_should_replay_operations_on_another_instance() {
+    let (source, records) = create_log();
+    let mut hit = create_hit_with_subobjects();
+    let operations: Vec<Operation> = records.iter().map(|r| r.operation.clone()).collect();
+
+    hit.apply_operations(&operations).expect("Error");
+
+    // the copies have the same ids
+    assert_eq!(dump(&hit), dump(&source));
+}
+
+#[test]
+fn it_should_not_apply_any_operation_when_one_fails() {
+    let (_, records) = create_log();
+    let mut hit = create_hit_with_subobjects();
+    let initial_state = dump(&hit);
+    let mut operations: Vec<Operation> = records.iter().map(|r| r.operation.clone()).collect();
+    operations.push(Operation::Set {
+        id: "id2".into(),
+        property: "name".into(),
+        value: ObjectValue::F32(1.0),
+        old_value: ObjectValue::Null,
+    });
+
+    let result = hit.apply_operations(&operations);
+
+    assert_eq!(result, Err(HitError::InvalidDataType()));
+    assert_eq!(dump(&hit), initial_state);
+}
+
+#[test]
+fn it_should_apply_a_log_only_once() {
+    let (source, records) = create_log();
+    let mut hit = create_hit_with_subobjects();
+    // both documents were built with the same operations
+    assert_eq!(hit.get_operation_sequence(), records[0].sequence - 1);
+
+    assert_eq!(hit.apply_log(&records[..2]), Ok(2));
+    assert_eq!(hit.apply_log(&records), Ok(records.len() - 2));
+    assert_eq!(hit.apply_log(&records), Ok(0));
+
+    assert_eq!(dump(&hit), dump(&source));
+    assert_eq!(
+        hit.get_operation_sequence(),
+        source.get_operation_sequence()
+    );
+}
+
+#[test]
+fn it_should_refuse_a_log_with_missing_records() {
+    let (_, records) = create_log();
+    let mut hit = create_hit_with_subobjects();
+    let initial_state = dump(&hit);
+    let expected_sequence = hit.get_operation_sequence() + 1;
+
+    let result = hit.apply_log(&records[1..]);
+
+    assert_eq!(
+        result,
+        Err(HitError::InvalidOperationSequence(
+            expected_sequence,
+            expected_sequence + 1
+        ))
+    );
+    assert_eq!(dump(&hit), initial_state);
+}
+
+#[test]
+fn it_should_import_a_json_export_and_a_log() {
+    let mut source = create_hit_with_subobjects();
+    let base = export(&source).expect("Error");
+    let sequence = source.get_operation_sequence();
+    let listener = Rc::new(RefCell::new(LogListener {
+        id: "log".into(),
+        records: vec![],
+    }));
+    source.subscribe_operations(listener.clone());
+    make_changes(&mut source);
+    let records = listener.borrow().records.clone();
+
+    let hit = import_with_operation_log(&base, sequence, &records, source.kernel.clone())
+        .expect("Error");
+
+    assert_eq!(export(&hit), export(&source));
+    assert_eq!(
+        hit.get_operation_sequence(),
+        source.get_operation_sequence()
+    );
+}
@@ -0,0 +1,219 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::field_types::{
+    FieldTypeBacklinks, FieldTypeReference, FieldTypeReferenceArray, FieldTypeString,
+    FieldTypeSubobjectArray,
+};
+use crate::json::export::export;
+use crate::json::import::import;
+use crate::test_kernel::TestListener;
+use crate::{
+    modele, Hit, HitError, HitKernel, IndexEntryProperty, KernelBuilder, LinkedHashMap,
+    ObjectValue, Reference, TraversalOrder,
+};
+
+fn create_kernel() -> Rc<HitKernel> {
+    let kernel = KernelBuilder::new()
+        .add_model(modele!("test/folder", "Folder" =>
+            "items": FieldTypeSubobjectArray {
+                authorized_models: vec!["test/file".into()]
+            }
+        ))
+        .add_model(modele!("test/file", "File" =>
+            "name": FieldTypeString {},
+            "link": FieldTypeReference {
+                authorized_models: vec!["test/file".into()]
+            },
+            "links": FieldTypeReferenceArray {
+                authorized_models: vec!["test/file".into()]
+            },
+            "linked_by": FieldTypeBacklinks {
+                source_model: "test/file".into(),
+                source_field: "link".into(),
+                exported: true
+            },
+            "listed_by": FieldTypeBacklinks {
+                source_model: "test/file".into(),
+                source_field: "links".into()
+            }
+        ))
+        .mark_instantiable("test/folder")
+        .build()
+        .expect("Error");
+    Rc::new(kernel)
+}
+
+fn create_hit() -> Hit {
+    let mut hit = Hit::new("id", "test/folder", create_kernel()).expect("Error");
+    for id in ["a", "b", "c"].iter() {
+        hit.insert(
+            "test/file",
+            id,
+            LinkedHashMap::new(),
+            IndexEntryProperty {
+                id: "id".into(),
+                property: "items".into(),
+            },
+            None,
+        )
+        .expect("Error");
+    }
+    hit
+}
+
+fn reference(id: &str) -> ObjectValue {
+    ObjectValue::Reference(Reference { id: id.into() })
+}
+
+fn references(ids: &[&str]) -> ObjectValue {
+    ObjectValue::VecReference(
+        ids.iter()
+            .map(|id| Reference { id: id.to_string() })
+            .collect(),
+    )
+}
+
+#[test]
+fn it_should_read_the_backlinks_and_notify_their_listeners() {
+    let mut hit = create_hit();
+    let listener = Rc::new(RefCell::new(TestListener { values: vec![] }));
+    hit.subscribe_field("c", "linked_by", listener.clone())
+        .expect("Error");
+    assert_eq!(hit.get_value("c", "linked_by"), Some(references(&[])));
+
+    hit.set("a", "link", reference("c")).expect("Error");
+    hit.set("b", "link", reference("c")).expect("Error");
+    assert_eq!(
+        hit.get_value("c", "linked_by"),
+        Some(references(&["a", "b"]))
+    );
+    // the other backlinks field reads another source field
+    assert_eq!(hit.get_value("c", "listed_by"), Some(references(&[])));
+
+    hit.set("a", "link", ObjectValue::Null).expect("Error");
+    hit.remove_object("b").expect("Error");
+    assert_eq!(
+        listener.borrow().values,
+        vec![
+            references(&["a"]),
+            references(&["a", "b"]),
+            references(&["b"]),
+            references(&[])
+        ]
+    );
+}
+
+#[test]
+fn it_should_update_the_backlinks_of_reference_arrays() {
+    let mut hit = create_hit();
+    let listener = Rc::new(RefCell::new(TestListener { values: vec![] }));
+    hit.subscribe_field("c", "listed_by", listener.clone())
+        .expect("Error");
+    let target = |id: &str| IndexEntryProperty {
+        id: id.into(),
+        property: "links".into(),
+    };
+    hit.insert_reference("c", target("a"), None).expect("Error");
+    hit.insert_reference("c", target("b"), None).expect("Error");
+    hit.remove_reference("c", target("a")).expect("Error");
+    assert_eq!(hit.get_value("c", "listed_by"), Some(references(&["b"])));
+    assert_eq!(
+        listener.borrow().values,
+        vec![
+            references(&["a"]),
+            references(&["a", "b"]),
+            references(&["b"])
+        ]
+    );
+
+    // the events of a transaction are sent once, with the final value
+    listener.borrow_mut().values.clear();
+    hit.transaction(|hit| {
+        hit.insert_reference("c", target("a"), None)?;
+        hit.remove_reference("c", target("b"))
+    })
+    .expect("Error");
+    assert_eq!(listener.borrow().values, vec![references(&["a"])]);
+}
+
+#[test]
+fn it_should_read_the_backlinks_from_the_entries() {
+    let mut hit = create_hit();
+    hit.set("a", "link", reference("c")).expect("Error");
+    hit.insert_reference(
+        "c",
+        IndexEntryProperty {
+            id: "b".into(),
+            property: "links".into(),
+        },
+        None,
+    )
+    .expect("Error");
+    let entry = hit.get("c").expect("Error");
+    assert_eq!(entry.get("linked_by"), references(&["a"]));
+    assert_eq!(entry.get_data().get("listed_by"), Some(&references(&["b"])));
+
+    // the entries of the traversal iterators read the same values as get_value
+    for item in hit.descendants("id", TraversalOrder::DepthFirst) {
+        let id = item.get_id();
+        for property in ["linked_by", "listed_by"].iter() {
+            assert_eq!(Some(item.entry.get(property)), hit.get_value(&id, property));
+        }
+    }
+}
+
+#[test]
+fn it_should_not_set_the_backlinks() {
+    let mut hit = create_hit();
+    assert_eq!(
+        hit.set("a", "linked_by", references(&["b"])),
+        Err(HitError::CannotSetBacklinksField("linked_by".into()))
+    );
+}
+
+#[test]
+fn it_should_only_export_the_exported_backlinks() {
+    let mut hit = create_hit();
+    hit.set("a", "link", reference("c")).expect("Error");
+    hit.insert_reference(
+        "c",
+        IndexEntryProperty {
+            id: "b".into(),
+            property: "links".into(),
+        },
+        None,
+    )
+    .expect("Error");
+
+    let document = export(&hit).expect("Error");
+    let exported_c = document["data"]
+        .as_array()
+        .expect("Error")
+        .iter()
+        .find(|object| object["id"] == "c")
+        .expect("Error")
+        .clone();
+    assert_eq!(
+        exported_c["data"]["linked_by"],
+        serde_json::json!({"type": "reference_array", "value": [{"id": "a"}]})
+    );
+    assert_eq!(exported_c["data"].get("listed_by"), None);
+
+    let hit = import(&document, create_kernel()).expect("Error");
+    assert_eq!(hit.get_value("c", "linked_by"), Some(references(&["a"])));
+    assert_eq!(hit.get_value("c", "listed_by"), Some(references(&["b"])));
+    assert_eq!(
+        hit.get_references("c").expect("Error"),
+        vec![
+            IndexEntryProperty {
+                id: "a".into(),
+                property: "link".into()
+            },
+            IndexEntryProperty {
+                id: "b".into(),
+                property: "links".into()
+            }
+        ]
+    );
+}
@@ -0,0 +1,278 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::field_types::{
+    ComputedDependency, FieldTypeComputed, FieldTypeInteger, FieldTypeReference, FieldTypeString,
+    FieldTypeSubobjectArray,
+};
+use crate::json::export::export;
+use crate::json::import::import;
+use crate::test_kernel::TestListener;
+use crate::{
+    modele, Hit, HitError, HitKernel, IndexEntryProperty, KernelBuilder,
+    LinkedHashMap, Model, ModelField, ModelFieldRef, ObjectValue, ObjectValues, Reference,
+};
+
+fn field<T: ModelField>(field: T) -> ModelFieldRef {
+    Rc::new(RefCell::new(field))
+}
+
+// an order sums the totals of its lines, and shows the name of its customer
+fn create_order_model() -> Model {
+    let mut order = Model::new("test/order".into(), "Order".into());
+    order.fields.insert(
+        "lines".into(),
+        field(FieldTypeSubobjectArray {
+            name: "lines".into(),
+            authorized_models: vec!["test/line".into()],
+            ..Default::default()
+        }),
+    );
+    order.fields.insert(
+        "archive".into(),
+        field(FieldTypeSubobjectArray {
+            name: "archive".into(),
+            authorized_models: vec!["test/line".into(), "test/customer".into()],
+            ..Default::default()
+        }),
+    );
+    order.fields.insert(
+        "customer".into(),
+        field(FieldTypeReference {
+            name: "customer".into(),
+            authorized_models: vec!["test/customer".into()],
+            ..Default::default()
+        }),
+    );
+    order.fields.insert(
+        "count".into(),
+        field(FieldTypeComputed::count("count", "lines")),
+    );
+    order.fields.insert(
+        "sum".into(),
+        field(FieldTypeComputed::sum("sum", "lines", "total")),
+    );
+    order.fields.insert(
+        "customer_name".into(),
+        field(FieldTypeComputed::new(
+            "customer_name",
+            vec![ComputedDependency::References {
+                field: "customer".into(),
+                property: Some("name".into()),
+            }],
+            |id, hit| match hit.get_value(id, "customer") {
+                Some(ObjectValue::Reference(customer)) => Ok(hit
+                    .get_value(&customer.id, "name")
+                    .unwrap_or(ObjectValue::Null)),
+                _ => Ok(ObjectValue::Null),
+            },
+        )),
+    );
+    order
+}
+
+fn create_line_model() -> Model {
+    let line = modele!("test/line", "Line" =>
+        "price": FieldTypeInteger {},
+        "quantity": FieldTypeInteger {}
+    );
+    let mut line = Rc::try_unwrap(line).ok().expect("Error");
+    line.fields.insert(
+        "total".into(),
+        field(FieldTypeComputed::new(
+            "total",
+            vec![
+                ComputedDependency::Field("price".into()),
+                ComputedDependency::Field("quantity".into()),
+            ],
+            |id, hit| match (hit.get_value(id, "price"), hit.get_value(id, "quantity")) {
+                (Some(ObjectValue::I32(price)), Some(ObjectValue::I32(quantity))) => {
+                    Ok(ObjectValue::I32(price * quantity))
+                }
+                _ => Ok(ObjectValue::I32(0)),
+            },
+        )),
+    );
+    line
+}
+
+fn create_kernel() -> Rc<HitKernel> {
+    let kernel = KernelBuilder::new()
+        .add_model(Rc::new(create_order_model()))
+        .add_model(Rc::new(create_line_model()))
+        .add_model(modele!("test/customer", "Customer" =>
+            "name": FieldTypeString {}
+        ))
+        .mark_instantiable("test/order")
+        .build()
+        .expect("Error");
+    Rc::new(kernel)
+}
+
+fn property(id: &str, property: &str) -> IndexEntryProperty {
+    IndexEntryProperty {
+        id: id.into(),
+        property: property.into(),
+    }
+}
+
+fn line_values(price: i32, quantity: i32) -> ObjectValues {
+    let mut values = LinkedHashMap::new();
+    values.insert("price".to_string(), ObjectValue::I32(price));
+    values.insert("quantity".to_string(), ObjectValue::I32(quantity));
+    values
+}
+
+fn create_hit() -> Hit {
+    let mut hit = Hit::new("id", "test/order", create_kernel()).expect("Error");
+    hit.insert(
+        "test/line",
+        "a",
+        line_values(2, 3),
+        property("id", "lines"),
+        None,
+    )
+    .expect("Error");
+    hit.insert(
+        "test/line",
+        "b",
+        line_values(5, 1),
+        property("id", "lines"),
+        None,
+    )
+    .expect("Error");
+    hit
+}
+
+#[test]
+fn it_should_compute_the_fields_of_new_objects() {
+    let hit = Hit::new("id", "test/order", create_kernel()).expect("Error");
+    assert_eq!(hit.get_value("id", "count"), Some(ObjectValue::I32(0)));
+    assert_eq!(hit.get_value("id", "sum"), Some(ObjectValue::I32(0)));
+    assert_eq!(
+        hit.get_value("id", "customer_name"),
+        Some(ObjectValue::Null)
+    );
+
+    let hit = create_hit();
+    assert_eq!(hit.get_value("a", "total"), Some(ObjectValue::I32(6)));
+    assert_eq!(hit.get_value("id", "count"), Some(ObjectValue::I32(2)));
+    assert_eq!(hit.get_value("id", "sum"), Some(ObjectValue::I32(11)));
+}
+
+#[test]
+fn it_should_update_the_computed_fields_and_notify_the_listeners() {
+    let mut hit = create_hit();
+    let listener = Rc::new(RefCell::new(TestListener { values: vec![] }));
+    hit.subscribe_field("id", "sum", listener.clone())
+        .expect("Error");
+
+    hit.set("a", "quantity", ObjectValue::I32(4))
+        .expect("Error");
+    assert_eq!(hit.get_value("a", "total"), Some(ObjectValue::I32(8)));
+    assert_eq!(hit.get_value("id", "sum"), Some(ObjectValue::I32(13)));
+    // an unchanged value is not sent again
+    hit.set("a", "price", ObjectValue::I32(2)).expect("Error");
+    assert_eq!(listener.borrow().values, vec![ObjectValue::I32(13)]);
+
+    hit.move_object("b", property("id", "archive"), None)
+        .expect("Error");
+    assert_eq!(hit.get_value("id", "count"), Some(ObjectValue::I32(1)));
+    assert_eq!(hit.get_value("id", "sum"), Some(ObjectValue::I32(8)));
+
+    hit.remove_object("a").expect("Error");
+    assert_eq!(hit.get_value("id", "count"), Some(ObjectValue::I32(0)));
+    assert_eq!(
+        listener.borrow().values,
+        vec![
+            ObjectValue::I32(13),
+            ObjectValue::I32(8),
+            ObjectValue::I32(0)
+        ]
+    );
+}
+
+#[test]
+fn it_should_update_the_fields_that_read_referenced_objects() {
+    let mut hit = create_hit();
+    let mut values = LinkedHashMap::new();
+    values.insert("name".to_string(), ObjectValue::String("Alice".into()));
+    hit.insert(
+        "test/customer",
+        "c",
+        values,
+        property("id", "archive"),
+        None,
+    )
+    .expect("Error");
+    hit.set(
+        "id",
+        "customer",
+        ObjectValue::Reference(Reference { id: "c".into() }),
+    )
+    .expect("Error");
+    assert_eq!(
+        hit.get_value("id", "customer_name"),
+        Some(ObjectValue::String("Alice".into()))
+    );
+    hit.set("c", "name", ObjectValue::String("Bob".into()))
+        .expect("Error");
+    assert_eq!(
+        hit.get_value("id", "customer_name"),
+        Some(ObjectValue::String("Bob".into()))
+    );
+}
+
+#[test]
+fn it_should_not_set_a_computed_field() {
+    let mut hit = create_hit();
+    assert_eq!(
+        hit.set("id", "sum", ObjectValue::I32(3)),
+        Err(HitError::CannotSetComputedField("sum".into()))
+    );
+    // the copies and the imports compute the values again
+    let new_id = hit
+        .copy_object("a".into(), property("id", "lines"), None)
+        .expect("Error");
+    assert_eq!(hit.get_value(&new_id, "total"), Some(ObjectValue::I32(6)));
+    assert_eq!(hit.get_value("id", "sum"), Some(ObjectValue::I32(17)));
+
+    let mut document = export(&hit).expect("Error");
+    for object in document["data"].as_array_mut().expect("Error").iter_mut() {
+        if object["id"] == "id" {
+            object["data"]["sum"] = serde_json::json!(1);
+        }
+    }
+    let hit = import(&document, create_kernel()).expect("Error");
+    assert_eq!(hit.get_value("id", "sum"), Some(ObjectValue::I32(17)));
+}
+
+#[test]
+fn it_should_reject_the_cyclic_computed_fields() {
+    let mut model = Model::new("test/cycle".into(), "Cycle".into());
+    model.fields.insert(
+        "a".into(),
+        field(FieldTypeComputed::new(
+            "a",
+            vec![ComputedDependency::Field("b".into())],
+            |_, _| Ok(ObjectValue::I32(1)),
+        )),
+    );
+    model.fields.insert(
+        "b".into(),
+        field(FieldTypeComputed::new(
+            "b",
+            vec![ComputedDependency::Field("a".into())],
+            |_, _| Ok(ObjectValue::I32(1)),
+        )),
+    );
+    let kernel = KernelBuilder::new()
+        .add_model(Rc::new(model))
+        .build()
+        .expect("Error");
+    let result = Hit::new("id", "test/cycle", Rc::new(kernel));
+    assert!(matches!(
+        result.err(),
+        Some(HitError::CyclicComputedField(id, _)) if id == "id"
+    ));
+}
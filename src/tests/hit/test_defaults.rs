@@ -0,0 +1,175 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::field_types::{
+    FieldTypeBool, FieldTypeInteger, FieldTypeString, FieldTypeSubobjectArray,
+};
+use crate::json::export::export;
+use crate::json::import::import;
+use crate::{
+    modele, FieldDefault, Hit, HitKernel, IndexEntryProperty, KernelBuilder, LinkedHashMap, Model,
+    ObjectValue, SecondaryIndexKind,
+};
+
+// the depth of a folder is the depth of its parent plus one
+fn create_kernel(defaults_on_import: bool) -> Rc<HitKernel> {
+    let kernel = KernelBuilder::new()
+        .add_model(modele!("test/folder", "Folder" =>
+            "name": FieldTypeString {
+                required: true
+            } = FieldDefault::Value(ObjectValue::String("untitled".into())),
+            "depth": FieldTypeInteger {} = FieldDefault::initializer(|parent, hit| match parent {
+                None => Ok(ObjectValue::I32(0)),
+                Some(parent) => match hit.get_value(&parent.id, "depth") {
+                    Some(ObjectValue::I32(depth)) => Ok(ObjectValue::I32(depth + 1)),
+                    _ => Ok(ObjectValue::Null),
+                },
+            }),
+            "hidden": FieldTypeBool {},
+            "items": FieldTypeSubobjectArray {
+                authorized_models: vec!["test/folder".into()]
+            }
+        ))
+        .mark_instantiable("test/folder")
+        .add_secondary_index(
+            "by_depth",
+            "test/folder",
+            "depth",
+            SecondaryIndexKind::Exact,
+        )
+        .set_defaults_on_import(defaults_on_import)
+        .build()
+        .expect("Error");
+    Rc::new(kernel)
+}
+
+fn items(id: &str) -> IndexEntryProperty {
+    IndexEntryProperty {
+        id: id.into(),
+        property: "items".into(),
+    }
+}
+
+fn string(value: &str) -> Option<ObjectValue> {
+    Some(ObjectValue::String(value.into()))
+}
+
+#[test]
+fn it_should_set_the_defaults_of_a_new_document() {
+    let hit = Hit::new("id", "test/folder", create_kernel(false)).expect("Error");
+    assert_eq!(hit.get_value("id", "name"), string("untitled"));
+    assert_eq!(hit.get_value("id", "depth"), Some(ObjectValue::I32(0)));
+    assert_eq!(hit.get_value("id", "hidden"), Some(ObjectValue::Null));
+    // the required field is satisfied by its default
+    assert_eq!(hit.get_validation_errors("id", "name"), None);
+}
+
+#[test]
+fn it_should_set_the_values_of_a_new_document_in_the_order_of_the_model() {
+    let mut values = LinkedHashMap::new();
+    values.insert("hidden".to_string(), ObjectValue::Bool(true));
+    values.insert("name".to_string(), ObjectValue::String("docs".into()));
+    let hit =
+        Hit::new_with_values("id", create_kernel(false), values, "test/folder").expect("Error");
+    let data = hit.get("id").expect("Error").get_data();
+    assert_eq!(
+        data.keys().collect::<Vec<&String>>(),
+        vec!["name", "depth", "hidden"]
+    );
+}
+
+#[test]
+fn it_should_set_the_defaults_of_the_absent_fields_on_insert() {
+    let mut hit = Hit::new("id", "test/folder", create_kernel(false)).expect("Error");
+    hit.insert("test/folder", "a", LinkedHashMap::new(), items("id"), None)
+        .expect("Error");
+    assert_eq!(hit.get_value("a", "name"), string("untitled"));
+    assert_eq!(hit.get_value("a", "depth"), Some(ObjectValue::I32(1)));
+
+    let mut values = LinkedHashMap::new();
+    values.insert("name".to_string(), ObjectValue::String("docs".into()));
+    values.insert("depth".to_string(), ObjectValue::Null);
+    hit.insert("test/folder", "b", values, items("a"), None)
+        .expect("Error");
+    assert_eq!(hit.get_value("b", "name"), string("docs"));
+    // a null value is not replaced
+    assert_eq!(hit.get_value("b", "depth"), Some(ObjectValue::Null));
+}
+
+#[test]
+fn it_should_set_the_defaults_of_the_null_fields_of_a_copy() {
+    let mut hit = Hit::new("id", "test/folder", create_kernel(false)).expect("Error");
+    let mut values = LinkedHashMap::new();
+    values.insert("depth".to_string(), ObjectValue::Null);
+    hit.insert("test/folder", "a", values, items("id"), None)
+        .expect("Error");
+    hit.set("a", "name", ObjectValue::String("docs".into()))
+        .expect("Error");
+
+    let new_id = hit
+        .copy_object("a".into(), items("id"), None)
+        .expect("Error");
+    assert_eq!(hit.get_value(&new_id, "name"), string("docs"));
+    assert_eq!(hit.get_value(&new_id, "depth"), Some(ObjectValue::I32(1)));
+}
+
+#[test]
+fn it_should_set_the_defaults_on_import_when_the_kernel_asks_for_it() {
+    let mut hit = Hit::new("id", "test/folder", create_kernel(false)).expect("Error");
+    let mut values = LinkedHashMap::new();
+    values.insert("depth".to_string(), ObjectValue::Null);
+    hit.insert("test/folder", "a", values, items("id"), None)
+        .expect("Error");
+    let exported = export(&hit).expect("Error");
+
+    let imported = import(&exported, create_kernel(false)).expect("Error");
+    assert_eq!(imported.get_value("a", "depth"), Some(ObjectValue::Null));
+    let imported = import(&exported, create_kernel(true)).expect("Error");
+    assert_eq!(imported.get_value("a", "depth"), Some(ObjectValue::I32(1)));
+    assert_eq!(imported.get_value("a", "name"), string("untitled"));
+    // the plugins see the defaults set on import
+    assert_eq!(
+        imported.index_lookup("by_depth", &ObjectValue::I32(1)),
+        Ok(vec!["a".into()])
+    );
+    assert_eq!(
+        imported.index_lookup("by_depth", &ObjectValue::Null),
+        Ok(vec![])
+    );
+}
+
+#[test]
+fn it_should_inherit_the_defaults_of_the_base_model() {
+    let mut base = Model::new("test/item".into(), "Item".into());
+    base.is_abstract = true;
+    base.fields.insert(
+        "name".into(),
+        Rc::new(RefCell::new(FieldTypeString {
+            name: "name".into(),
+            ..Default::default()
+        })),
+    );
+    base.fields.insert(
+        "hidden".into(),
+        Rc::new(RefCell::new(FieldTypeBool {
+            name: "hidden".into(),
+            ..Default::default()
+        })),
+    );
+    base.set_default(
+        "name",
+        FieldDefault::Value(ObjectValue::String("item".into())),
+    );
+    base.set_default("hidden", FieldDefault::Value(ObjectValue::Bool(false)));
+    let kernel = KernelBuilder::new()
+        .add_model(Rc::new(base))
+        .add_model(modele!("test/file", "File" =>
+            "hidden": FieldTypeBool {} = FieldDefault::Value(ObjectValue::Bool(true)),
+            extends: "test/item"
+        ))
+        .build()
+        .expect("Error");
+    let hit = Hit::new("id", "test/file", Rc::new(kernel)).expect("Error");
+    assert_eq!(hit.get_value("id", "name"), string("item"));
+    assert_eq!(hit.get_value("id", "hidden"), Some(ObjectValue::Bool(true)));
+}
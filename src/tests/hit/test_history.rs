@@ -0,0 +1,322 @@
+use linked_hash_map::LinkedHashMap;
+
+use crate::test_kernel::sub_items;
+use crate::tests::hit::test_copy_object::create_hit_with_subobjects;
+use crate::{Hit, HitError, Id, IndexEntryProperty, ObjectValue, Reference};
+
+pub(crate) type EntryDump = (
+    Id,
+    Vec<(String, ObjectValue)>,
+    Option<IndexEntryProperty>,
+    Vec<IndexEntryProperty>,
+);
+
+// the whole content of the index, to compare states
+// (setting a value changes the order of the fields in the entry)
+pub(crate) fn dump(hit: &Hit) -> Vec<EntryDump> {
+    hit.index
+        .iter()
+        .map(|(id, entry)| {
+            let entry = entry.borrow();
+            let mut references = entry.references.clone();
+            references.sort_by_key(|r| format!("{}{}", r.id, r.property));
+            let mut data: Vec<(String, ObjectValue)> = entry
+                .data
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            data.sort_by_key(|(key, _)| key.clone());
+            (
+                id.clone(),
+                data,
+                entry.get_parent(),
+                references,
+            )
+        })
+        .collect()
+}
+
+fn create_hit_with_history() -> Hit {
+    let mut hit = create_hit_with_subobjects();
+    hit.insert(
+        "test/test",
+        "id6",
+        LinkedHashMap::new(),
+        IndexEntryProperty {
+            id: "id2".into(),
+            property: "sub_items".into(),
+        },
+        None,
+    )
+    .expect("Error");
+    hit.enable_history(10);
+    hit
+}
+
+fn references(id: &str) -> IndexEntryProperty {
+    IndexEntryProperty {
+        id: id.into(),
+        property: "references".into(),
+    }
+}
+
+fn ids(ids: Vec<&str>) -> Vec<Reference> {
+    ids.into_iter().map(|id| Reference { id: id.into() }).collect()
+}
+
+#[test]
+fn it_should_not_record_anything_when_the_history_is_disabled() {
+    let mut hit = create_hit_with_subobjects();
+    hit.set("id2", "name", ObjectValue::String("a".into()))
+        .expect("Error");
+    assert!(!hit.can_undo());
+    assert_eq!(hit.undo(), Ok(false));
+}
+
+#[test]
+fn it_should_undo_and_redo_a_set() {
+    let mut hit = create_hit_with_history();
+    hit.set("id2", "name", ObjectValue::String("a".into()))
+        .expect("Error");
+    hit.set("id2", "name", ObjectValue::String("b".into()))
+        .expect("Error");
+
+    assert_eq!(hit.undo(), Ok(true));
+    assert_eq!(
+        hit.get_value("id2", "name"),
+        Some(ObjectValue::String("a".into()))
+    );
+    // the required field gets back its null value
+    assert_eq!(hit.undo(), Ok(true));
+    assert_eq!(hit.get_value("id2", "name"), Some(ObjectValue::Null));
+    assert_eq!(hit.get_validation_errors("id2", "name").unwrap().len(), 1);
+    assert!(!hit.can_undo());
+
+    assert_eq!(hit.redo(), Ok(true));
+    assert_eq!(hit.redo(), Ok(true));
+    assert_eq!(hit.redo(), Ok(false));
+    assert_eq!(
+        hit.get_value("id2", "name"),
+        Some(ObjectValue::String("b".into()))
+    );
+}
+
+#[test]
+fn it_should_undo_and_redo_an_insert() {
+    let mut hit = create_hit_with_history();
+    let initial_state = dump(&hit);
+    let mut values = LinkedHashMap::new();
+    values.insert("name".to_string(), ObjectValue::String("new".into()));
+    hit.insert("test/test", "new", values, sub_items("id2"), Some("id6".into()))
+        .expect("Error");
+    let inserted_state = dump(&hit);
+
+    hit.undo().expect("Error");
+    assert!(!hit.contains_key("new"));
+    assert_eq!(dump(&hit), initial_state);
+
+    hit.redo().expect("Error");
+    assert_eq!(
+        hit.get_value("id2", "sub_items"),
+        Some(ObjectValue::VecSubObjects(ids(vec!["id3", "new", "id6"])))
+    );
+    assert_eq!(dump(&hit), inserted_state);
+}
+
+#[test]
+fn it_should_restore_a_removed_subtree_with_its_ids_positions_and_references() {
+    let mut hit = create_hit_with_history();
+    let initial_state = dump(&hit);
+
+    hit.remove_object("id3").expect("Error");
+    let removed_state = dump(&hit);
+    assert!(!hit.contains_key("id5"));
+    // the references held by the removed objects are not indexed anymore
+    assert_eq!(hit.get_references("id2"), Ok(vec![]));
+
+    hit.undo().expect("Error");
+    assert_eq!(dump(&hit), initial_state);
+    assert_eq!(
+        hit.get_value("id2", "sub_items"),
+        Some(ObjectValue::VecSubObjects(ids(vec!["id3", "id6"])))
+    );
+    assert_eq!(
+        hit.get_value("id3", "sub_items"),
+        Some(ObjectValue::VecSubObjects(ids(vec!["id4", "id5"])))
+    );
+    assert_eq!(
+        hit.get_value("id5", "name"),
+        Some(ObjectValue::String("hello".into()))
+    );
+    assert!(hit.get_model("id4").is_some());
+
+    hit.redo().expect("Error");
+    assert_eq!(dump(&hit), removed_state);
+    hit.undo().expect("Error");
+    assert_eq!(dump(&hit), initial_state);
+}
+
+#[test]
+fn it_should_undo_and_redo_a_move() {
+    let mut hit = create_hit_with_history();
+    let initial_state = dump(&hit);
+    hit.move_object("id3", sub_items("id"), Some("id2".into()))
+        .expect("Error");
+    let moved_state = dump(&hit);
+
+    hit.undo().expect("Error");
+    assert_eq!(dump(&hit), initial_state);
+    assert_eq!(
+        hit.get_value("id2", "sub_items"),
+        Some(ObjectValue::VecSubObjects(ids(vec!["id3", "id6"])))
+    );
+    hit.redo().expect("Error");
+    assert_eq!(dump(&hit), moved_state);
+}
+
+#[test]
+fn it_should_undo_and_redo_a_copy_with_the_same_ids() {
+    let mut hit = create_hit_with_history();
+    let initial_state = dump(&hit);
+    let new_id = hit
+        .copy_object("id3".into(), sub_items("id"), None)
+        .expect("Error");
+    let copied_state = dump(&hit);
+
+    // the copy is a single step
+    hit.undo().expect("Error");
+    assert!(!hit.can_undo());
+    assert!(!hit.contains_key(&new_id));
+    assert_eq!(dump(&hit), initial_state);
+
+    hit.redo().expect("Error");
+    assert!(hit.contains_key(&new_id));
+    assert_eq!(dump(&hit), copied_state);
+}
+
+#[test]
+fn it_should_undo_and_redo_reference_operations() {
+    let mut hit = create_hit_with_history();
+    hit.insert_reference("id3", references("id4"), Some("id2".into()))
+        .expect("Error");
+    hit.insert_reference("id6", references("id4"), None)
+        .expect("Error");
+    hit.move_reference("id6", references("id4"), Some("id3".into()))
+        .expect("Error");
+    hit.remove_reference("id3", references("id4"))
+        .expect("Error");
+    assert_eq!(
+        hit.get_value("id4", "references"),
+        Some(ObjectValue::VecReference(ids(vec!["id6", "id2"])))
+    );
+
+    hit.undo().expect("Error");
+    assert_eq!(
+        hit.get_value("id4", "references"),
+        Some(ObjectValue::VecReference(ids(vec!["id6", "id3", "id2"])))
+    );
+    hit.undo().expect("Error");
+    assert_eq!(
+        hit.get_value("id4", "references"),
+        Some(ObjectValue::VecReference(ids(vec!["id3", "id2", "id6"])))
+    );
+    hit.undo().expect("Error");
+    hit.undo().expect("Error");
+    assert_eq!(
+        hit.get_value("id4", "references"),
+        Some(ObjectValue::VecReference(ids(vec!["id2"])))
+    );
+    assert_eq!(hit.get_references("id6"), Ok(vec![]));
+
+    for _ in 0..4 {
+        hit.redo().expect("Error");
+    }
+    assert_eq!(
+        hit.get_value("id4", "references"),
+        Some(ObjectValue::VecReference(ids(vec!["id6", "id2"])))
+    );
+    assert_eq!(hit.get_references("id6"), Ok(vec![references("id4")]));
+}
+
+#[test]
+fn it_should_undo_a_group_of_operations_as_one_step() {
+    let mut hit = create_hit_with_history();
+    let initial_state = dump(&hit);
+    hit.begin_undo_group();
+    hit.set("id2", "name", ObjectValue::String("a".into()))
+        .expect("Error");
+    hit.begin_undo_group();
+    hit.remove_object("id6").expect("Error");
+    hit.end_undo_group();
+    hit.set("id3", "name", ObjectValue::String("b".into()))
+        .expect("Error");
+    hit.end_undo_group();
+    hit.set("id4", "name", ObjectValue::String("c".into()))
+        .expect("Error");
+
+    hit.undo().expect("Error");
+    assert_eq!(
+        hit.get_value("id3", "name"),
+        Some(ObjectValue::String("b".into()))
+    );
+    hit.undo().expect("Error");
+    assert!(!hit.can_undo());
+    assert_eq!(dump(&hit), initial_state);
+}
+
+#[test]
+fn it_should_record_a_committed_transaction_as_one_step() {
+    let mut hit = create_hit_with_history();
+    let initial_state = dump(&hit);
+    hit.transaction(|tx| {
+        tx.set("id2", "name", ObjectValue::String("a".into()))?;
+        tx.move_object("id6", sub_items("id"), None)?;
+        let failed: Result<(), HitError> = tx.transaction(|tx| {
+            tx.set("id3", "name", ObjectValue::String("b".into()))?;
+            Err(HitError::ValidationError())
+        });
+        assert!(failed.is_err());
+        Ok(())
+    })
+    .expect("Error");
+    let failed: Result<(), HitError> = hit.transaction(|tx| {
+        tx.set("id4", "name", ObjectValue::String("c".into()))?;
+        Err(HitError::ValidationError())
+    });
+    assert!(failed.is_err());
+
+    hit.undo().expect("Error");
+    assert!(!hit.can_undo());
+    assert_eq!(dump(&hit), initial_state);
+}
+
+#[test]
+fn it_should_not_undo_inside_a_transaction() {
+    let mut hit = create_hit_with_history();
+    let result = hit.transaction(|tx| tx.undo());
+    assert_eq!(result, Err(HitError::CannotUndoInTransaction()));
+}
+
+#[test]
+fn it_should_forget_the_oldest_steps_and_the_redo_stack() {
+    let mut hit = create_hit_with_subobjects();
+    hit.enable_history(2);
+    for name in ["a", "b", "c"].iter() {
+        hit.set("id2", "name", ObjectValue::String(name.to_string()))
+            .expect("Error");
+    }
+    assert_eq!(hit.undo(), Ok(true));
+    assert_eq!(hit.undo(), Ok(true));
+    assert_eq!(hit.undo(), Ok(false));
+    assert_eq!(
+        hit.get_value("id2", "name"),
+        Some(ObjectValue::String("a".into()))
+    );
+
+    // a new operation clears the redo stack
+    assert!(hit.can_redo());
+    hit.set("id2", "name", ObjectValue::String("d".into()))
+        .expect("Error");
+    assert!(!hit.can_redo());
+    assert_eq!(hit.redo(), Ok(false));
+}
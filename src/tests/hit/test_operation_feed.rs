@@ -0,0 +1,253 @@
+use linked_hash_map::LinkedHashMap;
+
+use crate::test_kernel::sub_items;
+use crate::tests::hit::test_copy_object::create_hit_with_subobjects;
+use crate::{
+    FieldListener, HitError, IndexEntryProperty, ObjectValue, Operation, OperationRecord,
+    Reference,
+};
+use std::{cell::RefCell, rc::Rc};
+
+struct OperationListener {
+    id: String,
+    records: Vec<OperationRecord>,
+}
+
+impl FieldListener<OperationRecord> for OperationListener {
+    fn on_update(&mut self, value: &OperationRecord) {
+        self.records.push(value.clone());
+    }
+    fn get_unique_id(&self) -> &str {
+        &self.id
+    }
+}
+
+fn create_listener() -> Rc<RefCell<OperationListener>> {
+    Rc::new(RefCell::new(OperationListener {
+        id: "listener".into(),
+        records: vec![],
+    }))
+}
+
+#[test]
+fn it_should_emit_the_operations_with_their_full_payload() {
+    let mut hit = create_hit_with_subobjects();
+    let listener = create_listener();
+    let listener_id = hit.subscribe_operations(listener.clone());
+    assert_eq!(listener_id, "listener");
+    // the operations of the fixture were already emitted
+    let sequence = hit.get_operation_sequence();
+
+    hit.set("id2", "name", ObjectValue::String("a".into()))
+        .expect("Error");
+    hit.insert(
+        "test/test",
+        "id6",
+        LinkedHashMap::new(),
+        sub_items("id"),
+        None,
+    )
+    .expect("Error");
+    hit.move_object("id6", sub_items("id2"), Some("id3".into()))
+        .expect("Error");
+    hit.remove_object("id3").expect("Error");
+
+    let records = &listener.borrow().records;
+    assert_eq!(
+        records.iter().map(|r| r.sequence).collect::<Vec<u64>>(),
+        vec![sequence + 1, sequence + 2, sequence + 3, sequence + 4]
+    );
+    assert_eq!(hit.get_operation_sequence(), sequence + 4);
+    assert_eq!(
+        records[0].operation,
+        Operation::Set {
+            id: "id2".into(),
+            property: "name".into(),
+            value: ObjectValue::String("a".into()),
+            old_value: ObjectValue::Null,
+        }
+    );
+    match &records[1].operation {
+        Operation::Insert {
+            id, parent, values, ..
+        } => {
+            assert_eq!(id, "id6");
+            assert_eq!(parent, &sub_items("id"));
+            assert_eq!(values.get("name"), Some(&ObjectValue::Null));
+        }
+        _ => panic!("Expected an insert"),
+    }
+    assert_eq!(
+        records[2].operation,
+        Operation::Move {
+            id: "id6".into(),
+            target: sub_items("id2"),
+            before_id: Some("id3".into()),
+            original_parent: sub_items("id"),
+            original_before_id: None,
+        }
+    );
+    match &records[3].operation {
+        Operation::Remove {
+            id,
+            parent,
+            before_id,
+            entries,
+        } => {
+            assert_eq!(id, "id3");
+            assert_eq!(parent, &sub_items("id2"));
+            assert_eq!(before_id, &None);
+            assert_eq!(
+                entries.iter().map(|e| e.id.clone()).collect::<Vec<String>>(),
+                vec!["id3", "id4", "id5"]
+            );
+            assert_eq!(
+                entries[2].data.get("name"),
+                Some(&ObjectValue::String("hello".into()))
+            );
+        }
+        _ => panic!("Expected a removal"),
+    }
+}
+
+#[test]
+fn it_should_emit_a_copy_as_a_single_operation() {
+    let mut hit = create_hit_with_subobjects();
+    let listener = create_listener();
+    hit.subscribe_operations(listener.clone());
+
+    let new_id = hit
+        .copy_object("id3".into(), sub_items("id"), None)
+        .expect("Error");
+
+    let records = &listener.borrow().records;
+    assert_eq!(records.len(), 1);
+    match &records[0].operation {
+        Operation::Copy {
+            id,
+            new_id: copy_id,
+            entries,
+            ..
+        } => {
+            assert_eq!(id, "id3");
+            assert_eq!(copy_id, &new_id);
+            assert_eq!(entries.len(), 3);
+        }
+        _ => panic!("Expected a copy"),
+    }
+}
+
+#[test]
+fn it_should_emit_reference_operations() {
+    let mut hit = create_hit_with_subobjects();
+    let listener = create_listener();
+    hit.subscribe_operations(listener.clone());
+    let target = IndexEntryProperty {
+        id: "id4".into(),
+        property: "references".into(),
+    };
+
+    hit.insert_reference("id3", target.clone(), None)
+        .expect("Error");
+    hit.move_reference("id3", target.clone(), Some("id2".into()))
+        .expect("Error");
+    hit.remove_reference("id3", target.clone()).expect("Error");
+
+    let operations: Vec<Operation> = listener
+        .borrow()
+        .records
+        .iter()
+        .map(|r| r.operation.clone())
+        .collect();
+    assert_eq!(
+        operations,
+        vec![
+            Operation::InsertReference {
+                id: "id3".into(),
+                target: target.clone(),
+                before_id: None,
+            },
+            Operation::MoveReference {
+                id: "id3".into(),
+                target: target.clone(),
+                before_id: Some("id2".into()),
+                original_before_id: None,
+            },
+            Operation::RemoveReference {
+                id: "id3".into(),
+                target: target.clone(),
+                before_id: Some("id2".into()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn it_should_emit_the_operations_of_a_transaction_when_it_is_committed() {
+    let mut hit = create_hit_with_subobjects();
+    let listener = create_listener();
+    hit.subscribe_operations(listener.clone());
+    let sequence = hit.get_operation_sequence();
+
+    hit.transaction(|tx| {
+        tx.set("id2", "name", ObjectValue::String("a".into()))?;
+        tx.set("id3", "name", ObjectValue::String("b".into()))?;
+        assert_eq!(listener.borrow().records.len(), 0);
+        Ok(())
+    })
+    .expect("Error");
+    assert_eq!(listener.borrow().records.len(), 2);
+
+    let result: Result<(), HitError> = hit.transaction(|tx| {
+        tx.set("id4", "name", ObjectValue::String("c".into()))?;
+        Err(HitError::ValidationError())
+    });
+    assert!(result.is_err());
+    assert_eq!(listener.borrow().records.len(), 2);
+    assert_eq!(hit.get_operation_sequence(), sequence + 2);
+}
+
+#[test]
+fn it_should_not_emit_failed_operations() {
+    let mut hit = create_hit_with_subobjects();
+    let listener = create_listener();
+    hit.subscribe_operations(listener.clone());
+
+    assert!(hit.remove_object("id").is_err());
+    assert!(hit.set("id2", "name", ObjectValue::F32(1.0)).is_err());
+    assert_eq!(listener.borrow().records.len(), 0);
+}
+
+#[test]
+fn it_should_stop_emitting_after_unsubscribe() {
+    let mut hit = create_hit_with_subobjects();
+    let listener = create_listener();
+    let listener_id = hit.subscribe_operations(listener.clone());
+    hit.unsubscribe_operations(&listener_id).expect("Error");
+    let sequence = hit.get_operation_sequence();
+
+    hit.set("id2", "name", ObjectValue::String("a".into()))
+        .expect("Error");
+    assert_eq!(listener.borrow().records.len(), 0);
+    // the sequence still advances
+    assert_eq!(hit.get_operation_sequence(), sequence + 1);
+}
+
+#[test]
+fn it_should_serialize_and_deserialize_the_records() {
+    let mut hit = create_hit_with_subobjects();
+    let listener = create_listener();
+    hit.subscribe_operations(listener.clone());
+    hit.set(
+        "id2",
+        "reference",
+        ObjectValue::Reference(Reference { id: "id3".into() }),
+    )
+    .expect("Error");
+    hit.remove_object("id5").expect("Error");
+
+    let records = listener.borrow().records.clone();
+    let json = serde_json::to_string(&records).expect("Error");
+    let deserialized: Vec<OperationRecord> = serde_json::from_str(&json).expect("Error");
+    assert_eq!(deserialized, records);
+}
@@ -0,0 +1,146 @@
+use std::rc::Rc;
+
+use crate::test_kernel::{create_fs_hit, create_fs_kernel_builder};
+use crate::{Hit, HitError, IndexEntryProperty, LinkedHashMap, ObjectValue, PathTarget};
+
+fn create_hit() -> Hit {
+    create_fs_hit(Rc::new(create_fs_kernel_builder().build().expect("Error")))
+}
+
+fn field(id: &str, property: &str) -> IndexEntryProperty {
+    IndexEntryProperty {
+        id: id.into(),
+        property: property.into(),
+    }
+}
+
+#[test]
+fn it_should_resolve_the_paths() {
+    let hit = create_hit();
+    assert_eq!(hit.resolve_path("/"), Ok(PathTarget::Object("root".into())));
+    assert_eq!(
+        hit.resolve_path("/items[0]/items[1]"),
+        Ok(PathTarget::Object("lib".into()))
+    );
+    assert_eq!(
+        hit.resolve_path(r#"/items[name="src"]/items[name="lib.rs"]/size"#),
+        Ok(PathTarget::Field(field("lib", "size")))
+    );
+    assert_eq!(
+        hit.resolve_path("/items/#src/items[size=40]"),
+        Ok(PathTarget::Object("lib".into()))
+    );
+    assert_eq!(
+        hit.resolve_path("/readme/name"),
+        Ok(PathTarget::Field(field("readme", "name")))
+    );
+    assert_eq!(
+        hit.resolve_path("/items"),
+        Ok(PathTarget::Field(field("root", "items")))
+    );
+
+    assert_eq!(
+        hit.resolve_path("/items[2]"),
+        Err(HitError::PathNotFound("/items[2]".into()))
+    );
+    // the id must be a subobject of the field
+    assert_eq!(
+        hit.resolve_path("/items/#main"),
+        Err(HitError::PathNotFound("/items/#main".into()))
+    );
+    assert_eq!(
+        hit.resolve_path("/unknown"),
+        Err(HitError::PathNotFound("/unknown".into()))
+    );
+    assert_eq!(
+        hit.resolve_path("/name[0]"),
+        Err(HitError::InvalidPath(
+            "/name[0]".into(),
+            "`name` is not a subobject array".into()
+        ))
+    );
+    assert_eq!(
+        hit.resolve_path("/name/size"),
+        Err(HitError::InvalidPath(
+            "/name/size".into(),
+            "`name` does not contain objects".into()
+        ))
+    );
+}
+
+#[test]
+fn it_should_build_the_path_of_an_object() {
+    let hit = create_hit();
+    assert_eq!(hit.path_of("root"), Ok("/".into()));
+    assert_eq!(hit.path_of("lib"), Ok("/items[0]/items[1]".into()));
+    assert_eq!(hit.path_of("readme"), Ok("/readme".into()));
+    assert_eq!(hit.path_of_field("root", "name"), Ok("/name".into()));
+    assert_eq!(
+        hit.path_of_field("main", "size"),
+        Ok("/items[0]/items[0]/size".into())
+    );
+    assert_eq!(
+        hit.path_of("unknown"),
+        Err(HitError::IDNotFound("unknown".into(), "path_of".into()))
+    );
+    for id in ["root", "src", "docs", "main", "lib", "guide", "readme"].iter() {
+        let path = hit.path_of(id).expect("Error");
+        assert_eq!(
+            hit.resolve_path(&path),
+            Ok(PathTarget::Object(id.to_string()))
+        );
+    }
+}
+
+#[test]
+fn it_should_read_and_set_values_by_path() {
+    let mut hit = create_hit();
+    assert_eq!(
+        hit.get_value_by_path("/items[0]/items[1]/size"),
+        Ok(Some(ObjectValue::I32(40)))
+    );
+    hit.set_by_path(
+        r#"/items[name="docs"]/name"#,
+        ObjectValue::String("doc".into()),
+    )
+    .expect("Error");
+    assert_eq!(
+        hit.get_value("docs", "name"),
+        Some(ObjectValue::String("doc".into()))
+    );
+    assert_eq!(
+        hit.set_by_path("/readme", ObjectValue::Null),
+        Err(HitError::InvalidPath(
+            "/readme".into(),
+            "the path designates an object, not a field".into()
+        ))
+    );
+}
+
+#[test]
+fn it_should_report_the_validation_errors_by_path() {
+    let mut hit = create_hit();
+    assert!(hit.get_validation_report().is_empty());
+    for (id, parent) in [("unnamed", "docs"), ("other", "src")].iter() {
+        hit.insert(
+            "fs/file",
+            id,
+            LinkedHashMap::new(),
+            field(parent, "items"),
+            None,
+        )
+        .expect("Error");
+    }
+    hit.validate_field("unnamed", "name").expect("Error");
+    hit.validate_field("other", "name").expect("Error");
+
+    let report = hit.get_validation_report();
+    assert_eq!(
+        report.keys().collect::<Vec<&String>>(),
+        vec!["/items[0]/items[2]/name", "/items[1]/items[1]/name"]
+    );
+    assert_eq!(
+        report["/items[1]/items[1]/name"],
+        *hit.get_validation_errors("unnamed", "name").expect("Error")
+    );
+}
@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::test_kernel::{create_fs_hit, create_fs_kernel_builder, insert_fs_object};
+use crate::{Comparison, Hit, Id, ModelTypeIndexer, ObjectValue, Query, QueryIndex};
+
+fn create_hit(indexer: Rc<RefCell<ModelTypeIndexer>>) -> Hit {
+    let kernel = create_fs_kernel_builder()
+        .add_plugin(indexer.clone())
+        .add_delete_plugin(indexer.clone())
+        .add_transaction_plugin(indexer)
+        .build()
+        .expect("Error");
+    create_fs_hit(Rc::new(kernel))
+}
+
+fn run(hit: &Hit, query: &str) -> Vec<Id> {
+    hit.query(&Query::parse(query).expect("Error"))
+}
+
+#[test]
+fn it_should_filter_the_objects_by_model_and_field() {
+    let hit = create_hit(Rc::new(RefCell::new(ModelTypeIndexer::new())));
+    assert_eq!(
+        run(&hit, r#"model = "fs/file""#),
+        vec!["guide", "lib", "main", "readme"]
+    );
+    assert_eq!(run(&hit, r#"model = "fs/item""#).len(), 7);
+    assert_eq!(
+        run(&hit, r#"model = "fs/file" and name ends_with ".rs""#),
+        vec!["lib", "main"]
+    );
+    assert_eq!(run(&hit, "size > 50 or size = 40.0"), vec!["lib", "main"]);
+    assert_eq!(
+        run(&hit, r#"not model = "fs/file" and name != "docs""#),
+        vec!["root", "src"]
+    );
+    // the references are compared to their id
+    assert_eq!(run(&hit, r#"link = "lib""#), vec!["guide"]);
+    assert_eq!(run(&hit, r#"tags contains "main""#), vec!["guide"]);
+    assert_eq!(
+        run(&hit, r#"model = "fs/file" and size = null"#),
+        vec!["guide"]
+    );
+
+    let entries = hit.query_entries(&Query::field(
+        "name",
+        Comparison::StartsWith,
+        ObjectValue::String("main".into()),
+    ));
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].get_id(), "main");
+}
+
+#[test]
+fn it_should_filter_the_objects_by_relation() {
+    let hit = create_hit(Rc::new(RefCell::new(ModelTypeIndexer::new())));
+    assert_eq!(
+        run(&hit, r#"parent("root")"#),
+        vec!["docs", "readme", "src"]
+    );
+    assert_eq!(run(&hit, r#"parent("root", "items")"#), vec!["docs", "src"]);
+    assert_eq!(run(&hit, r#"parent("src", "items")"#), vec!["lib", "main"]);
+    assert_eq!(run(&hit, r#"parent("src", "other")"#), Vec::<Id>::new());
+    assert_eq!(
+        run(
+            &hit,
+            r#"model = "fs/file" and name starts_with "g" and ancestor("root")"#
+        ),
+        vec!["guide"]
+    );
+    assert_eq!(run(&hit, r#"descendant("main")"#), vec!["root", "src"]);
+    assert_eq!(run(&hit, r#"references("lib")"#), vec!["guide"]);
+    assert_eq!(
+        run(&hit, r#"references("guide", "link")"#),
+        Vec::<Id>::new()
+    );
+    assert_eq!(run(&hit, r#"referenced_by("guide")"#), vec!["lib", "main"]);
+    assert_eq!(run(&hit, r#"referenced_by("guide", "tags")"#), vec!["main"]);
+}
+
+// lists a single object for every model
+struct PartialIndex {}
+
+impl QueryIndex for PartialIndex {
+    fn get_ids_of_model(&self, model: &str) -> Option<Vec<Id>> {
+        match model {
+            "fs/file" => Some(vec!["main".into()]),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn it_should_read_the_objects_of_a_model_from_the_index() {
+    let indexer = Rc::new(RefCell::new(ModelTypeIndexer::new()));
+    let mut hit = create_hit(indexer.clone());
+    hit.remove_object("guide").expect("Error");
+    let query = Query::parse(r#"model = "fs/item" and name contains "." "#).expect("Error");
+    assert_eq!(
+        hit.query_with_index(&query, &*indexer.borrow()),
+        hit.query(&query)
+    );
+    assert_eq!(hit.query(&query), vec!["lib", "main"]);
+
+    // only the objects of the index are read
+    let query = Query::Model("fs/file".into());
+    assert_eq!(hit.query_with_index(&query, &PartialIndex {}), vec!["main"]);
+    hit.remove_object("main").expect("Error");
+    assert_eq!(
+        hit.query_with_index(&query, &PartialIndex {}),
+        Vec::<Id>::new()
+    );
+    insert_fs_object(&mut hit, "fs/file", "main", "main.rs", ("src", "items"));
+    assert_eq!(hit.query_with_index(&query, &PartialIndex {}), vec!["main"]);
+}
@@ -156,3 +156,34 @@ fn it_should_return_an_error_when_object_has_references() {
         HitError::CannotDeleteObjectWithReferences(expected_error)
     );
 }
+
+#[test]
+fn it_should_remove_the_references_held_by_a_removed_object() {
+    let mut hit = create_hit_with_subobjects();
+    hit.insert(
+        "test/test",
+        "id5",
+        LinkedHashMap::new(),
+        IndexEntryProperty {
+            id: "id".into(),
+            property: "sub_items".into(),
+        },
+        None,
+    )
+    .expect("Error");
+    hit.insert_reference(
+        "id5",
+        IndexEntryProperty {
+            id: "id4".into(),
+            property: "references".into(),
+        },
+        None,
+    )
+    .expect("Error");
+
+    // the reference is held by a subobject of the removed object
+    hit.remove_object("id3").expect("Error");
+    assert_eq!(hit.get_references("id5").expect("Error"), vec![]);
+    hit.remove_object("id5").expect("Error");
+    assert!(hit.get("id5").is_none());
+}
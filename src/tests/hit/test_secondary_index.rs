@@ -0,0 +1,205 @@
+use std::ops::Bound;
+use std::rc::Rc;
+
+use crate::field_types::{FieldTypeFloat, FieldTypeString};
+use crate::json::export::export;
+use crate::json::import::import;
+use crate::test_kernel::{create_fs_hit, create_fs_kernel_builder};
+use crate::{modele, Hit, HitError, HitKernel, KernelBuilder, ObjectValue, SecondaryIndexKind};
+
+fn create_kernel() -> Rc<HitKernel> {
+    let kernel = create_fs_kernel_builder()
+        .add_secondary_index("by_name", "fs/item", "name", SecondaryIndexKind::Exact)
+        .add_secondary_index("name_prefix", "fs/item", "name", SecondaryIndexKind::Prefix)
+        .add_secondary_index("by_size", "fs/file", "size", SecondaryIndexKind::Range)
+        .build()
+        .expect("Error");
+    Rc::new(kernel)
+}
+
+fn create_hit() -> Hit {
+    create_fs_hit(create_kernel())
+}
+
+fn string(value: &str) -> ObjectValue {
+    ObjectValue::String(value.into())
+}
+
+#[test]
+fn it_should_look_up_the_objects_by_value() {
+    let mut hit = create_hit();
+    assert_eq!(
+        hit.index_lookup("by_name", &string("root")),
+        Ok(vec!["root".into()])
+    );
+    assert_eq!(
+        hit.index_lookup("by_name", &string("lib.rs")),
+        Ok(vec!["lib".into()])
+    );
+    assert_eq!(
+        hit.index_lookup("name_prefix", &string("m")),
+        Ok(vec!["main".into()])
+    );
+    assert_eq!(
+        hit.index_lookup("by_size", &ObjectValue::I32(40)),
+        Ok(vec!["lib".into()])
+    );
+
+    hit.set("lib", "name", string("mod.rs")).expect("Error");
+    assert_eq!(hit.index_lookup("by_name", &string("lib.rs")), Ok(vec![]));
+    assert_eq!(
+        hit.index_lookup("name_prefix", &string("m")),
+        Ok(vec!["main".into(), "lib".into()])
+    );
+
+    // the subobjects of the removed objects are removed from the indexes
+    hit.remove_object("guide").expect("Error");
+    hit.remove_object("src").expect("Error");
+    assert_eq!(hit.index_lookup("name_prefix", &string("m")), Ok(vec![]));
+    assert_eq!(
+        hit.index_lookup("unknown", &string("m")),
+        Err(HitError::SecondaryIndexNotFound("unknown".into()))
+    );
+}
+
+#[test]
+fn it_should_read_the_objects_by_range() {
+    let hit = create_hit();
+    assert_eq!(
+        hit.index_range(
+            "by_size",
+            Bound::Included(&ObjectValue::I32(20)),
+            Bound::Excluded(&ObjectValue::I32(120))
+        ),
+        Ok(vec!["readme".into(), "lib".into()])
+    );
+    assert_eq!(
+        hit.index_range(
+            "by_size",
+            Bound::Excluded(&ObjectValue::F32(40.0)),
+            Bound::Unbounded
+        ),
+        Ok(vec!["main".into()])
+    );
+    assert_eq!(
+        hit.index_range(
+            "by_size",
+            Bound::Included(&ObjectValue::I32(120)),
+            Bound::Included(&ObjectValue::I32(20))
+        ),
+        Ok(vec![])
+    );
+    assert_eq!(
+        hit.index_range("by_name", Bound::Unbounded, Bound::Unbounded),
+        Err(HitError::InvalidSecondaryIndex(
+            "by_name".into(),
+            "only the range indexes can be read by range".into()
+        ))
+    );
+}
+
+#[test]
+fn it_should_restore_the_indexes_on_rollback_and_fill_them_on_import() {
+    let mut hit = create_hit();
+    let result: Result<(), HitError> = hit.transaction(|hit| {
+        hit.set("main", "name", string("other.rs"))?;
+        Err(HitError::InvalidPath("/".into(), "rollback".into()))
+    });
+    assert!(result.is_err());
+    assert_eq!(hit.index_lookup("by_name", &string("other.rs")), Ok(vec![]));
+    assert_eq!(
+        hit.index_lookup("by_name", &string("main.rs")),
+        Ok(vec!["main".into()])
+    );
+    let result: Result<(), HitError> = hit.transaction(|hit| {
+        hit.remove_object("guide")?;
+        Err(HitError::InvalidPath("/".into(), "rollback".into()))
+    });
+    assert!(result.is_err());
+    assert_eq!(
+        hit.index_lookup("by_name", &string("guide.md")),
+        Ok(vec!["guide".into()])
+    );
+
+    let document = export(&hit).expect("Error");
+    let imported = import(&document, create_kernel()).expect("Error");
+    assert_eq!(
+        imported.index_lookup("name_prefix", &string("R")),
+        Ok(vec!["readme".into()])
+    );
+    assert_eq!(
+        imported.index_range(
+            "by_size",
+            Bound::Unbounded,
+            Bound::Included(&ObjectValue::I32(20))
+        ),
+        Ok(vec!["readme".into()])
+    );
+}
+
+#[test]
+fn it_should_keep_the_indexes_of_each_document() {
+    let kernel = create_kernel();
+    let mut hit = create_fs_hit(kernel.clone());
+    let other = create_fs_hit(kernel.clone());
+    hit.remove_object("guide").expect("Error");
+    assert_eq!(hit.index_lookup("name_prefix", &string("g")), Ok(vec![]));
+    assert_eq!(
+        other.index_lookup("name_prefix", &string("g")),
+        Ok(vec!["guide".into()])
+    );
+
+    // the imports do not add the values of the previous imports
+    let document = export(&other).expect("Error");
+    import(&document, kernel.clone()).expect("Error");
+    let imported = import(&document, kernel).expect("Error");
+    assert_eq!(
+        imported.index_lookup("by_name", &string("guide.md")),
+        Ok(vec!["guide".into()])
+    );
+}
+
+#[test]
+fn it_should_reject_the_invalid_indexes() {
+    let builder = || {
+        KernelBuilder::new().add_model(modele!("fs/file", "File" =>
+            "name": FieldTypeString {},
+            "size": FieldTypeFloat {}
+        ))
+    };
+    let error = |name: &str, reason: &str| {
+        Some(HitError::InvalidSecondaryIndex(name.into(), reason.into()))
+    };
+    assert_eq!(
+        builder()
+            .add_secondary_index("a", "fs/unknown", "name", SecondaryIndexKind::Exact)
+            .build()
+            .err(),
+        error("a", "unknown model `fs/unknown`")
+    );
+    assert_eq!(
+        builder()
+            .add_secondary_index("a", "fs/file", "path", SecondaryIndexKind::Exact)
+            .build()
+            .err(),
+        error("a", "model `fs/file` has no field `path`")
+    );
+    assert_eq!(
+        builder()
+            .add_secondary_index("a", "fs/file", "name", SecondaryIndexKind::Range)
+            .build()
+            .err(),
+        error(
+            "a",
+            "a Range index cannot be used on the string field `name`"
+        )
+    );
+    assert_eq!(
+        builder()
+            .add_secondary_index("a", "fs/file", "size", SecondaryIndexKind::Range)
+            .add_secondary_index("a", "fs/file", "name", SecondaryIndexKind::Exact)
+            .build()
+            .err(),
+        error("a", "the index is declared twice")
+    );
+}
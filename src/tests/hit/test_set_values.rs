@@ -0,0 +1,140 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::test_kernel::create_test_kernel_with_all_types;
+use crate::{
+    apply_patches, create_patch, FieldListener, Hit, HitError, IndexEntryProperty, LinkedHashMap,
+    ObjectValue,
+};
+
+struct CountListener {
+    values: Vec<ObjectValue>,
+}
+
+impl FieldListener<ObjectValue> for CountListener {
+    fn on_update(&mut self, value: &ObjectValue) {
+        self.values.push(value.clone());
+    }
+    fn get_unique_id(&self) -> &str {
+        "count"
+    }
+}
+
+fn strings(values: &[&str]) -> ObjectValue {
+    ObjectValue::VecString(values.iter().map(|value| value.to_string()).collect())
+}
+
+fn sub_values() -> IndexEntryProperty {
+    IndexEntryProperty {
+        id: "id".into(),
+        property: "sub_values".into(),
+    }
+}
+
+fn create_hit() -> Hit {
+    Hit::new("id", "test/values", Rc::new(create_test_kernel_with_all_types())).expect("Error")
+}
+
+#[test]
+fn it_should_set_integers_and_lists_of_strings() {
+    let mut hit = create_hit();
+    hit.set("id", "integer", ObjectValue::I32(-3)).expect("Error");
+    hit.set("id", "strings", strings(&["a", "b"])).expect("Error");
+    assert_eq!(hit.get_value("id", "integer"), Some(ObjectValue::I32(-3)));
+    assert_eq!(hit.get_value("id", "strings"), Some(strings(&["a", "b"])));
+
+    hit.set("id", "strings", ObjectValue::Null).expect("Error");
+    assert_eq!(hit.get_value("id", "strings"), Some(ObjectValue::Null));
+    assert!(matches!(
+        hit.set("id", "integer", ObjectValue::F32(1.0)),
+        Err(HitError::InvalidDataType())
+    ));
+    assert!(matches!(
+        hit.set("id", "strings", ObjectValue::String("a".into())),
+        Err(HitError::InvalidDataType())
+    ));
+}
+
+#[test]
+fn it_should_insert_and_copy_integers_and_lists_of_strings() {
+    let mut hit = create_hit();
+    let mut values = LinkedHashMap::new();
+    values.insert("integer".to_string(), ObjectValue::I32(7));
+    values.insert("strings".to_string(), strings(&["a"]));
+    hit.insert("test/values", "id2", values, sub_values(), None)
+        .expect("Error");
+
+    let copy_id = hit.copy_object("id2".into(), sub_values(), None).expect("Error");
+    assert_eq!(hit.get_value(&copy_id, "integer"), Some(ObjectValue::I32(7)));
+    assert_eq!(hit.get_value(&copy_id, "strings"), Some(strings(&["a"])));
+}
+
+#[test]
+fn it_should_edit_a_list_of_strings() {
+    let mut hit = create_hit();
+    let listener = Rc::new(RefCell::new(CountListener { values: vec![] }));
+    hit.subscribe_field("id", "strings", listener.clone())
+        .expect("Error");
+
+    hit.push_string("id", "strings", "b").expect("Error");
+    hit.insert_string_at("id", "strings", 0, "a").expect("Error");
+    hit.push_string("id", "strings", "c").expect("Error");
+    assert_eq!(
+        hit.remove_string_at("id", "strings", 1).expect("Error"),
+        "b"
+    );
+
+    assert_eq!(hit.get_value("id", "strings"), Some(strings(&["a", "c"])));
+    assert_eq!(
+        listener.borrow().values,
+        vec![
+            strings(&["b"]),
+            strings(&["a", "b"]),
+            strings(&["a", "b", "c"]),
+            strings(&["a", "c"]),
+        ]
+    );
+
+    assert!(matches!(
+        hit.insert_string_at("id", "strings", 3, "d"),
+        Err(HitError::InvalidStringIndex(3))
+    ));
+    assert!(matches!(
+        hit.remove_string_at("id", "strings", 2),
+        Err(HitError::InvalidStringIndex(2))
+    ));
+    assert!(matches!(
+        hit.push_string("id", "string", "d"),
+        Err(HitError::InvalidDataType())
+    ));
+    assert_eq!(listener.borrow().values.len(), 4);
+}
+
+#[test]
+fn it_should_undo_the_edits_of_a_list_of_strings() {
+    let mut hit = create_hit();
+    hit.enable_history(10);
+    hit.push_string("id", "strings", "a").expect("Error");
+    hit.push_string("id", "strings", "b").expect("Error");
+    hit.remove_string_at("id", "strings", 0).expect("Error");
+
+    hit.undo().expect("Error");
+    assert_eq!(hit.get_value("id", "strings"), Some(strings(&["a", "b"])));
+    hit.undo().expect("Error");
+    hit.undo().expect("Error");
+    assert_eq!(hit.get_value("id", "strings"), Some(ObjectValue::Null));
+}
+
+#[test]
+fn it_should_patch_integers_and_lists_of_strings() {
+    let old = create_hit();
+    let mut new = create_hit();
+    new.set("id", "integer", ObjectValue::I32(2)).expect("Error");
+    new.push_string("id", "strings", "a").expect("Error");
+
+    let patch = create_patch(old.clone(), &new);
+    assert_eq!(patch.differences.len(), 2);
+    let patched = apply_patches(&old, vec![patch]).expect("Error");
+    assert_eq!(patched.get_value("id", "integer"), Some(ObjectValue::I32(2)));
+    assert_eq!(patched.get_value("id", "strings"), Some(strings(&["a"])));
+}
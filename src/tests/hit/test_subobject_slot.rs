@@ -0,0 +1,198 @@
+use std::rc::Rc;
+
+use crate::field_types::{
+    FieldTypeString, FieldTypeSubobject, FieldTypeSubobjectArray, SlotPolicy,
+};
+use crate::tests::hit::test_history::dump;
+use crate::{
+    modele, Hit, HitError, HitKernel, IndexEntryProperty, KernelBuilder, LinkedHashMap,
+    ObjectValue, Reference,
+};
+
+fn create_kernel() -> Rc<HitKernel> {
+    let kernel = KernelBuilder::new()
+        .add_model(modele!("test/page", "Page" =>
+            "body": FieldTypeSubobject {
+                authorized_models: vec!["test/block".into()]
+            },
+            "cover": FieldTypeSubobject {
+                authorized_models: vec!["test/block".into()]
+            },
+            "current": FieldTypeSubobject {
+                authorized_models: vec!["test/block".into()]
+            },
+            "history": FieldTypeSubobjectArray {
+                authorized_models: vec!["test/block".into()]
+            },
+            slot_policies:
+                "cover" => SlotPolicy::Replace,
+                "current" => SlotPolicy::MoveTo("history".into())
+        ))
+        .add_model(modele!("test/block", "Block" =>
+            "text": FieldTypeString {},
+            "note": FieldTypeSubobject {
+                authorized_models: vec!["test/block".into()]
+            }
+        ))
+        .mark_instantiable("test/page")
+        .build()
+        .expect("Error");
+    Rc::new(kernel)
+}
+
+fn field(id: &str, property: &str) -> IndexEntryProperty {
+    IndexEntryProperty {
+        id: id.into(),
+        property: property.into(),
+    }
+}
+
+fn subobject(id: &str) -> Option<ObjectValue> {
+    Some(ObjectValue::SubObject(Reference { id: id.into() }))
+}
+
+fn insert_block(hit: &mut Hit, id: &str, target: IndexEntryProperty) -> Result<(), HitError> {
+    hit.insert("test/block", id, LinkedHashMap::new(), target, None)
+}
+
+fn create_hit() -> Hit {
+    let mut hit = Hit::new("id", "test/page", create_kernel()).expect("Error");
+    insert_block(&mut hit, "a", field("id", "body")).expect("Error");
+    hit
+}
+
+#[test]
+fn it_should_insert_and_remove_an_object_in_a_single_subobject_field() {
+    let mut hit = create_hit();
+    assert_eq!(hit.get_value("id", "body"), subobject("a"));
+    assert_eq!(hit.get_parent("a"), Some(field("id", "body")));
+    assert_eq!(
+        hit.insert(
+            "test/block",
+            "b",
+            LinkedHashMap::new(),
+            field("id", "cover"),
+            Some("a".into())
+        ),
+        Err(HitError::InvalidBeforeId("a".into()))
+    );
+
+    hit.remove_object("a").expect("Error");
+    assert_eq!(hit.get_value("id", "body"), Some(ObjectValue::Null));
+    assert!(hit.get("a").is_none());
+}
+
+#[test]
+fn it_should_apply_the_policy_of_an_occupied_field() {
+    let mut hit = create_hit();
+    // the default policy refuses the insert, and keeps the document unchanged
+    let before = dump(&hit);
+    assert_eq!(
+        insert_block(&mut hit, "b", field("id", "body")),
+        Err(HitError::OccupiedSubobjectField("id".into(), "body".into()))
+    );
+    assert_eq!(dump(&hit), before);
+
+    insert_block(&mut hit, "b", field("id", "cover")).expect("Error");
+    insert_block(&mut hit, "c", field("id", "cover")).expect("Error");
+    assert_eq!(hit.get_value("id", "cover"), subobject("c"));
+    assert!(hit.get("b").is_none());
+
+    insert_block(&mut hit, "d", field("id", "current")).expect("Error");
+    insert_block(&mut hit, "e", field("id", "current")).expect("Error");
+    assert_eq!(hit.get_value("id", "current"), subobject("e"));
+    assert_eq!(
+        hit.get_value("id", "history"),
+        Some(ObjectValue::VecSubObjects(vec![Reference {
+            id: "d".into()
+        }]))
+    );
+    assert_eq!(hit.get_parent("d"), Some(field("id", "history")));
+}
+
+#[test]
+fn it_should_move_an_object_to_a_single_subobject_field() {
+    let mut hit = create_hit();
+    insert_block(&mut hit, "b", field("id", "history")).expect("Error");
+
+    assert_eq!(
+        hit.move_object("b", field("id", "body"), None),
+        Err(HitError::OccupiedSubobjectField("id".into(), "body".into()))
+    );
+    // moving an object to the field that contains it changes nothing
+    hit.move_object("a", field("id", "body"), None)
+        .expect("Error");
+    assert_eq!(hit.get_value("id", "body"), subobject("a"));
+
+    hit.move_object("a", field("id", "current"), None)
+        .expect("Error");
+    assert_eq!(hit.get_value("id", "body"), Some(ObjectValue::Null));
+    hit.move_object("b", field("id", "current"), None)
+        .expect("Error");
+    assert_eq!(hit.get_value("id", "current"), subobject("b"));
+    assert_eq!(
+        hit.get_value("id", "history"),
+        Some(ObjectValue::VecSubObjects(vec![Reference {
+            id: "a".into()
+        }]))
+    );
+    assert_eq!(
+        hit.move_object("a", field("a", "note"), None),
+        Err(HitError::CannotBeOwnParent("a".into()))
+    );
+}
+
+#[test]
+fn it_should_copy_an_object_with_a_single_subobject() {
+    let mut hit = create_hit();
+    insert_block(&mut hit, "note", field("a", "note")).expect("Error");
+    hit.set("note", "text", ObjectValue::String("hello".into()))
+        .expect("Error");
+
+    let new_id = hit
+        .copy_object("a".into(), field("id", "cover"), None)
+        .expect("Error");
+    let new_note = match hit.get_value(&new_id, "note") {
+        Some(ObjectValue::SubObject(reference)) => reference.id,
+        value => panic!("Unexpected value {:?}", value),
+    };
+    assert_ne!(new_note, "note");
+    assert_eq!(
+        hit.get_value(&new_note, "text"),
+        Some(ObjectValue::String("hello".into()))
+    );
+
+    // the copy replaces the previous one, in a single undo step
+    hit.enable_history(10);
+    let before = dump(&hit);
+    hit.copy_object("a".into(), field("id", "cover"), None)
+        .expect("Error");
+    assert!(hit.get(&new_id).is_none());
+    hit.undo().expect("Error");
+    assert_eq!(dump(&hit), before);
+}
+
+#[test]
+fn it_should_inherit_the_slot_policies_of_the_base_model() {
+    let kernel = KernelBuilder::new()
+        .add_model(modele!("test/page", "Page" =>
+            "cover": FieldTypeSubobject {
+                authorized_models: vec!["test/block".into()]
+            },
+            slot_policies: "cover" => SlotPolicy::Replace
+        ))
+        .add_model(modele!("test/article", "Article" =>
+            "title": FieldTypeString {},
+            extends: "test/page"
+        ))
+        .add_model(modele!("test/block", "Block" =>
+            "text": FieldTypeString {}
+        ))
+        .build()
+        .expect("Error");
+    let mut hit = Hit::new("id", "test/article", Rc::new(kernel)).expect("Error");
+    insert_block(&mut hit, "a", field("id", "cover")).expect("Error");
+    insert_block(&mut hit, "b", field("id", "cover")).expect("Error");
+    assert_eq!(hit.get_value("id", "cover"), subobject("b"));
+    assert!(hit.get("a").is_none());
+}
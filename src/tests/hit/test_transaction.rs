@@ -0,0 +1,378 @@
+use linked_hash_map::LinkedHashMap;
+
+use crate::test_kernel::{create_fs_hit, create_fs_kernel_builder, sub_items};
+use crate::tests::hit::test_history::dump;
+use crate::{
+    field_types::*, modele, FieldListener, Hit, HitError, Id, IndexEntryProperty, Kernel, Model,
+    ModelTypeIndexer, ObjectValue, ObjectValues, Plugin, Plugins, ValidationError,
+};
+use std::{cell::RefCell, rc::Rc};
+
+// refuses to insert objects named "forbidden", after the index has been updated
+struct FailingPlugin {}
+
+impl Plugin for FailingPlugin {
+    fn on_before_add_entry(
+        &mut self,
+        _model: Rc<Model>,
+        _id: &str,
+        _data: ObjectValues,
+        _parent: IndexEntryProperty,
+        _before_id: &Option<Id>,
+        _instance: &Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+
+    fn on_after_add_entry(
+        &mut self,
+        _model: Rc<Model>,
+        id: &str,
+        _data: ObjectValues,
+        _parent: IndexEntryProperty,
+        _before_id: &Option<Id>,
+        _instance: &mut Hit,
+    ) -> Result<(), HitError> {
+        if id == "forbidden" {
+            return Err(HitError::ValidationError());
+        }
+        Ok(())
+    }
+
+    fn on_before_set_value(
+        &mut self,
+        _property: IndexEntryProperty,
+        _value: &ObjectValue,
+        _old_value: &Option<ObjectValue>,
+        _instance: &Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+
+    fn on_after_set_value(
+        &mut self,
+        _property: IndexEntryProperty,
+        _value: &ObjectValue,
+        _old_value: &Option<ObjectValue>,
+        _instance: &mut Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+
+    fn on_before_move_subobject(
+        &mut self,
+        _id: &str,
+        _target: IndexEntryProperty,
+        _before_id: Option<String>,
+        _instance: &Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+
+    fn on_after_move_subobject(
+        &mut self,
+        _id: &str,
+        _target: IndexEntryProperty,
+        _original_parent: IndexEntryProperty,
+        _before_id: Option<String>,
+        _instance: &mut Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+}
+
+pub struct TestTransactionKernel {
+    model: Rc<Model>,
+    indexer: Rc<RefCell<ModelTypeIndexer>>,
+}
+
+impl Kernel for TestTransactionKernel {
+    fn get_model(&self, name: &str) -> Result<Rc<Model>, HitError> {
+        if name == "test/test" {
+            return Ok(self.model.clone());
+        }
+        Err(HitError::ModelDoesNotExist(name.into()))
+    }
+
+    fn get_instantiable_models(&self) -> Vec<&Model> {
+        vec![&self.model]
+    }
+
+    fn get_plugins(&self) -> Plugins {
+        let mut plugins = Plugins::new();
+        plugins.plugins.push(self.indexer.clone());
+        plugins.delete_plugins.push(self.indexer.clone());
+        plugins.init_plugins.push(self.indexer.clone());
+        plugins.transaction_plugins.push(self.indexer.clone());
+        plugins
+            .plugins
+            .push(Rc::new(RefCell::new(FailingPlugin {})));
+        plugins
+    }
+
+    fn get_models(&self) -> Vec<String> {
+        vec!["test/test".to_string()]
+    }
+}
+
+fn create_test_transaction_kernel() -> TestTransactionKernel {
+    TestTransactionKernel {
+        model: modele!("test/test", "Test" =>
+            "name": FieldTypeString {
+                required: true
+            },
+            "sub_items": FieldTypeSubobjectArray {
+                authorized_models: vec!["test/test".to_string()]
+            },
+            "reference": FieldTypeReference {
+                authorized_models: vec!["test/test".to_string()]
+            }
+        ),
+        indexer: Rc::new(RefCell::new(ModelTypeIndexer::new())),
+    }
+}
+
+struct CountListener<T> {
+    id: String,
+    values: Vec<T>,
+}
+
+impl<T: Clone> FieldListener<T> for CountListener<T> {
+    fn on_update(&mut self, value: &T) {
+        self.values.push(value.clone());
+    }
+    fn get_unique_id(&self) -> &str {
+        &self.id
+    }
+}
+
+fn insert(hit: &mut Hit, id: &str, name: &str) -> Result<(), HitError> {
+    let mut values = LinkedHashMap::new();
+    values.insert("name".to_string(), ObjectValue::String(name.into()));
+    hit.insert("test/test", id, values, sub_items("id"), None)
+}
+
+fn create_hit(kernel: Rc<TestTransactionKernel>) -> Hit {
+    let mut hit = Hit::new("id", "test/test", kernel).expect("Error");
+    insert(&mut hit, "id2", "child").expect("Error");
+    hit
+}
+
+#[test]
+fn it_should_commit_all_the_mutations_of_a_transaction() {
+    let mut hit = create_hit(Rc::new(create_test_transaction_kernel()));
+    let result = hit.transaction(|tx| {
+        insert(tx, "id3", "child 2")?;
+        tx.set("id2", "name", ObjectValue::String("renamed".into()))?;
+        tx.remove_object("id3")?;
+        insert(tx, "id4", "child 3")?;
+        Ok("done")
+    });
+
+    assert_eq!(result, Ok("done"));
+    assert!(!hit.is_in_transaction());
+    assert_eq!(
+        hit.get_value("id2", "name"),
+        Some(ObjectValue::String("renamed".into()))
+    );
+    assert!(hit.get("id3").is_none());
+    assert!(hit.get("id4").is_some());
+}
+
+#[test]
+fn it_should_restore_the_state_when_a_transaction_fails() {
+    let kernel = Rc::new(create_test_transaction_kernel());
+    let mut hit = create_hit(kernel.clone());
+    // the root object has no name yet
+    assert_eq!(hit.get_validation_errors("id", "name").unwrap().len(), 1);
+
+    let result = hit.transaction(|tx| {
+        tx.set("id", "name", ObjectValue::String("root".into()))?;
+        tx.set("id2", "name", ObjectValue::String("renamed".into()))?;
+        tx.set(
+            "id",
+            "reference",
+            ObjectValue::Reference(crate::Reference { id: "id2".into() }),
+        )?;
+        insert(tx, "id3", "child 2")?;
+        tx.remove_object("id3")?;
+        // the after_add_entry hook fails after the index was updated
+        insert(tx, "forbidden", "child 3")?;
+        Ok(())
+    });
+
+    assert_eq!(result, Err(HitError::ValidationError()));
+    assert_eq!(hit.get_value("id", "name"), Some(ObjectValue::Null));
+    assert_eq!(
+        hit.get_value("id2", "name"),
+        Some(ObjectValue::String("child".into()))
+    );
+    assert_eq!(hit.get_value("id", "reference"), Some(ObjectValue::Null));
+    assert_eq!(hit.get_references("id2"), Ok(vec![]));
+    assert!(hit.get("forbidden").is_none());
+    assert!(!hit.contains_key("forbidden"));
+    assert!(!hit.contains_key("id3"));
+    assert_eq!(
+        hit.get_value("id", "sub_items"),
+        Some(ObjectValue::VecSubObjects(vec![crate::Reference {
+            id: "id2".into()
+        }]))
+    );
+    assert_eq!(hit.get_validation_errors("id", "name").unwrap().len(), 1);
+    assert_eq!(
+        kernel.indexer.borrow().get("test/test"),
+        Some(&vec!["id2".to_string()])
+    );
+
+    // the instance is still usable after a rollback
+    insert(&mut hit, "id3", "child 2").expect("Error");
+    assert_eq!(
+        kernel.indexer.borrow().get("test/test"),
+        Some(&vec!["id2".to_string(), "id3".to_string()])
+    );
+}
+
+#[test]
+fn it_should_restore_a_deleted_object_when_a_transaction_fails() {
+    let mut hit = create_hit(Rc::new(create_test_transaction_kernel()));
+    let listener = Rc::new(RefCell::new(CountListener::<ObjectValue> {
+        id: "listener".into(),
+        values: vec![],
+    }));
+    hit.subscribe_field("id2", "name", listener.clone())
+        .expect("Error");
+
+    let result: Result<(), HitError> = hit.transaction(|tx| {
+        tx.remove_object("id2")?;
+        Err(HitError::ValidationError())
+    });
+
+    assert!(result.is_err());
+    assert_eq!(
+        hit.get_value("id2", "name"),
+        Some(ObjectValue::String("child".into()))
+    );
+    assert_eq!(hit.get_parent_index("id2"), Some(0));
+    // listeners of the restored object are still attached
+    hit.set("id2", "name", ObjectValue::String("renamed".into()))
+        .expect("Error");
+    assert_eq!(listener.borrow().values.len(), 1);
+}
+
+#[test]
+fn it_should_notify_field_listeners_only_when_the_transaction_is_committed() {
+    let mut hit = create_hit(Rc::new(create_test_transaction_kernel()));
+    let listener = Rc::new(RefCell::new(CountListener::<ObjectValue> {
+        id: "listener".into(),
+        values: vec![],
+    }));
+    let validation_listener = Rc::new(RefCell::new(CountListener::<Vec<ValidationError>> {
+        id: "validation_listener".into(),
+        values: vec![],
+    }));
+    hit.subscribe_field("id2", "name", listener.clone())
+        .expect("Error");
+    hit.subscribe_field_validation("id2", "name", validation_listener.clone());
+
+    hit.transaction(|tx| {
+        tx.set("id2", "name", ObjectValue::String("a".into()))?;
+        tx.set("id2", "name", ObjectValue::String("b".into()))?;
+        assert_eq!(listener.borrow().values.len(), 0);
+        assert_eq!(validation_listener.borrow().values.len(), 0);
+        Ok(())
+    })
+    .expect("Error");
+
+    assert_eq!(
+        listener.borrow().values,
+        vec![ObjectValue::String("b".into())]
+    );
+    assert_eq!(validation_listener.borrow().values, vec![vec![]]);
+}
+
+#[test]
+fn it_should_not_notify_field_listeners_when_the_transaction_fails() {
+    let mut hit = create_hit(Rc::new(create_test_transaction_kernel()));
+    let listener = Rc::new(RefCell::new(CountListener::<ObjectValue> {
+        id: "listener".into(),
+        values: vec![],
+    }));
+    hit.subscribe_field("id", "sub_items", listener.clone())
+        .expect("Error");
+
+    let result = hit.transaction(|tx| insert(tx, "forbidden", "child 2"));
+
+    assert!(result.is_err());
+    assert_eq!(listener.borrow().values.len(), 0);
+}
+
+#[test]
+fn it_should_only_roll_back_the_failing_nested_transaction() {
+    let kernel = Rc::new(create_test_transaction_kernel());
+    let mut hit = create_hit(kernel.clone());
+
+    hit.transaction(|tx| {
+        insert(tx, "id3", "child 2")?;
+        let nested = tx.transaction(|tx| {
+            insert(tx, "id4", "child 3")?;
+            insert(tx, "forbidden", "child 4")
+        });
+        assert!(nested.is_err());
+        assert!(tx.is_in_transaction());
+        Ok(())
+    })
+    .expect("Error");
+
+    assert!(hit.get("id3").is_some());
+    assert!(hit.get("id4").is_none());
+    assert_eq!(
+        kernel.indexer.borrow().get("test/test"),
+        Some(&vec!["id2".to_string(), "id3".to_string()])
+    );
+}
+
+#[test]
+fn it_should_restore_the_model_type_index_after_a_failed_deletion() {
+    let kernel = Rc::new(create_test_transaction_kernel());
+    let mut hit = create_hit(kernel.clone());
+
+    let result: Result<(), HitError> = hit.transaction(|tx| {
+        tx.remove_object("id2")?;
+        assert_eq!(kernel.indexer.borrow().get("test/test"), Some(&vec![]));
+        Err(HitError::ValidationError())
+    });
+
+    assert!(result.is_err());
+    assert_eq!(
+        kernel.indexer.borrow().get("test/test"),
+        Some(&vec!["id2".to_string()])
+    );
+}
+
+#[test]
+fn it_should_restore_the_moves_and_the_references_when_a_transaction_fails() {
+    let kernel = create_fs_kernel_builder().build().expect("Error");
+    let mut hit = create_fs_hit(Rc::new(kernel));
+    let field = |id: &str, property: &str| IndexEntryProperty {
+        id: id.into(),
+        property: property.into(),
+    };
+    let before = dump(&hit);
+
+    let result: Result<(), HitError> = hit.transaction(|tx| {
+        tx.move_object("lib", field("docs", "items"), None)?;
+        tx.move_object("readme", field("src", "items"), None)?;
+        tx.insert_reference("lib", field("guide", "tags"), None)?;
+        tx.remove_reference("main", field("guide", "tags"))?;
+        tx.set("guide", "link", ObjectValue::Null)?;
+        tx.remove_object("src")?;
+        Err(HitError::ValidationError())
+    });
+
+    assert_eq!(result, Err(HitError::ValidationError()));
+    assert_eq!(dump(&hit), before);
+    assert_eq!(
+        hit.get_model("main").map(|model| model.get_name().clone()),
+        Some("fs/file".into())
+    );
+}
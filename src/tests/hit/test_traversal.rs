@@ -0,0 +1,93 @@
+use std::rc::Rc;
+
+use crate::test_kernel::{create_fs_hit, create_fs_kernel_builder};
+use crate::{Hit, TraversalEntry, TraversalOrder};
+
+fn create_hit() -> Hit {
+    create_fs_hit(Rc::new(create_fs_kernel_builder().build().expect("Error")))
+}
+
+fn summarize<I: Iterator<Item = TraversalEntry>>(entries: I) -> Vec<(String, usize, usize)> {
+    entries
+        .map(|entry| (entry.get_id(), entry.depth, entry.position))
+        .collect()
+}
+
+fn item(id: &str, depth: usize, position: usize) -> (String, usize, usize) {
+    (id.to_string(), depth, position)
+}
+
+#[test]
+fn it_should_walk_the_descendants_in_both_orders() {
+    let hit = create_hit();
+    assert_eq!(
+        summarize(hit.descendants("root", TraversalOrder::DepthFirst)),
+        vec![
+            item("src", 1, 0),
+            item("main", 2, 0),
+            item("lib", 2, 1),
+            item("docs", 1, 1),
+            item("guide", 2, 0),
+            item("readme", 1, 0),
+        ]
+    );
+    assert_eq!(
+        summarize(hit.descendants("root", TraversalOrder::BreadthFirst)),
+        vec![
+            item("src", 1, 0),
+            item("docs", 1, 1),
+            item("readme", 1, 0),
+            item("main", 2, 0),
+            item("lib", 2, 1),
+            item("guide", 2, 0),
+        ]
+    );
+    assert_eq!(
+        summarize(hit.descendants("src", TraversalOrder::DepthFirst)),
+        vec![item("main", 1, 0), item("lib", 1, 1)]
+    );
+    assert_eq!(
+        hit.descendants("unknown", TraversalOrder::DepthFirst)
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn it_should_prune_a_subtree_during_the_walk() {
+    let hit = create_hit();
+    for order in [TraversalOrder::DepthFirst, TraversalOrder::BreadthFirst].iter() {
+        let mut descendants = hit.descendants("root", *order);
+        let mut ids = vec![];
+        while let Some(entry) = descendants.next() {
+            if entry.get_id() == "src" || entry.get_id() == "docs" {
+                descendants.prune();
+            }
+            ids.push(entry.get_id());
+        }
+        assert_eq!(ids, vec!["src", "docs", "readme"]);
+    }
+}
+
+#[test]
+fn it_should_list_the_ancestors_children_and_siblings() {
+    let hit = create_hit();
+    assert_eq!(
+        summarize(hit.ancestors("lib")),
+        vec![item("src", 1, 0), item("root", 2, 0)]
+    );
+    assert_eq!(hit.ancestors("root").count(), 0);
+    assert_eq!(
+        summarize(hit.children("src", "items")),
+        vec![item("main", 1, 0), item("lib", 1, 1)]
+    );
+    assert_eq!(
+        summarize(hit.children("root", "readme")),
+        vec![item("readme", 1, 0)]
+    );
+    assert_eq!(hit.children("root", "name").count(), 0);
+    assert_eq!(summarize(hit.siblings("lib")), vec![item("main", 0, 0)]);
+    assert_eq!(summarize(hit.siblings("docs")), vec![item("src", 0, 0)]);
+    assert_eq!(hit.siblings("readme").count(), 0);
+    assert_eq!(hit.siblings("root").count(), 0);
+}
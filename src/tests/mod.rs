@@ -1,6 +1,8 @@
 mod hit;
 mod object_data;
 mod plugins;
+mod test_crdt;
 mod test_events;
 mod test_helpers;
+mod test_validate_kernel;
 mod validation;
@@ -8,3 +8,12 @@ fn it_should_return_a_datetime_struct() {
     let date = DateTimeUtc::new(date);
     assert_eq!("Thu, 01 Jan 1970 00:01:01 +0000", date.get_date_as_string());
 }
+
+#[test]
+fn it_should_create_a_date_from_a_timestamp() {
+    let date = DateTimeUtc::from_timestamp(61);
+    assert_eq!(date.get_nanoseconds(), 0);
+    assert_eq!(date, DateTimeUtc::from_timestamp_nanos(61, 0));
+    let date = DateTimeUtc::from_timestamp_nanos(61, 500_000_000);
+    assert_eq!("1970-01-01T00:01:01.500Z", date.get_date_as_rfc3339());
+}
@@ -0,0 +1,547 @@
+use linked_hash_map::LinkedHashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::test_kernel::{
+    create_test_kernel, create_test_kernel_with_all_types, sub_items, TestListener,
+};
+use crate::tests::hit::test_history::{dump, EntryDump};
+use crate::{
+    export, CrdtMessage, Hit, HitError, HitKernel, Id, IndexEntryProperty, Kernel, KernelBuilder,
+    Model, ObjectValue, ObjectValues, Plugin, Reference, Replica,
+};
+
+struct Peer {
+    replica: Replica,
+    receiver: Receiver<Vec<CrdtMessage>>,
+}
+
+// replicas exchanging their messages through channels
+struct Network {
+    peers: Vec<Peer>,
+    senders: Vec<Sender<Vec<CrdtMessage>>>,
+}
+
+impl Network {
+    fn new(replica_ids: &[&str]) -> Network {
+        Network::from_hit(replica_ids, &create_hit())
+    }
+
+    fn from_hit(replica_ids: &[&str], hit: &Hit) -> Network {
+        let mut peers = vec![];
+        let mut senders = vec![];
+        for replica_id in replica_ids.iter() {
+            let (sender, receiver) = channel();
+            peers.push(Peer {
+                replica: Replica::new(replica_id, hit).expect("Error"),
+                receiver,
+            });
+            senders.push(sender);
+        }
+        Network { peers, senders }
+    }
+
+    fn replica(&mut self, index: usize) -> &mut Replica {
+        &mut self.peers[index].replica
+    }
+
+    fn send(&mut self, index: usize) {
+        let messages = self.peers[index].replica.take_messages();
+        for (other, sender) in self.senders.iter().enumerate() {
+            if other != index {
+                sender.send(messages.clone()).expect("Error");
+            }
+        }
+    }
+
+    fn receive(&mut self, index: usize) {
+        let peer = &mut self.peers[index];
+        for messages in peer.receiver.try_iter() {
+            peer.replica.receive(&messages).expect("Error");
+        }
+    }
+
+    fn sync(&mut self) {
+        for index in 0..self.peers.len() {
+            self.send(index);
+        }
+        for index in 0..self.peers.len() {
+            self.receive(index);
+        }
+    }
+
+    fn assert_converged(&self) {
+        let expected = dump_values(self.peers[0].replica.get_hit());
+        for peer in self.peers.iter() {
+            assert_eq!(dump_values(peer.replica.get_hit()), expected);
+        }
+    }
+}
+
+fn is_empty(value: &ObjectValue) -> bool {
+    match value {
+        ObjectValue::Null => true,
+        ObjectValue::VecReference(list) | ObjectValue::VecSubObjects(list) => list.is_empty(),
+        _ => false,
+    }
+}
+
+// The replicas apply the changes in a different order : an empty field can be missing
+// on one replica and be null or an empty list on another one.
+fn dump_values(hit: &Hit) -> Vec<EntryDump> {
+    dump(hit)
+        .into_iter()
+        .map(|(id, mut data, parent, references)| {
+            data.retain(|(_, value)| !is_empty(value));
+            (id, data, parent, references)
+        })
+        .collect()
+}
+
+fn references(id: &str) -> IndexEntryProperty {
+    IndexEntryProperty {
+        id: id.into(),
+        property: "references".into(),
+    }
+}
+
+fn to_references(ids: &[&str]) -> Vec<Reference> {
+    ids.iter().map(|id| Reference { id: id.to_string() }).collect()
+}
+
+// main contains a, b and c, a contains d
+fn create_hit() -> Hit {
+    create_hit_with_kernel(Rc::new(create_test_kernel()))
+}
+
+fn create_hit_with_kernel(kernel: Rc<HitKernel>) -> Hit {
+    let mut hit = Hit::new("main", "test/test", kernel).expect("Error");
+    for (id, parent) in [("a", "main"), ("b", "main"), ("c", "main"), ("d", "a")].iter() {
+        hit.insert("test/test", id, LinkedHashMap::new(), sub_items(parent), None)
+            .expect("Error");
+    }
+    hit
+}
+
+#[test]
+fn it_should_start_from_the_same_document() {
+    let network = Network::new(&["r1", "r2"]);
+    network.assert_converged();
+    assert_eq!(
+        export(network.peers[0].replica.get_hit()),
+        export(&create_hit())
+    );
+}
+
+#[test]
+fn it_should_keep_the_last_value_written_to_a_field() {
+    let mut network = Network::new(&["r1", "r2", "r3"]);
+    network
+        .replica(0)
+        .edit(|hit| hit.set("a", "name", ObjectValue::String("r1".into())))
+        .expect("Error");
+    network
+        .replica(1)
+        .edit(|hit| hit.set("a", "name", ObjectValue::String("r2".into())))
+        .expect("Error");
+    network
+        .replica(2)
+        .edit(|hit| hit.set("b", "age", ObjectValue::F32(2.0)))
+        .expect("Error");
+
+    network.sync();
+
+    network.assert_converged();
+    let hit = network.replica(0).get_hit();
+    // same counter : the replica id decides
+    assert_eq!(
+        hit.get_value("a", "name"),
+        Some(ObjectValue::String("r2".into()))
+    );
+    assert_eq!(hit.get_value("b", "age"), Some(ObjectValue::F32(2.0)));
+}
+
+#[test]
+fn it_should_keep_the_objects_inserted_concurrently() {
+    let mut network = Network::new(&["r1", "r2"]);
+    network
+        .replica(0)
+        .edit(|hit| {
+            hit.insert("test/test", "e", LinkedHashMap::new(), sub_items("main"), Some("b".into()))?;
+            hit.insert("test/test", "f", LinkedHashMap::new(), sub_items("main"), None)
+        })
+        .expect("Error");
+    network
+        .replica(1)
+        .edit(|hit| {
+            hit.insert("test/test", "g", LinkedHashMap::new(), sub_items("main"), Some("b".into()))?;
+            hit.move_object("c", sub_items("main"), Some("a".into()))
+        })
+        .expect("Error");
+
+    network.sync();
+
+    network.assert_converged();
+    match network.replica(0).get_hit().get_value("main", "sub_items") {
+        Some(ObjectValue::VecSubObjects(list)) => {
+            let ids: Vec<String> = list.into_iter().map(|r| r.id).collect();
+            assert_eq!(ids[..2], ["c".to_string(), "a".to_string()]);
+            // e and g were inserted at the same place
+            assert!(ids[2..4].contains(&"e".to_string()) && ids[2..4].contains(&"g".to_string()));
+            assert_eq!(ids[4..], ["b".to_string(), "f".to_string()]);
+        }
+        _ => panic!("Expected a list of subobjects"),
+    }
+}
+
+#[test]
+fn it_should_not_create_cycles_with_concurrent_moves() {
+    let mut network = Network::new(&["r1", "r2"]);
+    network
+        .replica(0)
+        .edit(|hit| hit.move_object("b", sub_items("c"), None))
+        .expect("Error");
+    network
+        .replica(1)
+        .edit(|hit| hit.move_object("c", sub_items("b"), None))
+        .expect("Error");
+
+    network.sync();
+
+    network.assert_converged();
+    let hit = network.replica(0).get_hit();
+    // the move of r2 is the latest one, and would put c inside itself
+    assert_eq!(hit.get_parent("b"), Some(sub_items("c")));
+    assert_eq!(hit.get_parent("c"), Some(sub_items("main")));
+    assert_eq!(
+        hit.get_value("c", "sub_items"),
+        Some(ObjectValue::VecSubObjects(to_references(&["b"])))
+    );
+}
+
+#[test]
+fn it_should_keep_an_object_moved_out_of_a_deleted_object() {
+    let mut network = Network::new(&["r1", "r2"]);
+    network
+        .replica(0)
+        .edit(|hit| {
+            hit.remove_object("a")?;
+            Ok(())
+        })
+        .expect("Error");
+    network
+        .replica(1)
+        .edit(|hit| {
+            hit.insert("test/test", "e", LinkedHashMap::new(), sub_items("a"), None)?;
+            hit.move_object("d", sub_items("b"), None)
+        })
+        .expect("Error");
+
+    network.sync();
+
+    network.assert_converged();
+    let hit = network.replica(0).get_hit();
+    assert!(hit.get("a").is_none());
+    assert!(hit.get("e").is_none());
+    assert_eq!(hit.get_parent("d"), Some(sub_items("b")));
+}
+
+#[test]
+fn it_should_merge_the_references_lists() {
+    let mut network = Network::new(&["r1", "r2"]);
+    network
+        .replica(0)
+        .edit(|hit| {
+            hit.insert_reference("b", references("a"), None)?;
+            hit.insert_reference("c", references("a"), None)
+        })
+        .expect("Error");
+    network.sync();
+    network
+        .replica(0)
+        .edit(|hit| hit.insert_reference("d", references("a"), Some("c".into())))
+        .expect("Error");
+    network
+        .replica(1)
+        .edit(|hit| {
+            hit.remove_reference("b", references("a"))?;
+            hit.move_reference("c", references("a"), None)?;
+            hit.set("b", "reference", ObjectValue::Reference(Reference { id: "c".into() }))
+        })
+        .expect("Error");
+    network
+        .replica(0)
+        .edit(|hit| {
+            hit.remove_reference("c", references("a"))?;
+            hit.remove_object("c")?;
+            Ok(())
+        })
+        .expect("Error");
+
+    network.sync();
+
+    network.assert_converged();
+    let hit = network.replica(0).get_hit();
+    assert_eq!(
+        hit.get_value("a", "references"),
+        Some(ObjectValue::VecReference(to_references(&["d"])))
+    );
+    // the reference to the deleted object is ignored
+    assert_eq!(hit.get_value("b", "reference"), Some(ObjectValue::Null));
+    assert_eq!(hit.get_references("d").expect("Error"), vec![references("a")]);
+}
+
+#[test]
+fn it_should_converge_whatever_the_order_of_the_messages() {
+    let mut network = Network::new(&["r1", "r2", "r3"]);
+    let mut messages = vec![];
+    network
+        .replica(0)
+        .edit(|hit| {
+            hit.insert("test/test", "e", LinkedHashMap::new(), sub_items("c"), None)?;
+            hit.set("e", "name", ObjectValue::String("e".into()))
+        })
+        .expect("Error");
+    messages.extend(network.replica(0).take_messages());
+    network
+        .replica(1)
+        .edit(|hit| {
+            hit.move_object("a", sub_items("b"), None)?;
+            hit.set("d", "name", ObjectValue::String("d".into()))
+        })
+        .expect("Error");
+    messages.extend(network.replica(1).take_messages());
+
+    // r3 receives the messages in the reverse order, some of them twice
+    let mut reversed = messages.clone();
+    reversed.reverse();
+    reversed.extend(messages[..3].iter().cloned());
+    network.replica(2).receive(&reversed).expect("Error");
+    network.replica(0).receive(&messages).expect("Error");
+    network.replica(1).receive(&messages).expect("Error");
+
+    network.assert_converged();
+    let hit = network.replica(2).get_hit();
+    assert_eq!(hit.get_parent("e"), Some(sub_items("c")));
+    assert_eq!(hit.get_parent("a"), Some(sub_items("b")));
+    assert_eq!(
+        hit.get_value("e", "name"),
+        Some(ObjectValue::String("e".into()))
+    );
+}
+
+#[test]
+fn it_should_not_send_invalid_edits() {
+    let mut network = Network::new(&["r1", "r2"]);
+    let result = network.replica(0).edit(|hit| {
+        hit.set("a", "name", ObjectValue::String("a".into()))?;
+        hit.set("a", "name", ObjectValue::F32(1.0))
+    });
+    assert!(result.is_err());
+    assert_eq!(network.replica(0).take_messages(), vec![]);
+    assert_eq!(network.replica(0).get_hit().get_value("a", "name"), Some(ObjectValue::Null));
+}
+
+#[test]
+fn it_should_keep_one_of_the_objects_inserted_in_a_single_subobject_field() {
+    let kernel = Rc::new(create_test_kernel_with_all_types());
+    let hit = Hit::new("main", "test/values", kernel).expect("Error");
+    let mut network = Network::from_hit(&["r1", "r2"], &hit);
+    let sub_item = IndexEntryProperty {
+        id: "main".into(),
+        property: "sub_item".into(),
+    };
+    network
+        .replica(0)
+        .edit(|hit| hit.insert("test/test", "x", LinkedHashMap::new(), sub_item.clone(), None))
+        .expect("Error");
+    network
+        .replica(1)
+        .edit(|hit| hit.insert("test/test", "y", LinkedHashMap::new(), sub_item.clone(), None))
+        .expect("Error");
+
+    network.sync();
+
+    network.assert_converged();
+    let kept = match network.replica(0).get_hit().get_value("main", "sub_item") {
+        Some(ObjectValue::SubObject(reference)) => reference.id,
+        _ => panic!("Expected a subobject"),
+    };
+    let other = if kept == "x" { "y" } else { "x" };
+    assert!(network.replica(0).get_hit().get(other).is_none());
+
+    // the other object takes the place of the removed one
+    network
+        .replica(0)
+        .edit(|hit| {
+            hit.remove_object(&kept)?;
+            Ok(())
+        })
+        .expect("Error");
+    network.sync();
+
+    network.assert_converged();
+    assert_eq!(
+        network.replica(1).get_hit().get_value("main", "sub_item"),
+        Some(ObjectValue::SubObject(Reference { id: other.into() }))
+    );
+}
+
+#[test]
+fn it_should_notify_the_listeners_of_the_received_changes() {
+    let mut network = Network::new(&["r1", "r2"]);
+    let listener = Rc::new(RefCell::new(TestListener { values: vec![] }));
+    network
+        .replica(1)
+        .get_hit()
+        .subscribe_field("a", "name", listener.clone())
+        .expect("Error");
+    network
+        .replica(0)
+        .edit(|hit| hit.set("a", "name", ObjectValue::String("r1".into())))
+        .expect("Error");
+    network
+        .replica(0)
+        .edit(|hit| hit.set("b", "name", ObjectValue::String("r1".into())))
+        .expect("Error");
+
+    network.sync();
+
+    assert_eq!(
+        listener.borrow().values,
+        vec![ObjectValue::String("r1".into())]
+    );
+}
+
+#[test]
+fn it_should_keep_the_history_of_the_local_edits() {
+    let mut network = Network::new(&["r1", "r2"]);
+    network
+        .replica(0)
+        .edit(|hit| {
+            hit.enable_history(10);
+            hit.set("a", "name", ObjectValue::String("r1".into()))
+        })
+        .expect("Error");
+    network
+        .replica(1)
+        .edit(|hit| hit.move_object("c", sub_items("b"), None))
+        .expect("Error");
+
+    network.sync();
+
+    network.assert_converged();
+    let hit = network.replica(0).get_hit();
+    assert_eq!(hit.get_parent("c"), Some(sub_items("b")));
+    assert!(hit.can_undo());
+}
+
+// refuses every update of a value while `rejecting` is set
+struct RejectingPlugin {
+    rejecting: Rc<RefCell<bool>>,
+}
+
+impl Plugin for RejectingPlugin {
+    fn on_before_add_entry(
+        &mut self,
+        _model: Rc<Model>,
+        _id: &str,
+        _data: ObjectValues,
+        _parent: IndexEntryProperty,
+        _before_id: &Option<Id>,
+        _instance: &Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+
+    fn on_after_add_entry(
+        &mut self,
+        _model: Rc<Model>,
+        _id: &str,
+        _data: ObjectValues,
+        _parent: IndexEntryProperty,
+        _before_id: &Option<Id>,
+        _instance: &mut Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+
+    fn on_before_set_value(
+        &mut self,
+        _property: IndexEntryProperty,
+        _value: &ObjectValue,
+        _old_value: &Option<ObjectValue>,
+        _instance: &Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+
+    fn on_after_set_value(
+        &mut self,
+        _property: IndexEntryProperty,
+        _value: &ObjectValue,
+        _old_value: &Option<ObjectValue>,
+        _instance: &mut Hit,
+    ) -> Result<(), HitError> {
+        if *self.rejecting.borrow() {
+            return Err(HitError::ValidationError());
+        }
+        Ok(())
+    }
+
+    fn on_before_move_subobject(
+        &mut self,
+        _id: &str,
+        _target: IndexEntryProperty,
+        _before_id: Option<String>,
+        _instance: &Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+
+    fn on_after_move_subobject(
+        &mut self,
+        _id: &str,
+        _target: IndexEntryProperty,
+        _original_parent: IndexEntryProperty,
+        _before_id: Option<String>,
+        _instance: &mut Hit,
+    ) -> Result<(), HitError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn it_should_apply_the_rejected_changes_again_once_they_are_accepted() {
+    let rejecting = Rc::new(RefCell::new(false));
+    let model = create_test_kernel().get_model("test/test").expect("Error");
+    let kernel = KernelBuilder::new()
+        .add_model(model)
+        .add_plugin(Rc::new(RefCell::new(RejectingPlugin {
+            rejecting: rejecting.clone(),
+        })))
+        .build()
+        .expect("Error");
+    let mut network = Network::from_hit(&["r1", "r2"], &create_hit_with_kernel(Rc::new(kernel)));
+    network
+        .replica(0)
+        .edit(|hit| hit.set("a", "name", ObjectValue::String("r1".into())))
+        .expect("Error");
+    let messages = network.replica(0).take_messages();
+
+    *rejecting.borrow_mut() = true;
+    assert!(network.replica(1).receive(&messages).is_err());
+    assert_eq!(
+        network.replica(1).get_hit().get_value("a", "name"),
+        Some(ObjectValue::Null)
+    );
+
+    *rejecting.borrow_mut() = false;
+    network.replica(1).receive(&[]).expect("Error");
+    network.assert_converged();
+    assert_eq!(
+        network.replica(1).get_hit().get_value("a", "name"),
+        Some(ObjectValue::String("r1".into()))
+    );
+}
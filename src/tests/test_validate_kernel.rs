@@ -0,0 +1,226 @@
+use std::rc::Rc;
+
+use crate::field_types::{
+    FieldTypeBacklinks, FieldTypeReference, FieldTypeReferenceArray, FieldTypeString,
+    FieldTypeSubobject, FieldTypeSubobjectArray, SlotPolicy,
+};
+use crate::test_kernel::create_test_kernel;
+use crate::utils::{validate_kernel, KernelIssue};
+use crate::{modele, HitError, Kernel, Model, Plugins};
+
+// a kernel that is not checked when it is created
+struct UncheckedKernel {
+    models: Vec<Rc<Model>>,
+    instantiable_models: Vec<String>,
+}
+
+impl Kernel for UncheckedKernel {
+    fn get_model(&self, name: &str) -> Result<Rc<Model>, HitError> {
+        match self.models.iter().find(|model| model.get_name() == name) {
+            Some(model) => Ok(model.clone()),
+            None => Err(HitError::ModelDoesNotExist(name.to_string())),
+        }
+    }
+    fn get_instantiable_models(&self) -> Vec<&Model> {
+        self.models
+            .iter()
+            .filter(|model| self.instantiable_models.contains(model.get_name()))
+            .map(|model| model.as_ref())
+            .collect()
+    }
+    fn get_plugins(&self) -> Plugins {
+        Plugins::new()
+    }
+    fn get_models(&self) -> Vec<String> {
+        self.models.iter().map(|model| model.get_name().clone()).collect()
+    }
+}
+
+fn create_kernel(models: Vec<Rc<Model>>) -> UncheckedKernel {
+    UncheckedKernel {
+        models,
+        instantiable_models: vec!["fs/root".into()],
+    }
+}
+
+fn root() -> Rc<Model> {
+    modele!("fs/root", "Root" =>
+        "items": FieldTypeSubobjectArray {
+            authorized_models: vec!["fs/item".into()]
+        }
+    )
+}
+
+#[test]
+fn it_should_not_report_issues_for_a_valid_kernel() {
+    assert_eq!(validate_kernel(&create_test_kernel()), vec![]);
+    let kernel = create_kernel(vec![
+        root(),
+        modele!("fs/folder", "Folder" =>
+            "name": FieldTypeString {},
+            "items": FieldTypeSubobjectArray {
+                authorized_models: vec!["fs/item".into()]
+            },
+            "link": FieldTypeReference {
+                authorized_models: vec!["fs/item".into()]
+            },
+            interfaces: "fs/item"
+        ),
+    ]);
+    assert_eq!(validate_kernel(&kernel), vec![]);
+}
+
+#[test]
+fn it_should_report_unknown_names_and_conflicting_interfaces() {
+    let kernel = create_kernel(vec![
+        root(),
+        modele!("fs/folder", "Folder" =>
+            "items": FieldTypeSubobjectArray {
+                authorized_models: vec!["fs/item".into(), "fs/file".into()]
+            },
+            interfaces: "fs/item", "fs/root", "fs/item"
+        ),
+    ]);
+    assert_eq!(
+        validate_kernel(&kernel),
+        vec![
+            KernelIssue::InterfaceIsAModel("fs/folder".into(), "fs/root".into()),
+            KernelIssue::DuplicateInterface("fs/folder".into(), "fs/item".into()),
+            KernelIssue::UnknownAuthorizedModel(
+                "fs/folder".into(),
+                "items".into(),
+                "fs/file".into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn it_should_report_unreachable_models_and_unsatisfiable_references() {
+    let kernel = create_kernel(vec![
+        root(),
+        modele!("fs/folder", "Folder" =>
+            "tags": FieldTypeReferenceArray {
+                authorized_models: vec!["fs/tag".into()]
+            },
+            interfaces: "fs/item"
+        ),
+        // nothing can contain a tag
+        modele!("fs/tag", "Tag" =>
+            "name": FieldTypeString {}
+        ),
+    ]);
+    assert_eq!(
+        validate_kernel(&kernel),
+        vec![
+            KernelIssue::UnsatisfiableReference("fs/folder".into(), "tags".into()),
+            KernelIssue::UnreachableModel("fs/tag".into()),
+        ]
+    );
+}
+
+#[test]
+fn it_should_report_cyclic_required_subobjects() {
+    let kernel = create_kernel(vec![
+        root(),
+        modele!("fs/folder", "Folder" =>
+            "child": FieldTypeSubobject {
+                required: true,
+                authorized_models: vec!["fs/item".into()]
+            },
+            interfaces: "fs/item"
+        ),
+        // a required subobject that can be created breaks the cycle
+        modele!("fs/file", "File" =>
+            "content": FieldTypeSubobject {
+                required: true,
+                authorized_models: vec!["fs/content".into()]
+            },
+            interfaces: "fs/item"
+        ),
+        modele!("fs/content", "Content" =>
+            "value": FieldTypeString {}
+        ),
+    ]);
+    assert_eq!(validate_kernel(&kernel), vec![]);
+
+    let kernel = create_kernel(vec![
+        root(),
+        modele!("fs/folder", "Folder" =>
+            "child": FieldTypeSubobject {
+                required: true,
+                authorized_models: vec!["fs/folder".into()]
+            },
+            interfaces: "fs/item"
+        ),
+    ]);
+    assert_eq!(
+        validate_kernel(&kernel),
+        vec![KernelIssue::CyclicRequiredSubobject(
+            "fs/folder".into(),
+            "child".into()
+        )]
+    );
+}
+
+#[test]
+fn it_should_report_the_backlinks_without_source_field() {
+    let kernel = create_kernel(vec![
+        root(),
+        modele!("fs/file", "File" =>
+            "link": FieldTypeReference {
+                authorized_models: vec!["fs/file".into()]
+            },
+            "name": FieldTypeString {},
+            "linked_by": FieldTypeBacklinks {
+                source_model: "fs/item".into(),
+                source_field: "link".into()
+            },
+            "named_by": FieldTypeBacklinks {
+                source_model: "fs/file".into(),
+                source_field: "name".into()
+            },
+            interfaces: "fs/item"
+        ),
+    ]);
+    assert_eq!(
+        validate_kernel(&kernel),
+        vec![KernelIssue::InvalidBacklinksSource(
+            "fs/file".into(),
+            "named_by".into()
+        )]
+    );
+}
+
+#[test]
+fn it_should_report_the_slot_policies_without_subobject_field() {
+    let kernel = create_kernel(vec![
+        root(),
+        modele!("fs/file", "File" =>
+            "name": FieldTypeString {},
+            "content": FieldTypeSubobject {
+                authorized_models: vec!["fs/content".into()]
+            },
+            "previous": FieldTypeSubobjectArray {
+                authorized_models: vec!["fs/content".into()]
+            },
+            "draft": FieldTypeSubobject {
+                authorized_models: vec!["fs/content".into()]
+            },
+            interfaces: "fs/item",
+            slot_policies:
+                "content" => SlotPolicy::MoveTo("previous".into()),
+                "draft" => SlotPolicy::MoveTo("name".into())
+        ),
+        modele!("fs/content", "Content" =>
+            "value": FieldTypeString {}
+        ),
+    ]);
+    assert_eq!(
+        validate_kernel(&kernel),
+        vec![KernelIssue::InvalidSlotPolicy(
+            "fs/file".into(),
+            "draft".into()
+        )]
+    );
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+
+use crate::field_types::get_linked_ids;
+use crate::model::FieldKind;
+use crate::object_data::Id;
+use crate::{Hit, HitEntry};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraversalOrder {
+    DepthFirst,
+    BreadthFirst,
+}
+
+// An object met during a walk of the document
+pub struct TraversalEntry {
+    pub entry: HitEntry,
+    // the distance from the object where the walk started
+    pub depth: usize,
+    // the position of the object in its parent field, 0 in a single subobject field
+    pub position: usize,
+}
+
+impl TraversalEntry {
+    fn new(hit: &Hit, id: &str, depth: usize, position: usize) -> Option<TraversalEntry> {
+        Some(TraversalEntry {
+            entry: hit.get(id)?,
+            depth,
+            position,
+        })
+    }
+
+    pub fn get_id(&self) -> Id {
+        self.entry.get_id()
+    }
+}
+
+// the subobjects of an object with their position, in the order of the fields of the model
+fn get_children(hit: &Hit, id: &str, property: Option<&str>) -> Vec<(Id, usize)> {
+    let model = match hit.get_model(id) {
+        Some(model) => model,
+        None => return vec![],
+    };
+    let mut children = vec![];
+    for (name, field) in model.get_fields().iter() {
+        if property.is_some_and(|property| property != name) {
+            continue;
+        }
+        let kind = field.borrow().get_kind();
+        if kind != FieldKind::Subobject && kind != FieldKind::SubobjectArray {
+            continue;
+        }
+        let ids = get_linked_ids(hit.get_value(id, name));
+        children.extend(
+            ids.into_iter()
+                .enumerate()
+                .map(|(position, reference)| (reference.id, position)),
+        );
+    }
+    children
+}
+
+pub(crate) fn get_child_entries(
+    hit: &Hit,
+    id: &str,
+    property: Option<&str>,
+    depth: usize,
+) -> Vec<TraversalEntry> {
+    get_children(hit, id, property)
+        .iter()
+        .filter_map(|(child, position)| TraversalEntry::new(hit, child, depth, *position))
+        .collect()
+}
+
+// The descendants of an object, without the object itself.
+// `prune` skips the subobjects of the last returned object.
+pub struct Descendants<'a> {
+    hit: &'a Hit,
+    order: TraversalOrder,
+    // a stack in depth first order, a queue in breadth first order
+    pending: VecDeque<(Id, usize, usize)>,
+    // the last returned object, whose subobjects are added on the next call
+    last: Option<(Id, usize)>,
+}
+
+impl<'a> Descendants<'a> {
+    pub(crate) fn new(hit: &'a Hit, id: &str, order: TraversalOrder) -> Descendants<'a> {
+        Descendants {
+            hit,
+            order,
+            pending: VecDeque::new(),
+            last: Some((id.to_string(), 0)),
+        }
+    }
+
+    pub fn prune(&mut self) {
+        self.last = None;
+    }
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = TraversalEntry;
+
+    fn next(&mut self) -> Option<TraversalEntry> {
+        if let Some((id, depth)) = self.last.take() {
+            let children = get_children(self.hit, &id, None)
+                .into_iter()
+                .map(|(child, position)| (child, depth + 1, position));
+            match self.order {
+                TraversalOrder::DepthFirst => {
+                    for child in children.rev() {
+                        self.pending.push_front(child);
+                    }
+                }
+                TraversalOrder::BreadthFirst => self.pending.extend(children),
+            }
+        }
+        while let Some((id, depth, position)) = self.pending.pop_front() {
+            if let Some(entry) = TraversalEntry::new(self.hit, &id, depth, position) {
+                self.last = Some((id, depth));
+                return Some(entry);
+            }
+        }
+        None
+    }
+}
+
+// The parent of an object, then its parent, up to the main object
+pub struct Ancestors<'a> {
+    hit: &'a Hit,
+    current: Option<Id>,
+    depth: usize,
+}
+
+impl<'a> Ancestors<'a> {
+    pub(crate) fn new(hit: &'a Hit, id: &str) -> Ancestors<'a> {
+        Ancestors {
+            hit,
+            current: Some(id.to_string()),
+            depth: 0,
+        }
+    }
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = TraversalEntry;
+
+    fn next(&mut self) -> Option<TraversalEntry> {
+        let current = self.current.take()?;
+        let parent = self.hit.get_parent(&current)?;
+        self.depth += 1;
+        let position = self.hit.get_parent_index(&parent.id).unwrap_or(0);
+        let entry = TraversalEntry::new(self.hit, &parent.id, self.depth, position)?;
+        self.current = Some(parent.id);
+        Some(entry)
+    }
+}
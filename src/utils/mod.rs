@@ -1,5 +1,9 @@
 mod kernel_init;
 mod model_property_vectors;
+mod undo_log;
+mod validate_kernel;
 
 pub use kernel_init::kernel_init;
 pub use model_property_vectors::ModelPropertyVectors;
+pub(crate) use undo_log::UndoLog;
+pub use validate_kernel::{validate_kernel, KernelIssue};
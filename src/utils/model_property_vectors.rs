@@ -65,6 +65,15 @@ impl<T: PartialEq> ModelPropertyVectors<T> {
         }
     } */
 
+    // the vectors, with their id and property
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String, &Vec<T>)> {
+        self.entry_map.iter().flat_map(|(id, properties)| {
+            properties
+                .iter()
+                .map(move |(property, vector)| (id, property, vector))
+        })
+    }
+
     pub fn delete(&mut self, id: &str, property: &str) {
         let property_map = self.get_property_map_mut(id);
         match property_map {
@@ -0,0 +1,72 @@
+// The changes made to the state of a plugin during the transactions, so that a rollback
+// only reverts these changes instead of restoring a copy of the whole state.
+// Transactions can be nested : each begin call marks the position of the log.
+#[derive(Clone, Debug)]
+pub(crate) struct UndoLog<T> {
+    changes: Vec<T>,
+    marks: Vec<usize>,
+}
+
+impl<T> UndoLog<T> {
+    pub fn new() -> Self {
+        UndoLog {
+            changes: vec![],
+            marks: vec![],
+        }
+    }
+
+    // true while a transaction is running, to skip building the changes otherwise
+    pub fn is_recording(&self) -> bool {
+        !self.marks.is_empty()
+    }
+
+    // the changes are only kept while a transaction is running
+    pub fn record(&mut self, change: T) {
+        if !self.marks.is_empty() {
+            self.changes.push(change);
+        }
+    }
+
+    pub fn begin(&mut self) {
+        self.marks.push(self.changes.len());
+    }
+
+    // the changes stay in the log until the outermost transaction is committed,
+    // an outer transaction can still roll them back
+    pub fn commit(&mut self) {
+        self.marks.pop();
+        if self.marks.is_empty() {
+            self.changes.clear();
+        }
+    }
+
+    // the changes of the transaction, the most recent first
+    pub fn rollback(&mut self) -> Vec<T> {
+        let mark = self.marks.pop().unwrap_or(0);
+        let mut changes = self.changes.split_off(mark);
+        changes.reverse();
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UndoLog;
+
+    #[test]
+    fn it_should_return_the_changes_of_the_rolled_back_transaction() {
+        let mut log = UndoLog::new();
+        log.record(0);
+        log.begin();
+        log.record(1);
+        log.begin();
+        log.record(2);
+        log.record(3);
+        assert_eq!(log.rollback(), vec![3, 2]);
+        log.begin();
+        log.record(4);
+        log.commit();
+        assert_eq!(log.rollback(), vec![4, 1]);
+        assert_eq!(log.rollback(), Vec::<i32>::new());
+    }
+}
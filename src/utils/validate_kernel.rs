@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use crate::field_types::{FieldTypeBacklinks, FieldTypeSubobject, SlotPolicy};
+use crate::{FieldDescription, FieldKind, Kernel, Model};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum KernelIssue {
+    // model, field, name : the name is neither a model nor an interface implemented by a model
+    UnknownAuthorizedModel(String, String, String),
+    // model, interface : the interface has the name of a model
+    InterfaceIsAModel(String, String),
+    // model, interface
+    DuplicateInterface(String, String),
+    // the model is not abstract, not instantiable and cannot be added as a subobject of a
+    // reachable model
+    UnreachableModel(String),
+    // model, field : none of the authorized models can be part of a document
+    UnsatisfiableReference(String, String),
+    // model, field : the required subobject cannot be created, because it always
+    // requires, directly or not, an object of the same model
+    CyclicRequiredSubobject(String, String),
+    // model, field : the source models of the backlinks have no reference field with the
+    // name of the source field
+    InvalidBacklinksSource(String, String),
+    // model, field : the slot policy moves the object to a field that is not a subobject
+    // field of the model
+    InvalidSlotPolicy(String, String),
+}
+
+fn authorizes(description: &FieldDescription, model: &Model) -> bool {
+    match &description.authorized_models {
+        Some(authorized_models) => authorized_models
+            .iter()
+            .any(|name| !model.is_abstract && model.is_a(name)),
+        None => false,
+    }
+}
+
+fn is_subobject_field(description: &FieldDescription) -> bool {
+    description.kind == FieldKind::Subobject || description.kind == FieldKind::SubobjectArray
+}
+
+fn is_reference_field(description: &FieldDescription) -> bool {
+    description.kind == FieldKind::Reference || description.kind == FieldKind::ReferenceArray
+}
+
+// the models that can be the root of a document, and the models that can be added in them
+fn get_reachable_models(kernel: &dyn Kernel, models: &[Rc<Model>]) -> HashSet<String> {
+    let mut reachable: HashSet<String> = HashSet::new();
+    let mut to_visit: Vec<String> = kernel
+        .get_instantiable_models()
+        .iter()
+        .map(|model| model.get_name().clone())
+        .collect();
+    while let Some(name) = to_visit.pop() {
+        if !reachable.insert(name.clone()) {
+            continue;
+        }
+        let model = match models.iter().find(|model| *model.get_name() == name) {
+            Some(model) => model,
+            None => continue,
+        };
+        for field in model.fields.values() {
+            let description = field.borrow().describe();
+            if !is_subobject_field(&description) {
+                continue;
+            }
+            for child in models.iter() {
+                if authorizes(&description, child) && !reachable.contains(child.get_name()) {
+                    to_visit.push(child.get_name().clone());
+                }
+            }
+        }
+    }
+    reachable
+}
+
+// the models whose required subobjects can all be created
+fn get_constructible_models(models: &[Rc<Model>]) -> HashSet<String> {
+    let mut constructible: HashSet<String> = HashSet::new();
+    loop {
+        let mut changed = false;
+        for model in models.iter() {
+            if constructible.contains(model.get_name()) {
+                continue;
+            }
+            let is_constructible = model.fields.values().all(|field| {
+                let description = field.borrow().describe();
+                description.kind != FieldKind::Subobject
+                    || !description.required
+                    || models.iter().any(|child| {
+                        constructible.contains(child.get_name()) && authorizes(&description, child)
+                    })
+            });
+            if is_constructible {
+                constructible.insert(model.get_name().clone());
+                changed = true;
+            }
+        }
+        if !changed {
+            return constructible;
+        }
+    }
+}
+
+// whether one of the source models references other objects through the source field
+fn has_backlinks_source(backlinks: &FieldTypeBacklinks, models: &[Rc<Model>]) -> bool {
+    models.iter().any(|model| {
+        model.is_a(&backlinks.source_model)
+            && match model.get_field(&backlinks.source_field) {
+                Some(field) => is_reference_field(&field.borrow().describe()),
+                None => false,
+            }
+    })
+}
+
+// whether the object replaced in a single subobject field can be moved where the policy says
+fn has_valid_slot_policy(field_name: &str, model: &Model) -> bool {
+    match model.get_slot_policy(field_name) {
+        Some(SlotPolicy::MoveTo(property)) => match model.get_field(&property) {
+            Some(field) => is_subobject_field(&field.borrow().describe()),
+            None => false,
+        },
+        _ => true,
+    }
+}
+
+// Looks for the inconsistencies of the models of a kernel, that would only be noticed
+// when the documents are edited
+pub fn validate_kernel(kernel: &dyn Kernel) -> Vec<KernelIssue> {
+    let models: Vec<Rc<Model>> = kernel
+        .get_models()
+        .iter()
+        .filter_map(|name| kernel.get_model(name).ok())
+        .collect();
+    let model_names: HashSet<&String> = models.iter().map(|model| model.get_name()).collect();
+    let interfaces: HashSet<&String> = models
+        .iter()
+        .flat_map(|model| model.interfaces.iter())
+        .collect();
+    let reachable = get_reachable_models(kernel, &models);
+    let constructible = get_constructible_models(&models);
+
+    let mut issues = vec![];
+    for model in models.iter() {
+        let name = model.get_name();
+        let mut declared: HashSet<&String> = HashSet::new();
+        for interface in model.interfaces.iter() {
+            if model_names.contains(interface) {
+                issues.push(KernelIssue::InterfaceIsAModel(name.clone(), interface.clone()));
+            }
+            if !declared.insert(interface) {
+                issues.push(KernelIssue::DuplicateInterface(name.clone(), interface.clone()));
+            }
+        }
+        if !model.is_abstract && !reachable.contains(name) {
+            issues.push(KernelIssue::UnreachableModel(name.clone()));
+        }
+
+        for (field_name, field) in model.fields.iter() {
+            let description = field.borrow().describe();
+            for authorized_model in description.authorized_models.iter().flatten() {
+                if !model_names.contains(authorized_model) && !interfaces.contains(authorized_model) {
+                    issues.push(KernelIssue::UnknownAuthorizedModel(
+                        name.clone(),
+                        field_name.clone(),
+                        authorized_model.clone(),
+                    ));
+                }
+            }
+            if is_reference_field(&description)
+                && !models.iter().any(|target| {
+                    reachable.contains(target.get_name()) && authorizes(&description, target)
+                })
+            {
+                issues.push(KernelIssue::UnsatisfiableReference(
+                    name.clone(),
+                    field_name.clone(),
+                ));
+            }
+            if description.kind == FieldKind::Subobject
+                && description.required
+                && !constructible.contains(name)
+                && !models.iter().any(|child| {
+                    constructible.contains(child.get_name()) && authorizes(&description, child)
+                })
+            {
+                issues.push(KernelIssue::CyclicRequiredSubobject(
+                    name.clone(),
+                    field_name.clone(),
+                ));
+            }
+            let is_invalid_backlinks = match field.borrow().downcast_ref::<FieldTypeBacklinks>() {
+                Some(backlinks) => !has_backlinks_source(backlinks, &models),
+                None => false,
+            };
+            if is_invalid_backlinks {
+                issues.push(KernelIssue::InvalidBacklinksSource(
+                    name.clone(),
+                    field_name.clone(),
+                ));
+            }
+            let is_invalid_slot_policy = field.borrow().is::<FieldTypeSubobject>()
+                && !has_valid_slot_policy(field_name, model);
+            if is_invalid_slot_policy {
+                issues.push(KernelIssue::InvalidSlotPolicy(
+                    name.clone(),
+                    field_name.clone(),
+                ));
+            }
+        }
+    }
+    issues
+}